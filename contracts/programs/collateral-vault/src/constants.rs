@@ -1,4 +1,5 @@
 // programs/collateral-vault/src/constants.rs
+use anchor_lang::prelude::*;
 
 /// Minimum collateral amount (1 token with 9 decimals)
 pub const MIN_COLLATERAL_AMOUNT: u64 = 1_000_000_000;
@@ -7,4 +8,11 @@ pub const MIN_COLLATERAL_AMOUNT: u64 = 1_000_000_000;
 pub const LIQUIDATION_THRESHOLD: u16 = 90;
 
 /// Warning threshold (1.2x = 120)
-pub const WARNING_THRESHOLD: u16 = 120;
\ No newline at end of file
+pub const WARNING_THRESHOLD: u16 = 120;
+
+/// lending-pool program, owns the protocol roles PDA
+pub const LENDING_POOL_PROGRAM_ID: Pubkey = pubkey!("8EJ5aeJVwQuZjftHywYjA1KUVGjaKwLYwUTJE8iLQbhr");
+
+/// Layout version written into every account this program creates,
+/// bumped whenever an account layout changes (see the migrate_* instructions)
+pub const ACCOUNT_VERSION: u8 = 1;
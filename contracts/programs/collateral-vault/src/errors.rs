@@ -29,4 +29,22 @@ pub enum VaultError {
     
     #[msg("Arithmetic underflow")]
     ArithmeticUnderflow,
+
+    #[msg("Proposed authority must differ from the current one and not be empty")]
+    InvalidPendingAuthority,
+
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+
+    #[msg("Only the pending authority can accept the transfer")]
+    UnauthorizedPendingAuthority,
+
+    #[msg("Signer does not hold the role required for this action")]
+    MissingRole,
+
+    #[msg("Account is not a migratable account of this type")]
+    InvalidAccountLayout,
+
+    #[msg("Account already uses the current layout version")]
+    AccountAlreadyMigrated,
 }
\ No newline at end of file
@@ -41,3 +41,38 @@ pub struct  CollateralLiquidated{
     pub health_factor : u16,
     pub timestamp : i64
 }
+
+#[event]
+
+pub struct  AuthorityTransferProposed{
+    pub vault : Pubkey,
+    pub authority : Pubkey,
+    pub pending_authority : Pubkey,
+    pub timestamp : i64
+}
+
+#[event]
+
+pub struct  AuthorityTransferCancelled{
+    pub vault : Pubkey,
+    pub authority : Pubkey,
+    pub cancelled_authority : Pubkey,
+    pub timestamp : i64
+}
+
+#[event]
+
+pub struct  AuthorityTransferred{
+    pub vault : Pubkey,
+    pub old_authority : Pubkey,
+    pub new_authority : Pubkey,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::VaultError, events::{AuthorityTransferCancelled, AuthorityTransferProposed, AuthorityTransferred}, state::Vault};
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX],
+        bump = vault.bump,
+        constraint = vault.pending_authority != Pubkey::default() @ VaultError::NoPendingAuthority,
+        constraint = vault.pending_authority == new_authority.key() @ VaultError::UnauthorizedPendingAuthority
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [Vault::SEED_PREFIX],
+        bump = vault.bump,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+pub fn propose_handler(
+    ctx:Context<ProposeAuthority>,
+    new_authority : Pubkey
+)->Result<()>{
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    require!(
+        new_authority != Pubkey::default() && new_authority != vault.authority,
+        VaultError::InvalidPendingAuthority
+    );
+
+    vault.pending_authority = new_authority;
+
+    emit!(AuthorityTransferProposed {
+        vault: vault.key(),
+        authority: vault.authority,
+        pending_authority: new_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Vault authority transfer proposed to {}", new_authority);
+
+    Ok(())
+}
+
+pub fn accept_handler(
+    ctx:Context<AcceptAuthority>
+)->Result<()>{
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    let old_authority = vault.authority;
+
+    vault.authority = vault.pending_authority;
+    vault.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferred {
+        vault: vault.key(),
+        old_authority,
+        new_authority: vault.authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Vault authority transferred to {}", vault.authority);
+
+    Ok(())
+}
+
+pub fn cancel_handler(
+    ctx:Context<CancelAuthorityTransfer>
+)->Result<()>{
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    require!(vault.pending_authority != Pubkey::default(), VaultError::NoPendingAuthority);
+
+    let cancelled_authority = vault.pending_authority;
+
+    vault.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferCancelled {
+        vault: vault.key(),
+        authority: vault.authority,
+        cancelled_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Vault authority transfer cancelled");
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{events::VaultInitialize, state::Vault};
+use crate::{constants::ACCOUNT_VERSION, events::VaultInitialize, state::Vault};
 
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
@@ -31,6 +31,7 @@ pub fn handler(
     let clock = Clock::get()?;
 
     vault.authority = ctx.accounts.authority.key();
+    vault.pending_authority = Pubkey::default();
     vault.collateral_mint = collateral_mint;
     vault.vault_token_account = ctx.accounts.vault_token_account.key();
     vault.total_locked = 0;
@@ -40,6 +41,7 @@ pub fn handler(
     vault.active_locks_count = 0;
     vault.total_locks_count=0;
     vault.created_at = clock.unix_timestamp;
+    vault.version = ACCOUNT_VERSION;
     vault.bump = ctx.bumps.vault;
 
     emit!(VaultInitialize {
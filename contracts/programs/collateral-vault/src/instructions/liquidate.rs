@@ -1,22 +1,45 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{self, Token, TokenAccount, Transfer}, token_2022::spl_token_2022::extension::memo_transfer::instruction::RequiredMemoTransfersInstruction};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
-use crate::{constants::LIQUIDATION_THRESHOLD, errors::VaultError, events::CollateralLiquidated, state::{CollateralLock, LockStatus, Vault}};
+use lending_pool::states::{ProtocolRoles, Role};
+
+use crate::{constants::{LENDING_POOL_PROGRAM_ID, LIQUIDATION_THRESHOLD}, errors::VaultError, events::CollateralLiquidated, state::{CollateralLock, LockStatus, Vault}};
 
 #[derive(Accounts)]
 
 pub struct LiquidateCollateral<'info>{
 
-    #[account(mut)]
+    pub keeper : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+        constraint = roles.has_role(Role::LiquidatorKeeper, &keeper.key()) @ VaultError::MissingRole
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        constraint = collateral_lock.vault == vault.key() @ VaultError::LockNotFound
+    )]
     pub collateral_lock : Account<'info,CollateralLock>,
     #[account(mut)]
     pub vault : Account<'info,Vault>,
+    #[account(
+        mut,
+        constraint = lender_collateral_account.owner == collateral_lock.lender @ VaultError::Unauthorized
+    )]
+    pub lender_collateral_account : InterfaceAccount<'info,TokenAccount>,
     #[account(mut)]
-    pub lender_collateral_account : Account<'info,TokenAccount>,
-    #[account(mut)]
-    pub vault_token_accont : Account<'info,TokenAccount>,
+    pub vault_token_accont : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        constraint = collateral_mint.key() == vault.collateral_mint @ VaultError::InvalidMint
+    )]
+    pub collateral_mint : InterfaceAccount<'info,Mint>,
 
-    pub token_program : Program<'info,Token>
+    pub token_program : Interface<'info,TokenInterface>
 }
 
 pub fn handler(
@@ -39,18 +62,20 @@ pub fn handler(
     let seeds = &[Vault::SEED_PREFIX, &[vault_bump]];
     let signer_seeds = &[&seeds[..]];
     
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.vault_token_accont.to_account_info(),
+        mint: ctx.accounts.collateral_mint.to_account_info(),
         to: ctx.accounts.lender_collateral_account.to_account_info(),
         authority: vault.to_account_info(),
     };
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             cpi_accounts,
             signer_seeds
         ),
-        lock.amount
+        lock.amount,
+        ctx.accounts.collateral_mint.decimals
     )?;
     
     // Update lock status
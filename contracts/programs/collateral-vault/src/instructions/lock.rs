@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
-use crate::{constants::MIN_COLLATERAL_AMOUNT, errors::VaultError, events::CollateralLocked, state::{CollateralLock, Vault}};
+use crate::{constants::{ACCOUNT_VERSION, MIN_COLLATERAL_AMOUNT}, errors::VaultError, events::CollateralLocked, state::{CollateralLock, Vault}};
 
 
 #[derive(Accounts)]
@@ -27,12 +27,17 @@ pub struct LockCollateral<'info>{
 
 
     #[account(mut)]
-    pub borrower_collateral_account : Account<'info,TokenAccount>,
+    pub borrower_collateral_account : InterfaceAccount<'info,TokenAccount>,
 
     #[account(mut)]
-    pub vault_token_account : Account<'info,TokenAccount>,
+    pub vault_token_account : InterfaceAccount<'info,TokenAccount>,
 
-    pub token_program : Program<'info,Token>,
+    #[account(
+        constraint = collateral_mint.key() == vault.collateral_mint @ VaultError::InvalidMint
+    )]
+    pub collateral_mint : InterfaceAccount<'info,Mint>,
+
+    pub token_program : Interface<'info,TokenInterface>,
 
     pub system_program : Program<'info,System>
 
@@ -56,15 +61,25 @@ pub fn handler(
 
     // Transfer Collateral to Vault 
 
-    let cpi_account = Transfer{
+    let balance_before = ctx.accounts.vault_token_account.amount;
+
+    let cpi_account = TransferChecked{
         from    : ctx.accounts.borrower_collateral_account.to_account_info(),
+        mint : ctx.accounts.collateral_mint.to_account_info(),
         to   : ctx.accounts.vault_token_account.to_account_info(),
         authority : ctx.accounts.borrower.to_account_info()
     };
 
     let cpi_ctx =  CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account);
 
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.collateral_mint.decimals)?;
+
+    // transfer-fee mints deliver less than was sent, lock what the vault actually holds
+    ctx.accounts.vault_token_account.reload()?;
+
+    let received = ctx.accounts.vault_token_account.amount
+        .checked_sub(balance_before)
+        .ok_or(error!(VaultError::ArithmeticUnderflow))?;
 
     //  Initalize Lock 
 
@@ -72,28 +87,29 @@ pub fn handler(
     lock.borrower = ctx.accounts.borrower.key();
     lock.lender = ctx.accounts.lender.key();
     lock.vault = vault_key;
-    lock.amount = amount;
+    lock.amount = received;
     lock.commitment = collateral_commitment;
     lock.status = crate::state::LockStatus::Locked;
     lock.locked_at = clock.unix_timestamp;
     lock.released_at = 0;
     lock.initial_health_factor=100;
     lock.current_health_factor =100;
+    lock.version = ACCOUNT_VERSION;
     lock.bump = ctx.bumps.collateral_lock;
 
     // Update Vault State 
 
-    vault.total_locked = vault.total_locked.checked_add(amount).ok_or(error!(VaultError::ArithmeticOverflow))?;
-    vault.total_locked_all_time = vault.total_locked_all_time.checked_add(amount).unwrap();
+    vault.total_locked = vault.total_locked.checked_add(received).ok_or(error!(VaultError::ArithmeticOverflow))?;
+    vault.total_locked_all_time = vault.total_locked_all_time.checked_add(received).unwrap();
     vault.active_locks_count = vault.active_locks_count.checked_add(1).unwrap();
     vault.total_locks_count = vault.total_locks_count.checked_add(1).unwrap();
     emit!(CollateralLocked {
         lock_id: lock.key(),
         loan_id,
         borrower: lock.borrower,
-        amount,
+        amount: received,
         timestamp: clock.unix_timestamp,
     });
-    msg!("Collateral locked: {}", amount);
+    msg!("Collateral locked: {}", received);
     Ok(())
 }
\ No newline at end of file
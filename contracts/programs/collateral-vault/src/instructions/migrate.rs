@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use lending_pool::migration::{migrate_account, Migrate};
+
+use crate::{constants::ACCOUNT_VERSION, errors::VaultError, events::AccountMigrated, state::{CollateralLock, LockStatus, Vault}};
+
+// permissionless: the new layout is derived from the account's own data only
+#[derive(Accounts)]
+pub struct MigrateAccount<'info>{
+    // tops up the rent for the larger layout
+    #[account(mut)]
+    pub payer : Signer<'info>,
+
+    /// CHECK: baseline layouts do not deserialize as the current one, discriminator and size are checked in migrate
+    #[account(
+        mut,
+        owner = crate::ID @ VaultError::InvalidAccountLayout
+    )]
+    pub account : UncheckedAccount<'info>,
+
+    pub system_program : Program<'info,System>,
+}
+
+/// Vault as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct VaultV0{
+    pub authority : Pubkey,
+    pub collateral_mint :Pubkey,
+    pub vault_token_account : Pubkey,
+    pub total_locked : u64,
+    pub total_locked_all_time : u64,
+    pub total_released : u64,
+    pub total_liquidated : u64,
+    pub active_locks_count : u32,
+    pub total_locks_count : u32,
+    pub created_at : i64,
+    pub bump : u8
+}
+
+impl Migrate for Vault {
+    type V0 = VaultV0;
+    const V0_SPACE: usize = 161;
+    const SPACE: usize = Vault::LEN;
+
+    fn from_v0(old: VaultV0, _now: i64) -> Result<Self> {
+        Ok(Vault{
+            version: ACCOUNT_VERSION,
+            authority: old.authority,
+            pending_authority: Pubkey::default(),
+            collateral_mint: old.collateral_mint,
+            vault_token_account: old.vault_token_account,
+            total_locked: old.total_locked,
+            total_locked_all_time: old.total_locked_all_time,
+            total_released: old.total_released,
+            total_liquidated: old.total_liquidated,
+            active_locks_count: old.active_locks_count,
+            total_locks_count: old.total_locks_count,
+            created_at: old.created_at,
+            bump: old.bump,
+        })
+    }
+}
+
+/// CollateralLock as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct CollateralLockV0{
+    pub loan_id : u64,
+    pub borrower : Pubkey,
+    pub lender : Pubkey,
+    pub vault : Pubkey,
+    pub amount : u64,
+    pub commitment : [u8;32],
+    pub status : LockStatus,
+    pub locked_at : i64,
+    pub released_at : i64,
+    pub initial_health_factor : u16,
+    pub current_health_factor : u16,
+    pub bump : u8
+}
+
+impl Migrate for CollateralLock {
+    type V0 = CollateralLockV0;
+    const V0_SPACE: usize = 175;
+    const SPACE: usize = CollateralLock::LEN;
+
+    fn from_v0(old: CollateralLockV0, _now: i64) -> Result<Self> {
+        Ok(CollateralLock{
+            version: ACCOUNT_VERSION,
+            loan_id: old.loan_id,
+            borrower: old.borrower,
+            lender: old.lender,
+            vault: old.vault,
+            amount: old.amount,
+            commitment: old.commitment,
+            status: old.status,
+            locked_at: old.locked_at,
+            released_at: old.released_at,
+            initial_health_factor: old.initial_health_factor,
+            current_health_factor: old.current_health_factor,
+            bump: old.bump,
+        })
+    }
+}
+
+/// Upgrade a baseline account of type T to the current layout in place
+pub fn migrate<T: Migrate>(ctx:Context<MigrateAccount>)->Result<()>{
+    let clock = Clock::get()?;
+    let account = ctx.accounts.account.to_account_info();
+
+    migrate_account::<T>(
+        &ctx.accounts.payer.to_account_info(),
+        &account,
+        &ctx.accounts.system_program.to_account_info(),
+        clock.unix_timestamp,
+        VaultError::InvalidAccountLayout.into(),
+        VaultError::AccountAlreadyMigrated.into(),
+    )?;
+
+    emit!(AccountMigrated{
+        account: account.key(),
+        from_version: 0,
+        to_version: ACCOUNT_VERSION,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Account migrated: v0 -> v{}", ACCOUNT_VERSION);
+    Ok(())
+}
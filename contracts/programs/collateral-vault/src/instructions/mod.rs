@@ -4,9 +4,13 @@ pub mod lock;
 pub mod release;
 pub mod liquidate;
 pub mod stats;
+pub mod authority;
+pub mod migrate;
 
 pub use initialize::*;
 pub use lock::*;
 pub use release::*;
 pub use liquidate::*;
-pub use stats::*;
\ No newline at end of file
+pub use stats::*;
+pub use authority::*;
+pub use migrate::*;
\ No newline at end of file
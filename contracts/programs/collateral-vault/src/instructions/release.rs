@@ -1,26 +1,50 @@
 
 use anchor_lang::{prelude::*};
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
-use crate::{errors::VaultError, events::CollateralReleased, state::{CollateralLock, Vault}};
+use lending_pool::states::{ProtocolRoles, Role};
+
+use crate::{constants::LENDING_POOL_PROGRAM_ID, errors::VaultError, events::CollateralReleased, state::{CollateralLock, Vault}};
 
 
 #[derive(Accounts)]
 
 pub struct ReleaseCollateral<'info>{
 
-    #[account(mut)]
+    // health monitor service releases collateral once the loan is repaid
+    pub monitor : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+        constraint = roles.has_role(Role::HealthMonitor, &monitor.key()) @ VaultError::MissingRole
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        constraint = collateral_lock.vault == vault.key() @ VaultError::LockNotFound
+    )]
     pub collateral_lock : Account<'info,CollateralLock>,
 
     #[account(mut)]
     pub vault : Account<'info,Vault>,
 
+    #[account(
+        mut,
+        constraint = borrower_collateral_account.owner == collateral_lock.borrower @ VaultError::Unauthorized
+    )]
+    pub borrower_collateral_account :InterfaceAccount<'info,TokenAccount>,
     #[account(mut)]
-    pub borrower_collateral_account :Account<'info,TokenAccount>,
-    #[account(mut)]
-    pub vault_token_account : Account<'info,TokenAccount>,
+    pub vault_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        constraint = collateral_mint.key() == vault.collateral_mint @ VaultError::InvalidMint
+    )]
+    pub collateral_mint : InterfaceAccount<'info,Mint>,
 
-    pub token_program  :  Program<'info,Token>
+    pub token_program  :  Interface<'info,TokenInterface>
 }
 
 pub fn handler(ctx:Context<ReleaseCollateral>,loan_id : u64)->Result<()>{
@@ -38,15 +62,16 @@ pub fn handler(ctx:Context<ReleaseCollateral>,loan_id : u64)->Result<()>{
 
     let signer_seeds = &[&seeds[..]];
 
-    let cpi_account = Transfer{
+    let cpi_account = TransferChecked{
         from : ctx.accounts.vault_token_account.to_account_info(),
+        mint : ctx.accounts.collateral_mint.to_account_info(),
         to : ctx.accounts.borrower_collateral_account.to_account_info(),
         authority : vault.to_account_info()
     } ;
 
     let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds);
 
-    token::transfer(cpi_ctx, lock.amount)?;
+    token_interface::transfer_checked(cpi_ctx, lock.amount, ctx.accounts.collateral_mint.decimals)?;
 
     lock.status = crate::state::LockStatus::Released;
     lock.released_at = clock.unix_timestamp;
@@ -54,4 +54,35 @@ pub mod collateral_vault {
     ) -> Result<VaultStats> {
         instructions::stats::handler(ctx)
     }
+
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::authority::propose_handler(ctx, new_authority)
+    }
+
+    pub fn accept_authority(
+        ctx: Context<AcceptAuthority>,
+    ) -> Result<()> {
+        instructions::authority::accept_handler(ctx)
+    }
+
+    pub fn cancel_authority_transfer(
+        ctx: Context<CancelAuthorityTransfer>,
+    ) -> Result<()> {
+        instructions::authority::cancel_handler(ctx)
+    }
+
+    pub fn migrate_vault(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<Vault>(ctx)
+    }
+
+    pub fn migrate_collateral_lock(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<CollateralLock>(ctx)
+    }
 }
\ No newline at end of file
@@ -5,7 +5,12 @@ use anchor_lang::prelude::*;
 #[account]
 
 pub struct  Vault{
+    // layout version
+    pub version : u8,
+
     pub authority : Pubkey,
+    // proposed new authority, takes over only once it accepts (default = none)
+    pub pending_authority : Pubkey,
     pub collateral_mint :Pubkey,
     pub vault_token_account : Pubkey,
     pub total_locked : u64,
@@ -19,7 +24,7 @@ pub struct  Vault{
 }
 
 impl Vault {
-    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 4 + 4 + 8 + 1;
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 4 + 4 + 8 + 1;
     pub const SEED_PREFIX: &'static [u8] = b"vault";
 }
 
@@ -33,6 +38,9 @@ pub enum  LockStatus {
 #[account]
 
 pub struct  CollateralLock{ 
+    // layout version
+    pub version : u8,
+
     pub loan_id : u64,
     pub borrower : Pubkey,
     pub lender : Pubkey,
@@ -49,7 +57,7 @@ pub struct  CollateralLock{
 
 
 impl CollateralLock {
-    pub const LEN: usize = 8 + 8 + 32 + 32 + 32 + 8 + 32 + 2 + 8 + 8 + 2 + 2 + 1;
+    pub const LEN: usize = 8 + 1 + 8 + 32 + 32 + 32 + 8 + 32 + 2 + 8 + 8 + 2 + 2 + 1;
     pub const SEED_PREFIX: &'static [u8] = b"collateral_lock";
     
     pub fn is_active(&self) -> bool {
@@ -20,4 +20,8 @@ pub const COLLATERAL_EXCELLENT: u16 = 5000;  // 50%
 pub const COLLATERAL_VERY_GOOD: u16 = 7500;  // 75%
 pub const COLLATERAL_GOOD: u16 = 10000;      // 100%
 pub const COLLATERAL_FAIR: u16 = 12500;      // 125%
-pub const COLLATERAL_POOR: u16 = 15000;      // 150%
\ No newline at end of file
+pub const COLLATERAL_POOR: u16 = 15000;      // 150%
+
+/// Layout version written into every account this program creates,
+/// bumped whenever an account layout changes (see the migrate_* instructions)
+pub const ACCOUNT_VERSION: u8 = 1;
\ No newline at end of file
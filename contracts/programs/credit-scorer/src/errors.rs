@@ -38,4 +38,19 @@ pub enum CreditScorerError {
     
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
+
+    #[msg("Proposed admin must differ from the current one and not be empty")]
+    InvalidPendingAdmin,
+
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+
+    #[msg("Only the pending admin can accept the transfer")]
+    UnauthorizedPendingAdmin,
+
+    #[msg("Account is not a migratable account of this type")]
+    InvalidAccountLayout,
+
+    #[msg("Account already uses the current layout version")]
+    AccountAlreadyMigrated,
 }
\ No newline at end of file
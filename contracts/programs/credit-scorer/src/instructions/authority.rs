@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::CreditScorerError, events::{AdminTransferCancelled, AdminTransferProposed, AdminTransferred}, state::ScoringConfig};
+
+
+#[derive(Accounts)]
+
+pub struct ProposeAdmin<'info>{
+    pub admin : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ScoringConfig::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ CreditScorerError::UnauthorizedAdmin
+    )]
+    pub config : Account<'info,ScoringConfig>,
+}
+
+#[derive(Accounts)]
+
+pub struct AcceptAdmin<'info>{
+    pub new_admin : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ScoringConfig::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.pending_admin != Pubkey::default() @ CreditScorerError::NoPendingAdmin,
+        constraint = config.pending_admin == new_admin.key() @ CreditScorerError::UnauthorizedPendingAdmin
+    )]
+    pub config : Account<'info,ScoringConfig>,
+}
+
+#[derive(Accounts)]
+
+pub struct CancelAdminTransfer<'info>{
+    pub admin : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ScoringConfig::SEED_PREFIX],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ CreditScorerError::UnauthorizedAdmin
+    )]
+    pub config : Account<'info,ScoringConfig>,
+}
+
+
+pub fn propose_handler(
+    ctx:Context<ProposeAdmin>,
+    new_admin : Pubkey
+)->Result<()>{
+    let config = &mut ctx.accounts.config;
+    let clock = Clock::get()?;
+
+    require!(
+        new_admin != Pubkey::default() && new_admin != config.admin,
+        CreditScorerError::InvalidPendingAdmin
+    );
+
+    config.pending_admin = new_admin;
+
+    emit!(AdminTransferProposed {
+        config: config.key(),
+        admin: config.admin,
+        pending_admin: new_admin,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Admin transfer proposed");
+    msg!("Pending admin: {}", new_admin);
+
+    Ok(())
+}
+
+pub fn accept_handler(
+    ctx:Context<AcceptAdmin>
+)->Result<()>{
+    let config = &mut ctx.accounts.config;
+    let clock = Clock::get()?;
+
+    let old_admin = config.admin;
+
+    config.admin = config.pending_admin;
+    config.pending_admin = Pubkey::default();
+
+    emit!(AdminTransferred {
+        config: config.key(),
+        old_admin,
+        new_admin: config.admin,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Admin transfer accepted");
+    msg!("New admin: {}", config.admin);
+
+    Ok(())
+}
+
+pub fn cancel_handler(
+    ctx:Context<CancelAdminTransfer>
+)->Result<()>{
+    let config = &mut ctx.accounts.config;
+    let clock = Clock::get()?;
+
+    require!(config.pending_admin != Pubkey::default(), CreditScorerError::NoPendingAdmin);
+
+    let cancelled_admin = config.pending_admin;
+
+    config.pending_admin = Pubkey::default();
+
+    emit!(AdminTransferCancelled {
+        config: config.key(),
+        admin: config.admin,
+        cancelled_admin,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Admin transfer cancelled");
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 
 use anchor_lang::prelude::*;
 
-use crate::{events::ScoringSystemInitialized, state::ScoringConfig};
+use crate::{constants::ACCOUNT_VERSION, events::ScoringSystemInitialized, state::ScoringConfig};
 
 
 #[derive(Accounts)]
@@ -32,9 +32,11 @@ pub fn handler(
 
     config.oracle_authority = oracle_authority;
     config.admin = ctx.accounts.admin.key();
+    config.pending_admin = Pubkey::default();
     config.total_score_issued = 0;
     config.total_requests = 0;
     config.created_at = clock.unix_timestamp;
+    config.version = ACCOUNT_VERSION;
     config.bump = ctx.bumps.config;
 
 
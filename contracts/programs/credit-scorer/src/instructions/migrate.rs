@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use lending_pool::migration::{migrate_account, Migrate};
+
+use crate::{constants::ACCOUNT_VERSION, errors::CreditScorerError, events::AccountMigrated, state::{CreditScore, ScoreStatus, ScoringConfig}};
+
+// permissionless: the new layout is derived from the account's own data only
+#[derive(Accounts)]
+pub struct MigrateAccount<'info>{
+    // tops up the rent for the larger layout
+    #[account(mut)]
+    pub payer : Signer<'info>,
+
+    /// CHECK: baseline layouts do not deserialize as the current one, discriminator and size are checked in migrate
+    #[account(
+        mut,
+        owner = crate::ID @ CreditScorerError::InvalidAccountLayout
+    )]
+    pub account : UncheckedAccount<'info>,
+
+    pub system_program : Program<'info,System>,
+}
+
+/// ScoringConfig as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct ScoringConfigV0 {
+    pub oracle_authority : Pubkey,
+
+    pub admin : Pubkey,
+
+    pub total_score_issued : u64,
+
+    pub total_requests : u64,
+
+    pub created_at : i64,
+
+    pub bump : u8
+}
+
+impl Migrate for ScoringConfig {
+    type V0 = ScoringConfigV0;
+    const V0_SPACE: usize = 97;
+    const SPACE: usize = ScoringConfig::LEN;
+
+    fn from_v0(old: ScoringConfigV0, _now: i64) -> Result<Self> {
+        Ok(ScoringConfig{
+            version: ACCOUNT_VERSION,
+            oracle_authority: old.oracle_authority,
+            admin: old.admin,
+            pending_admin: Pubkey::default(),
+            total_score_issued: old.total_score_issued,
+            total_requests: old.total_requests,
+            created_at: old.created_at,
+            bump: old.bump,
+        })
+    }
+}
+
+/// CreditScore as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct CreditScoreV0{
+    pub wallet : Pubkey ,
+
+    pub score : u16,
+
+    pub score_commitment : [u8;32],
+
+    pub status : ScoreStatus,
+
+    pub score_data : Vec<u8>,
+
+    pub requested_at : i64,
+
+    pub scored_at : i64,
+    pub expires_at : i64,
+
+    pub oracle_signature : [u8;64],
+
+    pub usage_count : u32,
+
+    pub bump : u8
+}
+
+impl Migrate for CreditScore {
+    type V0 = CreditScoreV0;
+    const V0_SPACE: usize = 373;
+    const SPACE: usize = CreditScore::MAX_LEN;
+
+    fn from_v0(old: CreditScoreV0, _now: i64) -> Result<Self> {
+        Ok(CreditScore{
+            version: ACCOUNT_VERSION,
+            wallet: old.wallet,
+            score: old.score,
+            score_commitment: old.score_commitment,
+            status: old.status,
+            score_data: old.score_data,
+            requested_at: old.requested_at,
+            scored_at: old.scored_at,
+            expires_at: old.expires_at,
+            oracle_signature: old.oracle_signature,
+            usage_count: old.usage_count,
+            bump: old.bump,
+        })
+    }
+}
+
+/// Upgrade a baseline account of type T to the current layout in place
+pub fn migrate<T: Migrate>(ctx:Context<MigrateAccount>)->Result<()>{
+    let clock = Clock::get()?;
+    let account = ctx.accounts.account.to_account_info();
+
+    migrate_account::<T>(
+        &ctx.accounts.payer.to_account_info(),
+        &account,
+        &ctx.accounts.system_program.to_account_info(),
+        clock.unix_timestamp,
+        CreditScorerError::InvalidAccountLayout.into(),
+        CreditScorerError::AccountAlreadyMigrated.into(),
+    )?;
+
+    emit!(AccountMigrated{
+        account: account.key(),
+        from_version: 0,
+        to_version: ACCOUNT_VERSION,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Account migrated: v0 -> v{}", ACCOUNT_VERSION);
+    Ok(())
+}
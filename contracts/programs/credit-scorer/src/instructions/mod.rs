@@ -3,8 +3,12 @@ pub mod initialize;
 pub mod request_score;
 pub mod submit_score;
 pub mod verify_proof;
+pub mod authority;
+pub mod migrate;
 
 pub use initialize::*;
 pub use request_score::*;
 pub use submit_score::*;
-pub use verify_proof::*;
\ No newline at end of file
+pub use verify_proof::*;
+pub use authority::*;
+pub use migrate::*;
\ No newline at end of file
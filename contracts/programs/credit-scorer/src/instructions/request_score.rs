@@ -2,7 +2,7 @@
 
 use anchor_lang::prelude::*;
 
-use crate::{events::ScoreRequested, state::{CreditScore, ScoringConfig}};
+use crate::{constants::ACCOUNT_VERSION, events::ScoreRequested, state::{CreditScore, ScoringConfig}};
 
 #[derive(Accounts)]
 
@@ -44,6 +44,7 @@ pub fn handler(ctx:Context<RequestScore>)->Result<()>{
     credit_score.scored_at = 0; 
     credit_score.expires_at = 0;
     credit_score.oracle_signature = [0u8;64];
+    credit_score.version = ACCOUNT_VERSION;
     credit_score.bump = ctx.bumps.credit_score;
 
     // change state the request is +1
@@ -54,5 +54,40 @@ pub mod credit_scorer {
     ) -> Result<bool> {
         instructions::verify_proof::handler(ctx, proof_data, public_inputs, threshold)
     }
-}
 
+    /// Propose a new admin, it takes over once it accepts (admin only)
+    pub fn propose_admin(
+        ctx: Context<ProposeAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        instructions::authority::propose_handler(ctx, new_admin)
+    }
+
+    /// Accept a pending admin transfer (pending admin only)
+    pub fn accept_admin(
+        ctx: Context<AcceptAdmin>,
+    ) -> Result<()> {
+        instructions::authority::accept_handler(ctx)
+    }
+
+    /// Cancel a pending admin transfer (admin only)
+    pub fn cancel_admin_transfer(
+        ctx: Context<CancelAdminTransfer>,
+    ) -> Result<()> {
+        instructions::authority::cancel_handler(ctx)
+    }
+
+    /// Upgrade a ScoringConfig account to the current layout version (permissionless, payer funds the rent)
+    pub fn migrate_scoring_config(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<ScoringConfig>(ctx)
+    }
+
+    /// Upgrade a CreditScore account to the current layout version (permissionless, payer funds the rent)
+    pub fn migrate_credit_score(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<CreditScore>(ctx)
+    }
+}
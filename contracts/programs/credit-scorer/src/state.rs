@@ -3,10 +3,16 @@ use anchor_lang::{prelude::*, solana_program::pubkey::PubkeyError};
 #[account]
 
 pub struct ScoringConfig {
+    // layout version
+    pub version : u8,
+
     pub oracle_authority : Pubkey,
 
     pub admin : Pubkey,
 
+    // proposed new admin, takes over only once it accepts (default = none)
+    pub pending_admin : Pubkey,
+
     pub total_score_issued : u64,
 
     pub total_requests : u64,
@@ -20,8 +26,10 @@ pub struct ScoringConfig {
 
 impl ScoringConfig {
     pub const LEN: usize = 8 + // discriminator
+        1 + // version
         32 + // oracle_authority
         32 + // admin
+        32 + // pending_admin
         8 + // total_scores_issued
         8 + // total_requests
         8 + // created_at
@@ -41,6 +49,9 @@ pub enum ScoreStatus {
 #[account]
 
 pub struct  CreditScore{
+    // layout version
+    pub version : u8,
+
     pub wallet : Pubkey ,
 
     pub score : u16,
@@ -66,6 +77,7 @@ pub struct  CreditScore{
 
 impl CreditScore {
     pub const MAX_LEN: usize = 8 + // discriminator
+        1 + // version
         32 + // wallet
         2 + // score
         32 + // score_commitment
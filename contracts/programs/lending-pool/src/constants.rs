@@ -2,6 +2,12 @@
 // Location: programs/lending-pool/src/constants.rs
 // Purpose: Define all constant values used across the contract
 
+use anchor_lang::prelude::*;
+
+/// Default pool limits below are in base units of a DEFAULT_CONFIG_DECIMALS mint,
+/// new pools get them rescaled to their mint's decimals (see PoolConfig)
+pub const DEFAULT_CONFIG_DECIMALS: u8 = 6;
+
 /// Minimum deposit amount (100 USDC with 6 decimals)
 /// Prevents spam deposits and dust
 pub const MIN_DEPOSIT_AMOUNT: u64 = 100_000_000; // 100 tokens (assuming 6 decimals)
@@ -24,6 +30,88 @@ pub const DEFAULT_INTEREST_RATE_BPS: u16 = 800;
 /// Seconds in a year (for interest calculations)
 pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
 
+/// Basis points divisor
+pub const BASIS_POINTS_DIVISOR: u64 = 10000;
+
+/// Fixed-point precision for the borrow index (1.0 = 1e18)
+pub const BORROW_INDEX_PRECISION: u128 = 1_000_000_000_000_000_000;
+
 /// Maximum utilization rate allowed (95%)
 /// If pool reaches this, no more loans can be approved
-pub const MAX_UTILIZATION_RATE: u64 = 95;
\ No newline at end of file
+pub const MAX_UTILIZATION_RATE: u64 = 95;
+
+/// Default kinked rate model: 2% base, +6% up to 80% utilization, +60% above it
+/// At the kink loans are priced at DEFAULT_INTEREST_RATE_BPS
+pub const DEFAULT_BASE_RATE_BPS: u16 = 200;
+pub const DEFAULT_SLOPE1_BPS: u16 = 600;
+pub const DEFAULT_KINK_UTILIZATION: u64 = 80;
+pub const DEFAULT_SLOPE2_BPS: u16 = 6000;
+
+/// Fixed-point precision for the share exchange rate (1.0 = 1_000_000_000)
+pub const SHARE_PRICE_PRECISION: u64 = 1_000_000_000;
+
+/// Default share of interest kept as protocol reserves (10%)
+pub const DEFAULT_RESERVE_FACTOR_BPS: u16 = 1000;
+
+/// Maximum reserve factor the authority can configure (50%)
+pub const MAX_RESERVE_FACTOR_BPS: u16 = 5000;
+
+/// Maximum pending requests in a pool's withdrawal queue
+pub const MAX_WITHDRAWAL_QUEUE_LENGTH: usize = 32;
+
+/// Pending requests one lender can hold in the queue, so a few lenders cannot fill it
+pub const MAX_WITHDRAWAL_REQUESTS_PER_LENDER: usize = 2;
+
+/// Queued withdrawals filled by each loan repayment, process_withdrawal_queue fills the rest
+pub const REPAYMENT_QUEUE_FILLS: u8 = 4;
+
+/// Fixed-term deposit lock-ups (days) and the multiplier on the interest
+/// earned by locked shares up to maturity, in basis points (10000 = 1.0x)
+/// The boost is paid out of protocol reserves, as far as they cover it
+pub const LOCK_TERM_SHORT_DAYS: u16 = 30;
+pub const LOCK_TERM_MEDIUM_DAYS: u16 = 90;
+pub const LOCK_TERM_LONG_DAYS: u16 = 180;
+pub const LOCK_MULTIPLIER_SHORT_BPS: u16 = 11000; // 1.10x
+pub const LOCK_MULTIPLIER_MEDIUM_BPS: u16 = 12500; // 1.25x
+pub const LOCK_MULTIPLIER_LONG_BPS: u16 = 15000; // 1.50x
+
+/// Share of a locked position burned on early unlock (2%)
+/// Burning shares without removing tokens returns the value to the pool
+pub const EARLY_UNLOCK_PENALTY_BPS: u16 = 200;
+
+/// Default cut of senior interest paid to the junior (first-loss) tranche (20%)
+pub const DEFAULT_JUNIOR_PREMIUM_BPS: u16 = 2000;
+
+/// Maximum junior premium the authority can configure (50%)
+pub const MAX_JUNIOR_PREMIUM_BPS: u16 = 5000;
+
+/// Maximum markets (pools) the registry can hold
+pub const MAX_REGISTERED_MARKETS: usize = 32;
+
+/// Maximum role grants (role, member pairs) the roles account can hold
+pub const MAX_ROLE_GRANTS: usize = 32;
+
+/// Credential type meaning "no attestation required"
+pub const NO_CREDENTIAL: u16 = 0;
+
+/// Default flash loan fee (0.09%)
+pub const DEFAULT_FLASH_LOAN_FEE_BPS: u16 = 9;
+
+/// Maximum flash loan fee the risk manager can configure (1%)
+pub const MAX_FLASH_LOAN_FEE_BPS: u16 = 100;
+
+/// Fixed-point precision of the pool-funded loan accumulators (1.0 = 1e18)
+pub const STAKE_ACCUMULATOR_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Layout version written into every account this program creates,
+/// bumped whenever an account layout changes (see the migrate_* instructions)
+pub const ACCOUNT_VERSION: u8 = 1;
+
+/// loan-manager program, the only caller of the loan bookkeeping instructions
+pub const LOAN_MANAGER_PROGRAM_ID: Pubkey = pubkey!("62cntT6xRY9yRPFENRwV8ZEnwkkTx84jGHKfVbHhv8fX");
+
+/// Seed of the loan-manager PDA that signs its calls into the pool
+pub const LOAN_AUTHORITY_SEED: &[u8] = b"loan_authority";
+
+/// Maximum share of available liquidity a yield strategy may hold (50%)
+pub const MAX_STRATEGY_ALLOCATION_BPS: u16 = 5000;
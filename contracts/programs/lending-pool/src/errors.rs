@@ -9,22 +9,22 @@ pub enum LendingPoolError {
     #[msg("Pool name exceeds maximum length of 50 characters")]
     PoolNameTooLong,
     
-    #[msg("Pool is currently paused. No deposits or loans allowed.")]
+    #[msg("This operation is currently paused on the pool")]
     PoolPaused,
     
-    #[msg("Pool is not paused")]
+    #[msg("None of the selected operations are paused")]
     PoolNotPaused,
     
-    #[msg("Deposit amount is below minimum required. Minimum: 100 tokens")]
+    #[msg("Deposit amount is below the pool's minimum deposit")]
     DepositBelowMinimum,
     
-    #[msg("Withdrawal amount is below minimum required. Minimum: 1 token")]
+    #[msg("Withdrawal amount is below the pool's minimum withdrawal")]
     WithdrawalBelowMinimum,
     
     #[msg("Insufficient available balance for withdrawal. Check your available vs lent amounts.")]
     InsufficientAvailableBalance,
     
-    #[msg("Withdrawal would leave balance below minimum required (10 tokens)")]
+    #[msg("Withdrawal would leave balance below the pool's minimum remaining balance")]
     BelowMinimumBalance,
     
     #[msg("Pool has insufficient liquidity for this operation")]
@@ -45,6 +45,207 @@ pub enum LendingPoolError {
     #[msg("Invalid token mint for this pool")]
     InvalidTokenMint,
     
-    #[msg("Cannot withdraw: funds are currently lent out to active loans")]
+    #[msg("Cannot withdraw: funds are currently lent out to active loans or reserved for offers")]
     FundsCurrentlyLent,
+
+    #[msg("Invalid share mint for this pool")]
+    InvalidShareMint,
+
+    #[msg("Insufficient pool shares for this withdrawal")]
+    InsufficientShares,
+
+    #[msg("Deposit is too small to mint any pool shares")]
+    ZeroSharesMinted,
+
+    #[msg("Invalid interest rate model parameters")]
+    InvalidInterestRateModel,
+
+    #[msg("Reserve factor exceeds the maximum of 50%")]
+    ReserveFactorTooHigh,
+
+    #[msg("Protocol treasury has not been configured for this pool")]
+    TreasuryNotSet,
+
+    #[msg("Invalid treasury token account")]
+    InvalidTreasury,
+
+    #[msg("Amount exceeds collectable protocol reserves")]
+    InsufficientReserves,
+
+    #[msg("Withdrawal queue is full. Try again after queued requests are filled.")]
+    WithdrawalQueueFull,
+
+    #[msg("Destination account does not match the queued withdrawal request")]
+    InvalidQueueDestination,
+
+    #[msg("Lender already has the maximum number of queued withdrawals")]
+    TooManyWithdrawalRequests,
+
+    #[msg("Unsupported lock term. Use 30, 90 or 180 days.")]
+    InvalidLockTerm,
+
+    #[msg("Lock amount must be greater than zero shares")]
+    ZeroLockAmount,
+
+    #[msg("Junior premium exceeds the maximum of 50%")]
+    JuniorPremiumTooHigh,
+
+    #[msg("Tranche was wiped out by losses and cannot take new deposits")]
+    TrancheWipedOut,
+
+    #[msg("Pool registry is full")]
+    MarketRegistryFull,
+
+    #[msg("Pool is not listed in the registry")]
+    MarketNotListed,
+
+    #[msg("Invalid market risk parameters")]
+    InvalidRiskParams,
+
+    #[msg("Invalid pool config: minimums must be non-zero and remaining balance at most the minimum deposit")]
+    InvalidPoolConfig,
+
+    #[msg("Proposed authority must differ from the current one and not be empty")]
+    InvalidPendingAuthority,
+
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+
+    #[msg("Only the pending authority can accept the transfer")]
+    UnauthorizedPendingAuthority,
+
+    #[msg("Signer does not hold the role required for this action")]
+    MissingRole,
+
+    #[msg("Role already granted to this account")]
+    RoleAlreadyGranted,
+
+    #[msg("Role is not granted to this account")]
+    RoleNotGranted,
+
+    #[msg("Roles account is full")]
+    RoleRegistryFull,
+
+    #[msg("Cannot revoke the last admin")]
+    LastAdminRevoke,
+
+    #[msg("No operations selected")]
+    NoPauseFlagsSelected,
+
+    #[msg("Only an admin can pause repayments")]
+    RepaymentPauseRequiresAdmin,
+
+    #[msg("Deposit would exceed the pool's supply cap")]
+    SupplyCapExceeded,
+
+    #[msg("Deposit would exceed the per-lender cap")]
+    LenderCapExceeded,
+
+    #[msg("Pool requires an attestation for this action")]
+    AttestationRequired,
+
+    #[msg("Attestation is expired or revoked")]
+    AttestationInvalid,
+
+    #[msg("Attestation expiry must be in the future")]
+    InvalidAttestationExpiry,
+
+    #[msg("Attestation already revoked")]
+    AttestationAlreadyRevoked,
+
+    #[msg("Credential type cannot be zero")]
+    InvalidCredentialType,
+
+    #[msg("Gated pool needs an attestation issuer")]
+    InvalidAccessPolicy,
+
+    #[msg("A flash loan is open on this pool")]
+    FlashLoanActive,
+
+    #[msg("Flash loan must be repaid by flash_repay later in the same transaction")]
+    FlashLoanNotRepaid,
+
+    #[msg("Flash loan can only be taken by a top-level instruction")]
+    FlashLoanCpiNotAllowed,
+
+    #[msg("Other pool instructions cannot run inside a flash loan")]
+    FlashLoanReentrancy,
+
+    #[msg("No flash loan is open on this pool")]
+    NoActiveFlashLoan,
+
+    #[msg("Flash loan repayment is less than principal plus fee")]
+    FlashLoanRepaymentTooLow,
+
+    #[msg("Flash loan fee exceeds the maximum")]
+    FlashLoanFeeTooHigh,
+
+    #[msg("Flash loan amount must be greater than zero")]
+    ZeroFlashLoanAmount,
+
+    #[msg("Written-off debt must be absorbed first (absorb_bad_debt)")]
+    BadDebtPending,
+
+    #[msg("Pool has no bad debt to absorb")]
+    NoBadDebt,
+
+    #[msg("Invalid insurance fund vault")]
+    InvalidInsuranceVault,
+
+    #[msg("Insurance amount must be greater than zero")]
+    ZeroInsuranceAmount,
+
+    #[msg("Lender account still holds a balance or lent funds")]
+    LenderAccountNotEmpty,
+
+    #[msg("Funds are still lent out in active loans or reserved for offers")]
+    LenderFundsLent,
+
+    #[msg("Account is not a migratable account of this type")]
+    InvalidAccountLayout,
+
+    #[msg("Account already uses the current layout version")]
+    AccountAlreadyMigrated,
+
+    #[msg("Strategy accounts do not match the pool's yield strategy")]
+    InvalidStrategyAccounts,
+
+    #[msg("Strategy allocation exceeds the maximum")]
+    InvalidStrategyAllocation,
+
+    #[msg("Allocation would exceed the strategy's share of pool liquidity")]
+    StrategyAllocationExceeded,
+
+    #[msg("Amount exceeds the funds deployed in the strategy")]
+    InsufficientStrategyBalance,
+
+    #[msg("Strategy did not report a balance")]
+    InvalidStrategyReport,
+
+    #[msg("Strategy amount must be greater than zero")]
+    ZeroStrategyAmount,
+
+    #[msg("Lender's senior share account is required to lend their funds")]
+    LenderShareAccountRequired,
+
+    #[msg("Junior withdrawals are frozen while a defaulted loan awaits write-off")]
+    DefaultPending,
+
+    #[msg("Pool has an insurance fund, its vault must be passed")]
+    InsuranceVaultRequired,
+
+    #[msg("Lender has a pending withdrawal request in the queue")]
+    WithdrawalRequestPending,
+
+    #[msg("Strategy reports at least the funds deployed in it")]
+    NoStrategyLoss,
+
+    #[msg("Strategy call did not move exactly the requested amount")]
+    InvalidStrategyTransfer,
+
+    #[msg("Claim amount must be greater than zero")]
+    ZeroClaimAmount,
+
+    #[msg("Amount exceeds the interest paid into the pool for the lender's shares")]
+    InsufficientClaimableInterest,
 }
\ No newline at end of file
@@ -1,9 +1,14 @@
 use anchor_lang::prelude::*;
+
+use crate::states::{AccessPolicy, PauseFlags, PoolConfig, Role, Tranche};
+
 #[event]
 pub struct PoolInitialized{
     pub pool  : Pubkey,
     pub authority : Pubkey,
     pub token_mint : Pubkey,
+    pub share_mint : Pubkey,
+    pub junior_share_mint : Pubkey,
     pub pool_name : String,
     pub timestamp : i64
 }
@@ -14,9 +19,11 @@ pub struct Deposited{
 
     pub pool : Pubkey,
 
+    pub tranche : Tranche,
+
     pub amount : u64,
 
-    pub new_available_amount : u64,
+    pub shares_minted : u64,
 
     pub new_pool_liquidity : u64,
 
@@ -29,9 +36,14 @@ pub struct Withdrawal{
     pub lender : Pubkey,
     pub pool : Pubkey,
 
+    pub tranche : Tranche,
+
     pub amount : u64,
 
-    pub remaining_available : u64,
+    pub shares_burned : u64,
+
+    // value of the shares left in the lender's share account
+    pub remaining_value : u64,
     // Fund is currentlly locked in active loan 
     pub remaining_lent : u64,
 
@@ -43,6 +55,10 @@ pub struct Withdrawal{
 pub struct PoolPaused {
     pub pool: Pubkey,
     pub authority: Pubkey,
+    // operations selected by this call
+    pub paused: PauseFlags,
+    // resulting pool flags
+    pub pause_flags: PauseFlags,
     pub timestamp: i64,
 }
 
@@ -51,6 +67,10 @@ pub struct PoolPaused {
 pub struct PoolUnpaused {
     pub pool: Pubkey,
     pub authority: Pubkey,
+    // operations selected by this call
+    pub unpaused: PauseFlags,
+    // resulting pool flags
+    pub pause_flags: PauseFlags,
     pub timestamp: i64,
 }
 
@@ -80,4 +100,438 @@ pub struct LiquidityReleased {
     pub interest: u64,
     pub loan_id: u64,
     pub timestamp: i64,
-}
\ No newline at end of file
+}
+
+/// Emitted when a written-off loan's debt is booked as bad debt (called by LoanManager)
+#[event]
+pub struct LoanLossRecorded {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub principal: u64,
+    pub loss: u64,
+    pub bad_debt: u64,
+    pub loan_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LenderFundsReserved {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub amount: u64,
+    // lender's total reservation after this one
+    pub reserved_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LenderFundsReleased {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub amount: u64,
+    pub reserved_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LoanDefaultRecorded {
+    pub pool: Pubkey,
+    pub loan_id: u64,
+    // defaulted loans awaiting write-off, including this one
+    pub pending_defaults: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InterestRateModelUpdated {
+    pub pool: Pubkey,
+    pub base_rate_bps: u16,
+    pub slope1_bps: u16,
+    pub kink_utilization: u64,
+    pub slope2_bps: u16,
+    pub max_utilization: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted whenever the pool borrow index is advanced
+#[event]
+pub struct InterestAccrued {
+    pub pool: Pubkey,
+    pub interest: u64,
+    pub reserves: u64,
+    pub borrow_rate_bps: u16,
+    pub borrow_index: u128,
+    pub total_borrowed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveConfigUpdated {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub old_reserve_factor_bps: u16,
+    pub new_reserve_factor_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReservesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub remaining_reserves: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalQueued {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub request_id: u64,
+    pub shares: u64,
+    pub estimated_amount: u64,
+    pub queue_position: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted for every (possibly partial) fill of a queued withdrawal
+#[event]
+pub struct WithdrawalFilled {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub request_id: u64,
+    pub amount: u64,
+    pub shares_burned: u64,
+    pub remaining_shares: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositLocked {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub position_id: u64,
+    pub shares: u64,
+    pub value_at_lock: u64,
+    pub lock_days: u16,
+    pub multiplier_bps: u16,
+    pub unlocks_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositUnlocked {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub position_id: u64,
+    pub shares_returned: u64,
+    // bonus shares minted for a matured position
+    pub bonus_shares: u64,
+    // shares burned back into the pool on early exit
+    pub penalty_shares: u64,
+    pub early: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TrancheConfigUpdated {
+    pub pool: Pubkey,
+    pub old_junior_premium_bps: u16,
+    pub new_junior_premium_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolRegistryInitialized {
+    pub registry: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketListed {
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub decimals: u8,
+    pub max_loan_amount: u64,
+    pub min_credit_score: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketDelisted {
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolConfigUpdated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_config: PoolConfig,
+    pub new_config: PoolConfig,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub pool: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolRolesInitialized {
+    pub roles: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub role: Role,
+    pub member: Pubkey,
+    pub granted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: Role,
+    pub member: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttestationIssued {
+    pub attestation: Pubkey,
+    pub issuer: Pubkey,
+    pub subject: Pubkey,
+    pub credential_type: u16,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub attestation: Pubkey,
+    pub issuer: Pubkey,
+    pub subject: Pubkey,
+    pub credential_type: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessPolicyUpdated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_policy: AccessPolicy,
+    pub new_policy: AccessPolicy,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanBorrowed {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanRepaid {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub reserves: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanFeeUpdated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolFundedSettled {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub interest: u64,
+    pub loss: u64,
+    pub stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundInitialized {
+    pub pool: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFunded {
+    pub pool: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    // moved from protocol reserves rather than paid in
+    pub from_reserves: bool,
+    pub fund_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BadDebtCovered {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub fund_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BadDebtSocialized {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub junior_loss: u64,
+    pub senior_loss: u64,
+    // part charged to lender stakes through the pool-funded loss accumulator
+    pub stake_loss: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LenderAccountOpened {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LenderAccountClosed {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LenderExited {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub tranche: Tranche,
+    pub amount: u64,
+    pub shares_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct YieldStrategyInitialized {
+    pub pool: Pubkey,
+    pub strategy_program: Pubkey,
+    pub strategy_state: Pubkey,
+    pub max_allocation_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyAllocationUpdated {
+    pub pool: Pubkey,
+    pub old_allocation_bps: u16,
+    pub new_allocation_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyDeposited {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub deployed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyRecalled {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub deployed: u64,
+    // recalled by a withdrawal or disbursement short of liquidity
+    pub automatic: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyHarvested {
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub reserves: u64,
+    pub reported_balance: u64,
+    pub deployed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrategyLossRealized {
+    pub pool: Pubkey,
+    pub loss: u64,
+    pub reported_balance: u64,
+    pub deployed: u64,
+    pub pending_bad_debt: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InterestClaimed {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub tranche: Tranche,
+    pub amount: u64,
+    pub shares_burned: u64,
+    pub remaining_claimable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InterestCompounded {
+    pub pool: Pubkey,
+    pub lender: Pubkey,
+    pub from_tranche: Tranche,
+    pub tranche: Tranche,
+    pub amount: u64,
+    pub shares_burned: u64,
+    pub shares_minted: u64,
+    pub remaining_claimable: u64,
+    pub timestamp: i64,
+}
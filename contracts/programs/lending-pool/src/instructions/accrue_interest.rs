@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::LendingPoolError, events::{InterestAccrued, PoolFundedSettled}, states::{InterestRateModel, LenderAccount, LendingPool}};
+
+/// Permissionless: anyone can advance the pool's borrow index
+#[derive(Accounts)]
+pub struct AccrueInterest<'info>{
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+}
+
+pub fn handler(ctx:Context<AccrueInterest>)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    msg!("Borrow index: {}", pool.borrow_index);
+    msg!("Total borrowed: {}", pool.total_borrowed);
+    msg!("Protocol reserves: {}", pool.total_reserves);
+    Ok(())
+}
+
+/// Accrue pool interest and emit InterestAccrued, shared by every instruction that touches pool balances
+pub fn accrue(pool:&mut Account<LendingPool>,model:&InterestRateModel,now:i64)->Result<u64>{
+    // reentrancy guard: nothing may touch pool balances while a flash loan is open
+    require!(pool.flash_loan_amount == 0,LendingPoolError::FlashLoanActive);
+
+    // share prices overstate pool value until written-off debt is absorbed
+    require!(pool.bad_debt == 0,LendingPoolError::BadDebtPending);
+
+    accrue_and_emit(pool, model, now)
+}
+
+/// Accrue and emit InterestAccrued without the guards, for absorb_bad_debt
+pub fn accrue_and_emit(pool:&mut Account<LendingPool>,model:&InterestRateModel,now:i64)->Result<u64>{
+    let borrow_rate_bps = model.borrow_rate(pool.calculate_utilization());
+
+    let (interest, reserves) = pool.accrue_interest(model, now).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    if interest > 0 {
+        emit!(InterestAccrued{
+            pool: pool.key(),
+            interest,
+            reserves,
+            borrow_rate_bps,
+            borrow_index: pool.borrow_index,
+            total_borrowed: pool.total_borrowed,
+            timestamp: now,
+        });
+    }
+    Ok(interest)
+}
+
+/// Book a lender's pending pool-funded interest and losses and weight it by `new_stake`
+/// from here on, must run whenever its net deposits change
+pub fn settle_pool_funded(pool:&mut Account<LendingPool>,lender_account:&mut LenderAccount,new_stake:u64,now:i64)->Result<()>{
+    let old_stake = lender_account.stake;
+
+    let (interest, loss) = lender_account
+        .settle_pool_funded(pool, new_stake)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    pool.total_lender_stake = pool
+        .total_lender_stake
+        .saturating_sub(old_stake)
+        .checked_add(new_stake)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    if interest > 0 || loss > 0 {
+        emit!(PoolFundedSettled {
+            pool: pool.key(),
+            lender: lender_account.lender,
+            interest,
+            loss,
+            stake: new_stake,
+            timestamp: now,
+        });
+    }
+    Ok(())
+}
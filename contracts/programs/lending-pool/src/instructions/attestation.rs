@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::{ACCOUNT_VERSION, NO_CREDENTIAL}, errors::LendingPoolError, events::{AccessPolicyUpdated, AttestationIssued, AttestationRevoked}, states::{AccessPolicy, Attestation, LendingPool, ProtocolRoles, Role}};
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey, credential_type: u16)]
+pub struct IssueAttestation<'info>{
+    /// Attestation issuer, pools choose which issuer they trust
+    #[account(mut)]
+    pub issuer : Signer<'info>,
+
+    // reissuing renews an expired or revoked attestation
+    #[account(
+        init_if_needed,
+        payer = issuer,
+        space = Attestation::LEN,
+        seeds = [
+            Attestation::SEED_PREFIX,
+            issuer.key().as_ref(),
+            subject.as_ref(),
+            credential_type.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub attestation : Account<'info,Attestation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info>{
+    /// Issuer of the attestation (only they can revoke it)
+    pub issuer : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            Attestation::SEED_PREFIX,
+            issuer.key().as_ref(),
+            attestation.subject.as_ref(),
+            attestation.credential_type.to_le_bytes().as_ref(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation : Account<'info,Attestation>,
+}
+
+#[derive(Accounts)]
+pub struct SetAccessPolicy<'info>{
+    /// Risk manager role holder (only they can gate a pool)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(mut)]
+    pub pool : Account<'info,LendingPool>,
+}
+
+pub fn issue_attestation_handler(ctx:Context<IssueAttestation>,subject:Pubkey,credential_type:u16,expires_at:i64)->Result<()>{
+    require!(credential_type != NO_CREDENTIAL,LendingPoolError::InvalidCredentialType);
+
+    let clock = Clock::get()?;
+
+    require!(expires_at == 0 || expires_at > clock.unix_timestamp,LendingPoolError::InvalidAttestationExpiry);
+
+    let attestation = &mut ctx.accounts.attestation;
+
+    attestation.issuer = ctx.accounts.issuer.key();
+    attestation.subject = subject;
+    attestation.credential_type = credential_type;
+    attestation.issued_at = clock.unix_timestamp;
+    attestation.expires_at = expires_at;
+    attestation.revoked = false;
+    attestation.revoked_at = 0;
+    attestation.version = ACCOUNT_VERSION;
+    attestation.bump = ctx.bumps.attestation;
+
+    emit!(AttestationIssued{
+        attestation: attestation.key(),
+        issuer: attestation.issuer,
+        subject,
+        credential_type,
+        expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Attestation issued");
+    msg!("Subject: {}", subject);
+    msg!("Credential type: {}", credential_type);
+    msg!("Expires at: {}", expires_at);
+    Ok(())
+}
+
+pub fn revoke_attestation_handler(ctx:Context<RevokeAttestation>)->Result<()>{
+    let clock = Clock::get()?;
+    let attestation = &mut ctx.accounts.attestation;
+
+    require!(!attestation.revoked,LendingPoolError::AttestationAlreadyRevoked);
+
+    attestation.revoked = true;
+    attestation.revoked_at = clock.unix_timestamp;
+
+    emit!(AttestationRevoked{
+        attestation: attestation.key(),
+        issuer: attestation.issuer,
+        subject: attestation.subject,
+        credential_type: attestation.credential_type,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Attestation revoked");
+    msg!("Subject: {}", attestation.subject);
+    Ok(())
+}
+
+pub fn set_policy_handler(ctx:Context<SetAccessPolicy>,policy:AccessPolicy)->Result<()>{
+    require!(policy.is_valid(),LendingPoolError::InvalidAccessPolicy);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    let old_policy = pool.access_policy;
+
+    pool.access_policy = policy;
+
+    emit!(AccessPolicyUpdated{
+        pool: pool.key(),
+        authority: ctx.accounts.risk_manager.key(),
+        old_policy,
+        new_policy: policy,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Pool access policy updated");
+    msg!("Issuer: {}", policy.issuer);
+    msg!("Lender credential: {}", policy.lender_credential);
+    msg!("Borrower credential: {}", policy.borrower_credential);
+    Ok(())
+}
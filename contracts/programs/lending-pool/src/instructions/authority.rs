@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::LendingPoolError, events::{AuthorityTransferCancelled, AuthorityTransferProposed, AuthorityTransferred}, states::LendingPool};
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info>{
+    /// Current pool authority
+    pub authority : Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info>{
+    /// Proposed authority, signing proves the key is usable
+    pub new_authority : Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = pool.pending_authority != Pubkey::default() @ LendingPoolError::NoPendingAuthority,
+        constraint = pool.pending_authority == new_authority.key() @ LendingPoolError::UnauthorizedPendingAuthority,
+    )]
+    pub pool : Account<'info,LendingPool>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info>{
+    /// Current pool authority
+    pub authority : Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+}
+
+pub fn propose_handler(ctx:Context<ProposeAuthority>,new_authority:Pubkey)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(
+        new_authority != Pubkey::default() && new_authority != pool.authority,
+        LendingPoolError::InvalidPendingAuthority
+    );
+
+    // a new proposal replaces any pending one
+    pool.pending_authority = new_authority;
+
+    emit!(AuthorityTransferProposed{
+        pool: pool.key(),
+        authority: pool.authority,
+        pending_authority: new_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Authority transfer proposed");
+    msg!("Pending authority: {}", new_authority);
+    msg!("Transfer completes when the new authority accepts");
+    Ok(())
+}
+
+pub fn accept_handler(ctx:Context<AcceptAuthority>)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    let old_authority = pool.authority;
+
+    pool.authority = pool.pending_authority;
+    pool.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferred{
+        pool: pool.key(),
+        old_authority,
+        new_authority: pool.authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Authority transfer accepted");
+    msg!("New authority: {}", pool.authority);
+    Ok(())
+}
+
+pub fn cancel_handler(ctx:Context<CancelAuthorityTransfer>)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(pool.pending_authority != Pubkey::default(),LendingPoolError::NoPendingAuthority);
+
+    let cancelled_authority = pool.pending_authority;
+
+    pool.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferCancelled{
+        pool: pool.key(),
+        authority: pool.authority,
+        cancelled_authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Authority transfer cancelled");
+    Ok(())
+}
@@ -0,0 +1,423 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{errors::LendingPoolError, events::{InterestClaimed, InterestCompounded}, instructions::{accrue_interest::{accrue, settle_pool_funded}, strategy::{optional_strategy_cpi, recall_shortfall}, withdraw::require_redeemable}, states::{InterestRateModel, LenderAccount, LendingPool, Tranche, YieldStrategy}};
+
+// Interest is part of the share price, a lender's claimable interest is what their shares
+// redeem for above the rate they were bought at (LenderAccount rate checkpoints).
+// The rate only counts interest borrowers have paid, so unpaid interest is never claimable.
+
+#[derive(Accounts)]
+#[instruction(amount: u64, tranche: Tranche)]
+pub struct ClaimInterest<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = lender_account.bump,
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    // interest is accrued before shares are priced
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
+        constraint = lender_token_account.mint == pool.token_mint,
+        constraint = lender_token_account.owner == lender.key()
+    )]
+    pub lender_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // share mint of the tranche the interest is claimed from
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.tranche_share_mint(tranche) @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    // shares worth the claimed interest are burned from here
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == share_mint.key() @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender.key()
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Moves interest earned in the other tranche into `tranche`, interest already compounds
+/// in the tranche it was earned in
+#[derive(Accounts)]
+#[instruction(amount: u64, tranche: Tranche)]
+pub struct CompoundInterest<'info>{
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = lender_account.bump,
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    // interest is accrued before shares are priced
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // share mint of the tranche the interest was earned in
+    #[account(
+        mut,
+        constraint = source_share_mint.key() == pool.tranche_share_mint(tranche.other()) @ LendingPoolError::InvalidShareMint
+    )]
+    pub source_share_mint : InterfaceAccount<'info,Mint>,
+
+    // shares worth the compounded interest are burned from here
+    #[account(
+        mut,
+        constraint = source_share_account.mint == source_share_mint.key() @ LendingPoolError::InvalidShareMint,
+        constraint = source_share_account.owner == lender.key()
+    )]
+    pub source_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    // share mint of the tranche the interest is compounded into
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.tranche_share_mint(tranche) @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    // receives the minted shares
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == share_mint.key() @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender.key()
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Shares of `tranche` to burn for `amount` of the lender's claimable interest,
+/// and the interest left in the rest of `share_balance`
+fn take_interest(pool:&LendingPool,lender_account:&mut LenderAccount,tranche:Tranche,share_balance:u64,amount:u64)->Result<(u64,u64)>{
+    let exchange_rate = pool.exchange_rate(tranche);
+
+    let claimable = lender_account
+        .claimable_interest(tranche, share_balance, exchange_rate)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(amount<=claimable,LendingPoolError::InsufficientClaimableInterest);
+
+    let shares = pool.shares_for_withdrawal(tranche, amount).ok_or(LendingPoolError::InsufficientShares)?;
+
+    require!(share_balance>=shares,LendingPoolError::InsufficientShares);
+
+    require_redeemable(pool, lender_account, tranche, share_balance, shares)?;
+
+    let remaining_shares = share_balance.checked_sub(shares).ok_or(LendingPoolError::InsufficientShares)?;
+
+    let remaining_claimable = claimable.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    lender_account
+        .record_interest_taken(tranche, remaining_shares, remaining_claimable, exchange_rate)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    Ok((shares, remaining_claimable))
+}
+
+/// Remove `amount` of value and `shares` from `tranche`, total_deposits is left to the caller
+fn remove_from_tranche(pool:&mut LendingPool,tranche:Tranche,amount:u64,shares:u64)->Result<()>{
+    match tranche {
+        Tranche::Senior => {
+            pool.total_shares = pool
+                .total_shares
+                .checked_sub(shares)
+                .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        }
+        Tranche::Junior => {
+            pool.junior_deposits = pool
+                .junior_deposits
+                .checked_sub(amount)
+                .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+            pool.junior_shares = pool
+                .junior_shares
+                .checked_sub(shares)
+                .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn claim_handler(ctx:Context<ClaimInterest>,amount:u64,tranche:Tranche)->Result<()>{
+    let pool = &mut ctx.accounts.pool;
+    let lender_account = &mut ctx.accounts.lender_account;
+    let clock = Clock::get()?;
+
+    require!(!pool.pause_flags.withdrawals,LendingPoolError::PoolPaused);
+
+    require!(tranche == Tranche::Senior || !pool.junior_exits_frozen(),LendingPoolError::DefaultPending);
+
+    require!(amount>0,LendingPoolError::ZeroClaimAmount);
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // claims can't jump liquidity earmarked for queued withdrawals either
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(unreserved_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    let (shares, remaining_claimable) = take_interest(pool, lender_account, tranche, ctx.accounts.lender_share_account.amount, amount)?;
+
+    // funds deployed in the yield strategy come back when the pool account is short
+    let strategy_cpi = optional_strategy_cpi(
+        &ctx.accounts.yield_strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, ctx.accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), ctx.accounts.pool_token_account.amount, amount, clock.unix_timestamp)?;
+
+    if recalled > 0 {
+        ctx.accounts.pool_token_account.reload()?;
+    }
+
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.pool_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.lender_token_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds);
+
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+    let cpi_account = Burn{
+        mint : ctx.accounts.share_mint.to_account_info(),
+        from : ctx.accounts.lender_share_account.to_account_info(),
+        authority : ctx.accounts.lender.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account);
+
+    token_interface::burn(cpi_ctx, shares)?;
+
+    // update lender account
+
+    lender_account.total_withdrawals = lender_account
+        .total_withdrawals
+        .checked_add(amount)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    // pool-funded interest and losses are booked on the old stake
+    let stake = lender_account.net_deposits();
+    settle_pool_funded(pool, lender_account, stake, clock.unix_timestamp)?;
+
+    // Update pool state, claimed interest leaves like a withdrawal
+    pool.available_liquidity = pool
+        .available_liquidity
+        .checked_sub(amount)
+        .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    pool.total_deposits = pool
+        .total_deposits
+        .checked_sub(amount)
+        .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    remove_from_tranche(pool, tranche, amount, shares)?;
+
+    emit!(InterestClaimed{
+        pool: pool.key(),
+        lender: ctx.accounts.lender.key(),
+        tranche,
+        amount,
+        shares_burned: shares,
+        remaining_claimable,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Interest claimed");
+    msg!("Tranche: {:?}", tranche);
+    msg!("Amount: {} tokens", amount);
+    msg!("Shares burned: {}", shares);
+    msg!("Remaining claimable: {}", remaining_claimable);
+    Ok(())
+}
+
+pub fn compound_handler(ctx:Context<CompoundInterest>,amount:u64,tranche:Tranche)->Result<()>{
+    let pool = &mut ctx.accounts.pool;
+    let lender_account = &mut ctx.accounts.lender_account;
+    let clock = Clock::get()?;
+    let from_tranche = tranche.other();
+
+    require!(!pool.pause_flags.deposits && !pool.pause_flags.withdrawals,LendingPoolError::PoolPaused);
+
+    require!(from_tranche == Tranche::Senior || !pool.junior_exits_frozen(),LendingPoolError::DefaultPending);
+
+    require!(amount>0,LendingPoolError::ZeroClaimAmount);
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    require!(!pool.is_tranche_wiped_out(tranche),LendingPoolError::TrancheWipedOut);
+
+    let (shares_burned, remaining_claimable) = take_interest(pool, lender_account, from_tranche, ctx.accounts.source_share_account.amount, amount)?;
+
+    // price the new shares before the interest moves between tranches
+    let shares_minted = pool.amount_to_shares(tranche, amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(shares_minted>0,LendingPoolError::ZeroSharesMinted);
+
+    lender_account
+        .record_shares_bought(tranche, ctx.accounts.lender_share_account.amount, shares_minted, amount)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let cpi_account = Burn{
+        mint : ctx.accounts.source_share_mint.to_account_info(),
+        from : ctx.accounts.source_share_account.to_account_info(),
+        authority : ctx.accounts.lender.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account);
+
+    token_interface::burn(cpi_ctx, shares_burned)?;
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = MintTo{
+        mint : ctx.accounts.share_mint.to_account_info(),
+        to : ctx.accounts.lender_share_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds);
+
+    token_interface::mint_to(cpi_ctx, shares_minted)?;
+
+    // the interest is withdrawn and deposited again, net deposits are unchanged
+    lender_account.total_withdrawals = lender_account
+        .total_withdrawals
+        .checked_add(amount)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    lender_account.deposited_amount = lender_account
+        .deposited_amount
+        .checked_add(amount)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    // no tokens move, total_deposits and liquidity stay, only the tranche split changes
+    remove_from_tranche(pool, from_tranche, amount, shares_burned)?;
+
+    match tranche {
+        Tranche::Senior => {
+            pool.total_shares = pool.total_shares.checked_add(shares_minted).ok_or(LendingPoolError::ArithmeticOverflow)?;
+        }
+        Tranche::Junior => {
+            pool.junior_deposits = pool.junior_deposits.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+            pool.junior_shares = pool.junior_shares.checked_add(shares_minted).ok_or(LendingPoolError::ArithmeticOverflow)?;
+        }
+    }
+
+    emit!(InterestCompounded{
+        pool: pool.key(),
+        lender: ctx.accounts.lender.key(),
+        from_tranche,
+        tranche,
+        amount,
+        shares_burned,
+        shares_minted,
+        remaining_claimable,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Interest compounded");
+    msg!("From {:?} into {:?}", from_tranche, tranche);
+    msg!("Amount: {} tokens", amount);
+    msg!("Shares burned: {}, minted: {}", shares_burned, shares_minted);
+    msg!("Remaining claimable: {}", remaining_claimable);
+    Ok(())
+}
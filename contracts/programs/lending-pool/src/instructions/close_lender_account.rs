@@ -0,0 +1,354 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{errors::LendingPoolError, events::{LenderAccountClosed, LenderAccountOpened, LenderExited}, instructions::{accrue_interest::{accrue, settle_pool_funded}, strategy::{optional_strategy_cpi, recall_shortfall}}, states::{InterestRateModel, LenderAccount, LendingPool, Tranche, WithdrawalQueue, YieldStrategy}};
+
+// for holders of transferred shares, deposit opens the account otherwise
+#[derive(Accounts)]
+pub struct OpenLenderAccount<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        init,
+        payer = lender,
+        space = LenderAccount::LEN,
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLenderAccount<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    // the account's stake leaves the pool-funded accumulators
+    #[account(mut)]
+    pub pool : Account<'info,LendingPool>,
+
+    // rent goes back to the lender
+    #[account(
+        mut,
+        close = lender,
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = lender_account.bump,
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    /// CHECK: the pool's withdrawal queue PDA, read when it exists (see queued_requests)
+    #[account(
+        seeds = [WithdrawalQueue::SEED_PREFIX,pool.key().as_ref()],
+        bump,
+    )]
+    pub withdrawal_queue : UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExitPool<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    // closed once everything is paid out, rent goes back to the lender
+    #[account(
+        mut,
+        close = lender,
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = lender_account.bump,
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
+        constraint = lender_token_account.mint == pool.token_mint,
+        constraint = lender_token_account.owner == lender.key()
+    )]
+    pub lender_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = senior_share_mint.key() == pool.share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub senior_share_mint : InterfaceAccount<'info,Mint>,
+
+    // every share in here is redeemed
+    #[account(
+        mut,
+        constraint = senior_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = senior_share_account.owner == lender.key()
+    )]
+    pub senior_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = junior_share_mint.key() == pool.junior_share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub junior_share_mint : InterfaceAccount<'info,Mint>,
+
+    // every share in here is redeemed
+    #[account(
+        mut,
+        constraint = junior_share_account.mint == pool.junior_share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = junior_share_account.owner == lender.key()
+    )]
+    pub junior_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    /// CHECK: the pool's withdrawal queue PDA, read when it exists (see queued_requests)
+    #[account(
+        seeds = [WithdrawalQueue::SEED_PREFIX,pool.key().as_ref()],
+        bump,
+    )]
+    pub withdrawal_queue : UncheckedAccount<'info>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pending withdrawal requests of `lender`, the queue account only exists after the pool's first request
+fn queued_requests(withdrawal_queue:&AccountInfo,lender:&Pubkey)->Result<usize>{
+    if withdrawal_queue.data_is_empty() {
+        return Ok(0);
+    }
+
+    require_keys_eq!(*withdrawal_queue.owner,crate::ID,ErrorCode::AccountOwnedByWrongProgram);
+
+    let queue = WithdrawalQueue::try_deserialize(&mut &withdrawal_queue.try_borrow_data()?[..])?;
+
+    Ok(queue.requests_of(lender))
+}
+
+pub fn open_handler(ctx:Context<OpenLenderAccount>)->Result<()>{
+    let clock = Clock::get()?;
+    let lender = ctx.accounts.lender.key();
+    let pool = ctx.accounts.pool.key();
+
+    ctx.accounts.lender_account.initialize_if_new(lender, pool, ctx.bumps.lender_account);
+
+    emit!(LenderAccountOpened{
+        pool,
+        lender,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Lender account opened");
+    Ok(())
+}
+
+pub fn close_handler(ctx:Context<CloseLenderAccount>)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    let lender_account = &mut ctx.accounts.lender_account;
+
+    require!(lender_account.is_empty(),LendingPoolError::LenderAccountNotEmpty);
+
+    // a queued request is paid out against this account's position, it has to be filled first
+    require!(queued_requests(&ctx.accounts.withdrawal_queue, &lender_account.lender)? == 0,LendingPoolError::WithdrawalRequestPending);
+
+    // pool-funded interest and losses up to now, then the stake leaves the pool
+    settle_pool_funded(pool, lender_account, 0, clock.unix_timestamp)?;
+
+    emit!(LenderAccountClosed{
+        pool: pool.key(),
+        lender: lender_account.lender,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Lender account closed, rent returned");
+    Ok(())
+}
+
+pub fn exit_handler(ctx:Context<ExitPool>)->Result<()>{
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+
+    require!(!accounts.pool.pause_flags.withdrawals,LendingPoolError::PoolPaused);
+
+    require!(
+        accounts.junior_share_account.amount == 0 || !accounts.pool.junior_exits_frozen(),
+        LendingPoolError::DefaultPending
+    );
+
+    require!(queued_requests(&accounts.withdrawal_queue, &accounts.lender.key())? == 0,LendingPoolError::WithdrawalRequestPending);
+
+    accrue(&mut accounts.pool, &accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // lent funds come back only as loans are repaid, reserved ones when the offers are cancelled
+    require!(accounts.lender_account.is_empty(),LendingPoolError::LenderFundsLent);
+
+    let senior_amount = redeem_all(accounts, Tranche::Senior, clock.unix_timestamp)?;
+    let junior_amount = redeem_all(accounts, Tranche::Junior, clock.unix_timestamp)?;
+
+    // the account is closed, its whole stake leaves the pool-funded accumulators
+    settle_pool_funded(&mut accounts.pool, &mut accounts.lender_account, 0, clock.unix_timestamp)?;
+
+    emit!(LenderAccountClosed{
+        pool: accounts.pool.key(),
+        lender: accounts.lender_account.lender,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Exited pool");
+    msg!("Senior withdrawn: {} tokens", senior_amount);
+    msg!("Junior withdrawn: {} tokens", junior_amount);
+    msg!("Lender account closed, rent returned");
+    Ok(())
+}
+
+/// Burn every share in the lender's `tranche` share account and pay out their value
+fn redeem_all(accounts:&mut ExitPool,tranche:Tranche,now:i64)->Result<u64>{
+    let (share_mint, share_account) = match tranche {
+        Tranche::Senior => (&accounts.senior_share_mint, &accounts.senior_share_account),
+        Tranche::Junior => (&accounts.junior_share_mint, &accounts.junior_share_account),
+    };
+
+    let shares = share_account.amount;
+
+    if shares == 0 {
+        return Ok(0);
+    }
+
+    let pool = &mut accounts.pool;
+
+    let amount = pool.shares_to_amount(tranche, shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(unreserved_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    // funds deployed in the yield strategy come back when the pool account is short
+    let strategy_cpi = optional_strategy_cpi(
+        &accounts.yield_strategy,
+        &accounts.strategy_program,
+        &accounts.strategy_state,
+        &accounts.strategy_token_account,
+        accounts.pool_token_account.to_account_info(),
+        accounts.token_mint.to_account_info(),
+        accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), accounts.pool_token_account.amount, amount, now)?;
+
+    if recalled > 0 {
+        accounts.pool_token_account.reload()?;
+    }
+
+    require!(accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    let cpi_account = Burn{
+        mint : share_mint.to_account_info(),
+        from : share_account.to_account_info(),
+        authority : accounts.lender.to_account_info()
+    };
+
+    token_interface::burn(CpiContext::new(accounts.token_program.to_account_info(), cpi_account), shares)?;
+
+    if amount > 0 {
+        let seeds = &[
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+            &[pool.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_account = TransferChecked{
+            from : accounts.pool_token_account.to_account_info(),
+            mint : accounts.token_mint.to_account_info(),
+            to : accounts.lender_token_account.to_account_info(),
+            authority : pool.to_account_info()
+        };
+
+        token_interface::transfer_checked(CpiContext::new_with_signer(accounts.token_program.to_account_info(), cpi_account, signer_seeds), amount, accounts.token_mint.decimals)?;
+
+        // the other tranche is paid from the same account
+        accounts.pool_token_account.reload()?;
+    }
+
+    pool.available_liquidity = pool.available_liquidity.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    pool.total_deposits = pool.total_deposits.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    match tranche {
+        Tranche::Senior => {
+            pool.total_shares = pool.total_shares.checked_sub(shares).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        }
+        Tranche::Junior => {
+            pool.junior_deposits = pool.junior_deposits.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+            pool.junior_shares = pool.junior_shares.checked_sub(shares).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        }
+    }
+
+    emit!(LenderExited{
+        pool: pool.key(),
+        lender: accounts.lender_account.lender,
+        tranche,
+        amount,
+        shares_burned: shares,
+        timestamp: now,
+    });
+
+    msg!("Tranche: {:?}", tranche);
+    msg!("Shares burned: {}", shares);
+    Ok(amount)
+}
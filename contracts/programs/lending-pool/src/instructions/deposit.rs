@@ -1,10 +1,11 @@
 
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
 
-use crate::{constants::MIN_DEPOSIT_AMOUNT, errors::LendingPoolError, events::Deposited, states::{LenderAccount, LendingPool}};
+use crate::{errors::LendingPoolError, events::Deposited, instructions::accrue_interest::{accrue, settle_pool_funded}, states::{Attestation, InterestRateModel, LenderAccount, LendingPool, Tranche}};
 #[derive(Accounts)]
+#[instruction(amount: u64, tranche: Tranche)]
 pub struct Deposit<'info>{
     #[account(mut)]
     pub lender : Signer<'info>,
@@ -27,105 +28,210 @@ pub struct Deposit<'info>{
     )]
     pub lender_account :  Account<'info,LenderAccount>,
 
+    // interest is accrued before shares are priced
     #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
         constraint = lender_token_account.mint == pool.token_mint,
-        constraint = lender_token_account.key() == lender.key()
+        constraint = lender_token_account.owner == lender.key()
     )]
-    pub lender_token_account : Account<'info,TokenAccount>,
+    pub lender_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // share mint of the tranche being deposited into
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.tranche_share_mint(tranche) @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    // receives the minted pool shares, any token account of the share mint works
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == share_mint.key() @ LendingPoolError::InvalidShareMint
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
 
 
     #[account(
         mut ,
         constraint = pool_token_account.key() == pool.pool_token_account
     )]
-    pub pool_token_account : Account<'info,TokenAccount>,
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // lender's attestation from the pool's issuer, only needed when the pool is gated
+    #[account(
+        seeds = [
+            Attestation::SEED_PREFIX,
+            pool.access_policy.issuer.as_ref(),
+            lender.key().as_ref(),
+            pool.access_policy.lender_credential.to_le_bytes().as_ref(),
+        ],
+        bump = attestation.bump,
+    )]
+    pub attestation : Option<Account<'info,Attestation>>,
 
     // System program
     pub system_program: Program<'info, System>,
 
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 
 
 impl <'info> Deposit <'info> {
     pub fn initialize_lender_account (&mut self , bump:u8) ->Result<()>{
-        let lender_account = &mut self.lender_account;
-
         //  initialize If there is a new account 
-
-        if lender_account.lender == Pubkey::default() {
-            lender_account.lender = self.lender.key();
-            lender_account.pool = self.pool.key();
-            lender_account.deposited_amount = 0;
-            lender_account.available_amount = 0;
-            lender_account.interest_earned = 0;
-            lender_account.total_withdrawals = 0;
-            lender_account.last_deposited_time = 0;
-            lender_account.bump = bump
-        }
+        let lender = self.lender.key();
+        let pool = self.pool.key();
+        self.lender_account.initialize_if_new(lender, pool, bump);
         Ok(())
     }
 }
 
 
-pub fn handler(ctx:Context<Deposit>,amount:u64)->Result<()>{
+pub fn handler(ctx:Context<Deposit>,amount:u64,tranche:Tranche)->Result<()>{
+    let lender_account_bump = ctx.bumps.lender_account;
+    ctx.accounts.initialize_lender_account(lender_account_bump)?;
+
     let pool = &mut ctx.accounts.pool;
 
     let lender_account = &mut ctx.accounts.lender_account;
 
     let clock = Clock::get()?;
 
-    require!(!pool.paused,LendingPoolError::PoolPaused);
+    require!(!pool.pause_flags.deposits,LendingPoolError::PoolPaused);
+
+    if pool.access_policy.requires_lender_attestation() {
+        let attestation = ctx.accounts.attestation.as_ref().ok_or(LendingPoolError::AttestationRequired)?;
+
+        require!(attestation.is_valid(clock.unix_timestamp),LendingPoolError::AttestationInvalid);
+    }
+
+    require!(amount>pool.config.min_deposit_amount,LendingPoolError::DepositBelowMinimum);
 
-    require!(amount>MIN_DEPOSIT_AMOUNT,LendingPoolError::DepositBelowMinimum);
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    require!(!pool.is_tranche_wiped_out(tranche),LendingPoolError::TrancheWipedOut);
 
     // transfer token from lender account to pool account 
 
-    let cpi_account = Transfer{
+    let balance_before = ctx.accounts.pool_token_account.amount;
+
+    let cpi_account = TransferChecked{
         from: ctx.accounts.lender_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
         to : ctx.accounts.pool_token_account.to_account_info(),
         authority : ctx.accounts.lender.to_account_info()
     };
 
     let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account);
 
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+    // Token-2022 transfer fees are withheld from the pool side, only credit what arrived
+    ctx.accounts.pool_token_account.reload()?;
+
+    let received = ctx.accounts.pool_token_account.amount
+        .checked_sub(balance_before)
+        .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    // caps are checked against what actually arrived
+    let new_total_deposits = pool.total_deposits.checked_add(received).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(pool.config.within_supply_cap(new_total_deposits),LendingPoolError::SupplyCapExceeded);
+
+    let new_lender_position = lender_account
+        .net_deposits()
+        .checked_add(received)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(pool.config.within_lender_cap(new_lender_position),LendingPoolError::LenderCapExceeded);
+
+    // price shares before the deposit changes the exchange rate
+    let shares = pool.amount_to_shares(tranche, received).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(shares>0,LendingPoolError::ZeroSharesMinted);
+
+    // interest is claimable above the average rate the lender paid
+    let held_shares = ctx.accounts.lender_share_account.amount;
+    lender_account
+        .record_shares_bought(tranche, held_shares, shares, received)
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    // mint pool shares to the lender 
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = MintTo{
+        mint : ctx.accounts.share_mint.to_account_info(),
+        to : ctx.accounts.lender_share_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds);
+
+    token_interface::mint_to(cpi_ctx, shares)?;
 
 
     // Update Lender account 
 
     lender_account.deposited_amount = lender_account
     .deposited_amount
-    .checked_add(amount)
+    .checked_add(received)
     .ok_or(LendingPoolError::ArithmeticOverflow)?;
 
-    lender_account.available_amount = lender_account
-        .available_amount
-        .checked_add(amount)
-        .ok_or(LendingPoolError::ArithmeticOverflow)?;
-
     lender_account.last_deposited_time = clock.unix_timestamp;
 
+    // pool-funded interest and losses are booked on the old stake
+    let stake = lender_account.net_deposits();
+    settle_pool_funded(pool, lender_account, stake, clock.unix_timestamp)?;
+
     // Update Pool state 
 
-    pool.total_deposits = pool.total_deposits.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    pool.total_deposits = new_total_deposits;
+
+    pool.available_liquidity = pool.available_liquidity.checked_add(received).ok_or(LendingPoolError::ArithmeticOverflow)?;
 
-    pool.available_liquidity = pool.available_liquidity.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    match tranche {
+        Tranche::Senior => {
+            pool.total_shares = pool.total_shares.checked_add(shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+        }
+        Tranche::Junior => {
+            pool.junior_deposits = pool.junior_deposits.checked_add(received).ok_or(LendingPoolError::ArithmeticOverflow)?;
+            pool.junior_shares = pool.junior_shares.checked_add(shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+        }
+    }
 
     emit!(Deposited {
         lender: ctx.accounts.lender.key(),
         pool: pool.key(),
-        amount,
-        new_available_amount: lender_account.available_amount,
+        tranche,
+        amount: received,
+        shares_minted: shares,
         new_pool_liquidity: pool.available_liquidity,
         timestamp: clock.unix_timestamp,
     });
 
     msg!(" Deposit successful");
-    msg!("Amount: {} tokens", amount);
-    msg!("Your available balance: {}", lender_account.available_amount);
+    msg!("Tranche: {:?}", tranche);
+    msg!("Amount: {} tokens", received);
+    msg!("Shares minted: {}", shares);
     msg!("Pool liquidity: {}", pool.available_liquidity);
     Ok(())
 }
\ No newline at end of file
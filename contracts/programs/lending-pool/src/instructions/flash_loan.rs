@@ -0,0 +1,285 @@
+use anchor_lang::{prelude::*, solana_program::{instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT}, sysvar::instructions::{load_current_index_checked, load_instruction_at_checked, ID as INSTRUCTIONS_SYSVAR_ID}}, Discriminator};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::MAX_FLASH_LOAN_FEE_BPS, errors::LendingPoolError, events::{FlashLoanBorrowed, FlashLoanFeeUpdated, FlashLoanRepaid}, instructions::{accrue_interest::accrue, strategy::{optional_strategy_cpi, recall_shortfall}}, states::{InterestRateModel, LendingPool, ProtocolRoles, Role, YieldStrategy}};
+
+/// Position of the pool in FlashRepay's accounts, checked by flash_borrow's introspection
+const REPAY_POOL_ACCOUNT_INDEX: usize = 1;
+
+#[derive(Accounts)]
+pub struct FlashBorrow<'info>{
+    pub borrower : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // receives the loan, any account of the pool token works
+    #[account(
+        mut,
+        constraint = borrower_token_account.mint == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub borrower_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    /// CHECK: instructions sysvar, used to find the matching flash_repay
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions : UncheckedAccount<'info>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info>{
+    pub borrower : Signer<'info>,
+
+    // must stay at REPAY_POOL_ACCOUNT_INDEX
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = borrower_token_account.mint == pool.token_mint @ LendingPoolError::InvalidTokenMint,
+        constraint = borrower_token_account.owner == borrower.key()
+    )]
+    pub borrower_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetFlashLoanFee<'info>{
+    /// Risk manager role holder (only they can change the flash loan fee)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(mut)]
+    pub pool : Account<'info,LendingPool>,
+}
+
+pub fn borrow_handler(ctx:Context<FlashBorrow>,amount:u64)->Result<()>{
+    require!(amount>0,LendingPoolError::ZeroFlashLoanAmount);
+
+    // a CPI caller could hide the repayment check behind its own instruction
+    require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,LendingPoolError::FlashLoanCpiNotAllowed);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(!pool.pause_flags.flash_loans,LendingPoolError::PoolPaused);
+
+    // also fails if a flash loan is already open
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    require!(amount<=pool.available_liquidity,LendingPoolError::InsufficientPoolLiquidity);
+
+    let fee = pool.flash_loan_fee(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    // the next lending-pool instruction in the transaction must be flash_repay on this pool
+    let instructions = ctx.accounts.instructions.to_account_info();
+    let current_index = load_current_index_checked(&instructions)? as usize;
+
+    let mut index = current_index + 1;
+    loop {
+        let ix = load_instruction_at_checked(index, &instructions)
+            .map_err(|_| error!(LendingPoolError::FlashLoanNotRepaid))?;
+
+        if ix.program_id == crate::ID {
+            require!(ix.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR),LendingPoolError::FlashLoanReentrancy);
+
+            let repay_pool = ix.accounts.get(REPAY_POOL_ACCOUNT_INDEX).ok_or(LendingPoolError::FlashLoanNotRepaid)?;
+
+            require!(repay_pool.pubkey == pool.key(),LendingPoolError::FlashLoanNotRepaid);
+            break;
+        }
+        index += 1;
+    }
+
+    // recalled before the loan opens, flash_repay measures the repayment from its own starting balance
+    let strategy_cpi = optional_strategy_cpi(
+        &ctx.accounts.yield_strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, ctx.accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), ctx.accounts.pool_token_account.amount, amount, clock.unix_timestamp)?;
+
+    if recalled > 0 {
+        ctx.accounts.pool_token_account.reload()?;
+    }
+
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    pool.flash_loan_amount = amount;
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.pool_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.borrower_token_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), amount, ctx.accounts.token_mint.decimals)?;
+
+    emit!(FlashLoanBorrowed{
+        pool: pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        amount,
+        fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Flash loan borrowed: {} tokens", amount);
+    msg!("Fee due: {} tokens", fee);
+    Ok(())
+}
+
+pub fn repay_handler(ctx:Context<FlashRepay>)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    let amount = pool.flash_loan_amount;
+
+    require!(amount>0,LendingPoolError::NoActiveFlashLoan);
+
+    let fee = pool.flash_loan_fee(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    let owed = amount.checked_add(fee).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let balance_before = ctx.accounts.pool_token_account.amount;
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.borrower_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.pool_token_account.to_account_info(),
+        authority : ctx.accounts.borrower.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account), owed, ctx.accounts.token_mint.decimals)?;
+
+    // transfer-fee mints deliver less than was sent, the pool must be made whole
+    ctx.accounts.pool_token_account.reload()?;
+
+    let received = ctx.accounts.pool_token_account.amount
+        .checked_sub(balance_before)
+        .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    require!(received>=owed,LendingPoolError::FlashLoanRepaymentTooLow);
+
+    pool.flash_loan_amount = 0;
+
+    let reserves = pool.credit_income(fee).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(FlashLoanRepaid{
+        pool: pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        amount,
+        fee,
+        reserves,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Flash loan repaid: {} tokens", amount);
+    msg!("Fee paid: {} tokens", fee);
+    Ok(())
+}
+
+pub fn set_fee_handler(ctx:Context<SetFlashLoanFee>,fee_bps:u16)->Result<()>{
+    require!(fee_bps<=MAX_FLASH_LOAN_FEE_BPS,LendingPoolError::FlashLoanFeeTooHigh);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    // the fee owed on an open loan is priced at repay time
+    require!(pool.flash_loan_amount == 0,LendingPoolError::FlashLoanActive);
+
+    let old_fee_bps = pool.flash_loan_fee_bps;
+
+    pool.flash_loan_fee_bps = fee_bps;
+
+    emit!(FlashLoanFeeUpdated{
+        pool: pool.key(),
+        authority: ctx.accounts.risk_manager.key(),
+        old_fee_bps,
+        new_fee_bps: fee_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Flash loan fee updated: {} bps", fee_bps);
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::{constants::MAX_POOL_NAME_LENGTH, errors::LendingPoolError, events::PoolInitialized, states::LendingPool};
+use crate::{constants::{ACCOUNT_VERSION, BORROW_INDEX_PRECISION, DEFAULT_BASE_RATE_BPS, DEFAULT_FLASH_LOAN_FEE_BPS, DEFAULT_JUNIOR_PREMIUM_BPS, DEFAULT_KINK_UTILIZATION, DEFAULT_RESERVE_FACTOR_BPS, DEFAULT_SLOPE1_BPS, DEFAULT_SLOPE2_BPS, MAX_POOL_NAME_LENGTH, MAX_UTILIZATION_RATE}, errors::LendingPoolError, events::PoolInitialized, states::{AccessPolicy, InterestRateModel, LendingPool, PauseFlags, PoolConfig}};
 #[derive(Accounts)]
 pub struct InitializePool<'info>{
     #[account(mut)]
@@ -16,18 +16,50 @@ pub struct InitializePool<'info>{
     pub pool : Account<'info,LendingPool>,
 
 
-    pub token_mint : Account<'info,Mint>,
+    pub token_mint : InterfaceAccount<'info,Mint>,
 
     #[account(
         constraint = pool_token_account.mint == token_mint.key(),
         constraint = pool_token_account.owner == pool.key()
     )]
-    pub pool_token_account : Account<'info,TokenAccount>,
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    /// Share (LP receipt) token, minted on deposit and burned on withdraw
+    #[account(
+        init,
+        payer = authority,
+        seeds = [LendingPool::SHARE_MINT_SEED,pool.key().as_ref()],
+        bump,
+        mint::decimals = token_mint.decimals,
+        mint::authority = pool,
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    /// Junior (first-loss) tranche share token, senior depositors hold share_mint
+    #[account(
+        init,
+        payer = authority,
+        seeds = [LendingPool::JUNIOR_SHARE_MINT_SEED,pool.key().as_ref()],
+        bump,
+        mint::decimals = token_mint.decimals,
+        mint::authority = pool,
+    )]
+    pub junior_share_mint : InterfaceAccount<'info,Mint>,
+
+    /// Rate model used by loan-manager to price loans against this pool
+    #[account(
+        init,
+        payer = authority,
+        space = InterestRateModel::LEN,
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
 
     pub system_program: Program<'info, System>,
 
     /// Token program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn handler(ctx:Context<InitializePool>,pool_name : String)->Result<()>{
@@ -38,27 +70,73 @@ pub fn handler(ctx:Context<InitializePool>,pool_name : String)->Result<()>{
 
     pool.authority = ctx.accounts.authority.key();
 
+    pool.pending_authority = Pubkey::default();
+
+    pool.access_policy = AccessPolicy::default();
+
+    pool.flash_loan_fee_bps = DEFAULT_FLASH_LOAN_FEE_BPS;
+    pool.flash_loan_amount = 0;
+
+    pool.pool_funded_principal = 0;
+    pool.total_lender_stake = 0;
+    pool.interest_per_stake = 0;
+    pool.loss_per_stake = 0;
+    pool.bad_debt = 0;
+    pool.bad_debt_stake_loss = 0;
+
     pool.token_mint = ctx.accounts.token_mint.key();
 
     pool.pool_token_account = ctx.accounts.pool_token_account.key();
 
+    pool.share_mint = ctx.accounts.share_mint.key();
+
+    pool.junior_share_mint = ctx.accounts.junior_share_mint.key();
+
     pool.pool_name = pool_name.clone();
 
     pool.total_deposits = 0;
+    pool.total_shares = 0;
     pool.available_liquidity = 0;
     pool.total_borrowed = 0;
     pool.cumulative_interest = 0;
+    pool.borrow_index = BORROW_INDEX_PRECISION;
+    pool.last_accrual_timestamp = clock.unix_timestamp;
+    pool.treasury = Pubkey::default();
+    pool.reserve_factor_bps = DEFAULT_RESERVE_FACTOR_BPS;
+    pool.total_reserves = 0;
+    pool.junior_deposits = 0;
+    pool.junior_shares = 0;
+    pool.junior_premium_bps = DEFAULT_JUNIOR_PREMIUM_BPS;
+    pool.config = PoolConfig::default_for_decimals(ctx.accounts.token_mint.decimals).ok_or(LendingPoolError::ArithmeticOverflow)?;
     pool.active_loans_count = 0;
     pool.total_loans_count = 0;
-    pool.paused = false;
+    pool.pause_flags = PauseFlags::default();
     pool.created_at = clock.unix_timestamp;
+    pool.unpaid_interest = 0;
+    pool.unpaid_junior_interest = 0;
+    pool.unpaid_reserves = 0;
+    pool.pending_defaults = 0;
+    pool.version = ACCOUNT_VERSION;
     pool.bump = ctx.bumps.pool;
 
+    let model = &mut ctx.accounts.interest_rate_model;
+    model.pool = pool_key;
+    model.base_rate_bps = DEFAULT_BASE_RATE_BPS;
+    model.slope1_bps = DEFAULT_SLOPE1_BPS;
+    model.kink_utilization = DEFAULT_KINK_UTILIZATION;
+    model.slope2_bps = DEFAULT_SLOPE2_BPS;
+    model.max_utilization = MAX_UTILIZATION_RATE;
+    model.updated_at = clock.unix_timestamp;
+    model.version = ACCOUNT_VERSION;
+    model.bump = ctx.bumps.interest_rate_model;
+
 
     emit!(PoolInitialized{
         pool: pool_key,
         authority: pool.authority,
         token_mint: pool.token_mint,
+        share_mint: pool.share_mint,
+        junior_share_mint: pool.junior_share_mint,
         pool_name,
         timestamp: clock.unix_timestamp,
     });
@@ -66,5 +144,7 @@ pub fn handler(ctx:Context<InitializePool>,pool_name : String)->Result<()>{
     msg!("Pool initialized successfully");
     msg!("Pool address: {}", pool.key());
     msg!("Token mint: {}", pool.token_mint);
+    msg!("Share mint: {}", pool.share_mint);
+    msg!("Junior share mint: {}", pool.junior_share_mint);
     Ok(())
 }
\ No newline at end of file
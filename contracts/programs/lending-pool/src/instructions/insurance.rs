@@ -0,0 +1,438 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::ACCOUNT_VERSION, errors::LendingPoolError, events::{BadDebtCovered, BadDebtSocialized, InsuranceFundInitialized, InsuranceFunded}, instructions::{accrue_interest::{accrue, accrue_and_emit}, strategy::{optional_strategy_cpi, recall_shortfall}}, states::{InsuranceFund, InterestRateModel, LendingPool, YieldStrategy}};
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info>{
+    /// Pool authority (only they can set up the fund)
+    #[account(mut)]
+    pub authority : Signer<'info>,
+
+    #[account(
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = InsuranceFund::LEN,
+        seeds = [InsuranceFund::SEED_PREFIX,pool.key().as_ref()],
+        bump
+    )]
+    pub insurance_fund : Account<'info,InsuranceFund>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    // owned by the pool so absorb_bad_debt can pay out of it
+    #[account(
+        init,
+        payer = authority,
+        seeds = [InsuranceFund::VAULT_SEED,pool.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = pool,
+    )]
+    pub insurance_vault : InterfaceAccount<'info,TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Anyone can stake tokens into the fund, contributions are not withdrawable
+#[derive(Accounts)]
+pub struct ContributeInsurance<'info>{
+    pub contributor : Signer<'info>,
+
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [InsuranceFund::SEED_PREFIX,pool.key().as_ref()],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund : Account<'info,InsuranceFund>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
+        constraint = contributor_token_account.mint == pool.token_mint,
+        constraint = contributor_token_account.owner == contributor.key()
+    )]
+    pub contributor_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = insurance_vault.key() == insurance_fund.vault @ LendingPoolError::InvalidInsuranceVault
+    )]
+    pub insurance_vault : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct FundInsuranceFromReserves<'info>{
+    /// Pool authority (only they can spend reserves)
+    pub authority : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        mut,
+        seeds = [InsuranceFund::SEED_PREFIX,pool.key().as_ref()],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund : Account<'info,InsuranceFund>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = insurance_vault.key() == insurance_fund.vault @ LendingPoolError::InvalidInsuranceVault
+    )]
+    pub insurance_vault : InterfaceAccount<'info,TokenAccount>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless crank, realizes bad debt recorded by loan-manager's write_off_loan
+#[derive(Accounts)]
+pub struct AbsorbBadDebt<'info>{
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // always passed so a caller cannot skip the fund, pools without one socialize the whole loss
+    /// CHECK: the pool's insurance fund PDA, deserialized in the handler when it exists
+    #[account(
+        mut,
+        seeds = [InsuranceFund::SEED_PREFIX,pool.key().as_ref()],
+        bump,
+    )]
+    pub insurance_fund : UncheckedAccount<'info>,
+
+    // required once the pool has an insurance fund
+    #[account(
+        mut,
+        seeds = [InsuranceFund::VAULT_SEED,pool.key().as_ref()],
+        bump,
+    )]
+    pub insurance_vault : Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn initialize_insurance_handler(ctx:Context<InitializeInsuranceFund>)->Result<()>{
+    let clock = Clock::get()?;
+    let fund = &mut ctx.accounts.insurance_fund;
+
+    fund.pool = ctx.accounts.pool.key();
+    fund.vault = ctx.accounts.insurance_vault.key();
+    fund.total_contributed = 0;
+    fund.total_from_reserves = 0;
+    fund.total_covered = 0;
+    fund.created_at = clock.unix_timestamp;
+    fund.version = ACCOUNT_VERSION;
+    fund.bump = ctx.bumps.insurance_fund;
+
+    emit!(InsuranceFundInitialized{
+        pool: fund.pool,
+        insurance_fund: fund.key(),
+        vault: fund.vault,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Insurance fund initialized");
+    msg!("Vault: {}", fund.vault);
+    Ok(())
+}
+
+pub fn contribute_handler(ctx:Context<ContributeInsurance>,amount:u64)->Result<()>{
+    require!(amount>0,LendingPoolError::ZeroInsuranceAmount);
+
+    let clock = Clock::get()?;
+
+    let balance_before = ctx.accounts.insurance_vault.amount;
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.contributor_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.insurance_vault.to_account_info(),
+        authority : ctx.accounts.contributor.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account), amount, ctx.accounts.token_mint.decimals)?;
+
+    // only credit what arrived, transfer-fee mints withhold part of it
+    ctx.accounts.insurance_vault.reload()?;
+
+    let fund_balance = ctx.accounts.insurance_vault.amount;
+    let received = fund_balance.checked_sub(balance_before).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    let fund = &mut ctx.accounts.insurance_fund;
+
+    fund.total_contributed = fund.total_contributed.checked_add(received).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(InsuranceFunded{
+        pool: fund.pool,
+        contributor: ctx.accounts.contributor.key(),
+        amount: received,
+        from_reserves: false,
+        fund_balance,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Insurance contribution: {} tokens", received);
+    msg!("Fund balance: {}", fund_balance);
+    Ok(())
+}
+
+pub fn fund_from_reserves_handler(ctx:Context<FundInsuranceFromReserves>,amount:u64)->Result<()>{
+    require!(amount>0,LendingPoolError::ZeroInsuranceAmount);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // same limits as collect_reserves, accrued but unpaid reserves are not in the pool
+    require!(amount<=pool.total_reserves,LendingPoolError::InsufficientReserves);
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+    require!(unreserved_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    // funds deployed in the yield strategy come back when the pool account is short
+    let strategy_cpi = optional_strategy_cpi(
+        &ctx.accounts.yield_strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, ctx.accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), ctx.accounts.pool_token_account.amount, amount, clock.unix_timestamp)?;
+
+    if recalled > 0 {
+        ctx.accounts.pool_token_account.reload()?;
+    }
+
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.pool_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.insurance_vault.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), amount, ctx.accounts.token_mint.decimals)?;
+
+    pool.total_reserves = pool.total_reserves.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+    pool.available_liquidity = pool.available_liquidity.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    ctx.accounts.insurance_vault.reload()?;
+
+    let fund = &mut ctx.accounts.insurance_fund;
+
+    fund.total_from_reserves = fund.total_from_reserves.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(InsuranceFunded{
+        pool: pool.key(),
+        contributor: ctx.accounts.authority.key(),
+        amount,
+        from_reserves: true,
+        fund_balance: ctx.accounts.insurance_vault.amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Reserves moved to insurance: {} tokens", amount);
+    msg!("Remaining reserves: {}", pool.total_reserves);
+    Ok(())
+}
+
+pub fn absorb_bad_debt_handler(ctx:Context<AbsorbBadDebt>)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(pool.bad_debt>0,LendingPoolError::NoBadDebt);
+
+    require!(pool.flash_loan_amount == 0,LendingPoolError::FlashLoanActive);
+
+    // accrue skips pools with bad debt, interest up to now is still owed by the other loans
+    accrue_and_emit(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let bad_debt = pool.bad_debt;
+
+    // 1. insurance fund covers as much as it holds
+    let mut covered = 0u64;
+
+    let fund_info = ctx.accounts.insurance_fund.to_account_info();
+
+    if !fund_info.data_is_empty() {
+        require_keys_eq!(*fund_info.owner,crate::ID,ErrorCode::AccountOwnedByWrongProgram);
+        let mut fund = InsuranceFund::try_deserialize(&mut &fund_info.try_borrow_data()?[..])?;
+        let vault = ctx.accounts.insurance_vault.as_mut().ok_or(LendingPoolError::InsuranceVaultRequired)?;
+        require!(vault.key() == fund.vault,LendingPoolError::InvalidInsuranceVault);
+
+        covered = bad_debt.min(vault.amount);
+
+        if covered > 0 {
+            let seeds = &[
+                LendingPool::SEED_PREFIX,
+                pool.token_mint.as_ref(),
+                &[pool.bump],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            let cpi_account = TransferChecked{
+                from : vault.to_account_info(),
+                mint : ctx.accounts.token_mint.to_account_info(),
+                to : ctx.accounts.pool_token_account.to_account_info(),
+                authority : pool.to_account_info()
+            };
+
+            token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), covered, ctx.accounts.token_mint.decimals)?;
+
+            vault.reload()?;
+
+            fund.total_covered = fund.total_covered.checked_add(covered).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            // the payout replaces the lost loan value, depositors are made whole for this part
+            pool.available_liquidity = pool.available_liquidity.checked_add(covered).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            emit!(BadDebtCovered{
+                pool: pool.key(),
+                amount: covered,
+                fund_balance: vault.amount,
+                timestamp: clock.unix_timestamp,
+            });
+
+            msg!("Covered by insurance: {} tokens", covered);
+        }
+
+        fund.try_serialize(&mut &mut fund_info.try_borrow_mut_data()?[..])?;
+    }
+
+    // 2. whatever is left is socialized, junior tranche first
+    let shortfall = bad_debt - covered;
+
+    if shortfall > 0 {
+        let (junior_loss, senior_loss) = pool.allocate_loss(shortfall).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+        // pool-funded principal is charged to lender stakes in the same proportion
+        let stake_loss = (pool.bad_debt_stake_loss as u128)
+            .checked_mul(shortfall as u128)
+            .and_then(|v| v.checked_div(bad_debt as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        pool.charge_pool_funded_loss(stake_loss).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        emit!(BadDebtSocialized{
+            pool: pool.key(),
+            amount: shortfall,
+            junior_loss,
+            senior_loss,
+            stake_loss,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Socialized across depositors: {} tokens", shortfall);
+        msg!("  Junior tranche: {}", junior_loss);
+        msg!("  Senior tranche: {}", senior_loss);
+    }
+
+    pool.bad_debt = 0;
+    pool.bad_debt_stake_loss = 0;
+
+    msg!(" Bad debt absorbed: {} tokens", bad_debt);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::LendingPoolError, events::InterestRateModelUpdated, instructions::accrue_interest::accrue, states::{InterestRateModel, LendingPool, ProtocolRoles, Role}};
+
+#[derive(Accounts)]
+pub struct SetInterestRateModel<'info>{
+    /// Risk manager role holder (only they can set the rate model)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    // interest up to now is accrued at the old rates first
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+}
+
+pub fn handler(
+    ctx:Context<SetInterestRateModel>,
+    base_rate_bps : u16,
+    slope1_bps : u16,
+    kink_utilization : u64,
+    slope2_bps : u16,
+    max_utilization : u64,
+)->Result<()>{
+    let clock = Clock::get()?;
+
+    accrue(&mut ctx.accounts.pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let model = &mut ctx.accounts.interest_rate_model;
+
+    // kink has to leave room for the steep slope, max utilization is a percentage
+    require!(kink_utilization>0 && kink_utilization<100,LendingPoolError::InvalidInterestRateModel);
+    require!(max_utilization>0 && max_utilization<=100,LendingPoolError::InvalidInterestRateModel);
+
+    let max_rate = (base_rate_bps as u32) + (slope1_bps as u32) + (slope2_bps as u32);
+    require!(max_rate<=u16::MAX as u32,LendingPoolError::InvalidInterestRateModel);
+
+    model.base_rate_bps = base_rate_bps;
+    model.slope1_bps = slope1_bps;
+    model.kink_utilization = kink_utilization;
+    model.slope2_bps = slope2_bps;
+    model.max_utilization = max_utilization;
+    model.updated_at = clock.unix_timestamp;
+
+    emit!(InterestRateModelUpdated{
+        pool: model.pool,
+        base_rate_bps,
+        slope1_bps,
+        kink_utilization,
+        slope2_bps,
+        max_utilization,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Interest rate model updated");
+    msg!("Current utilization: {}%", ctx.accounts.pool.calculate_utilization());
+    msg!("Current borrow rate: {} bps", model.borrow_rate(ctx.accounts.pool.calculate_utilization()));
+
+    Ok(())
+}
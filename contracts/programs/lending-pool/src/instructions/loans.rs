@@ -0,0 +1,532 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::{LOAN_AUTHORITY_SEED, LOAN_MANAGER_PROGRAM_ID, REPAYMENT_QUEUE_FILLS}, errors::LendingPoolError, events::{LenderFundsReleased, LenderFundsReserved, LiquidityLocked, LiquidityReleased, LoanDefaultRecorded, LoanLossRecorded}, instructions::{accrue_interest::accrue, strategy::{optional_strategy_cpi, recall_shortfall}, withdrawal_queue::{fill_queue, QueueFill}}, states::{InterestRateModel, LenderAccount, LendingPool, Tranche, WithdrawalQueue, YieldStrategy}};
+
+// Loan bookkeeping, only LoanManager can call these: it signs with its loan_authority PDA.
+// The lender account is passed for loans funded by one lender and left out for pool-funded loans.
+
+#[derive(Accounts)]
+pub struct DisburseLoanFunds<'info>{
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+        seeds::program = LOAN_MANAGER_PROGRAM_ID,
+    )]
+    pub loan_authority : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        mut,
+        constraint = lender_account.pool == pool.key() @ LendingPoolError::UnauthorizedAccess
+    )]
+    pub lender_account : Option<Account<'info,LenderAccount>>,
+
+    // the lender's senior shares, a lender can lend what they are worth minus what is already lent
+    #[account(
+        constraint = lender_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = lender_account.as_ref().is_some_and(|l| l.lender == lender_share_account.owner) @ LendingPoolError::UnauthorizedAccess
+    )]
+    pub lender_share_account : Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = borrower_token_account.mint == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub borrower_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// funds a lender promises to standing offers in LoanManager's offer book, they stay
+// reserved until a matched loan draws on them at disbursement or the offer is cancelled
+#[derive(Accounts)]
+pub struct ReserveLenderFunds<'info>{
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+        seeds::program = LOAN_MANAGER_PROGRAM_ID,
+    )]
+    pub loan_authority : Signer<'info>,
+
+    #[account(
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        constraint = lender_account.pool == pool.key() @ LendingPoolError::UnauthorizedAccess
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    // the lender's senior shares, only needed to reserve: reservations count against their value like loans
+    #[account(
+        constraint = lender_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender_account.lender @ LendingPoolError::UnauthorizedAccess
+    )]
+    pub lender_share_account : Option<InterfaceAccount<'info,TokenAccount>>,
+}
+
+// repaid liquidity fills queued withdrawals first when the queue accounts are passed, with the
+// requests' destination token accounts as remaining accounts. Without them, or at the first
+// destination that cannot be paid, the repayment is booked and process_withdrawal_queue fills the rest
+#[derive(Accounts)]
+pub struct RecordLoanRepayment<'info>{
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+        seeds::program = LOAN_MANAGER_PROGRAM_ID,
+    )]
+    pub loan_authority : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        constraint = lender_account.pool == pool.key() @ LendingPoolError::UnauthorizedAccess
+    )]
+    pub lender_account : Option<Account<'info,LenderAccount>>,
+
+    #[account(
+        mut,
+        seeds = [WithdrawalQueue::SEED_PREFIX,pool.key().as_ref()],
+        bump = withdrawal_queue.bump
+    )]
+    pub withdrawal_queue : Option<Account<'info,WithdrawalQueue>>,
+
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : Option<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        seeds = [WithdrawalQueue::ESCROW_SEED,pool.key().as_ref()],
+        bump,
+    )]
+    pub escrow_share_account : Option<InterfaceAccount<'info,TokenAccount>>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : Option<InterfaceAccount<'info,Mint>>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : Option<InterfaceAccount<'info,TokenAccount>>,
+
+    pub token_program : Option<Interface<'info, TokenInterface>>,
+}
+
+// defaults and write-offs return no liquidity, so nothing is filled
+#[derive(Accounts)]
+pub struct RecordLoanOutcome<'info>{
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+        seeds::program = LOAN_MANAGER_PROGRAM_ID,
+    )]
+    pub loan_authority : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        constraint = lender_account.pool == pool.key() @ LendingPoolError::UnauthorizedAccess
+    )]
+    pub lender_account : Option<Account<'info,LenderAccount>>,
+}
+
+/// Lend `amount` of pool liquidity to a borrower, returns the borrow index the loan starts at
+pub fn disburse_handler(ctx:Context<DisburseLoanFunds>,loan_id:u64,amount:u64,reserved:u64)->Result<u128>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(!pool.pause_flags.disbursements,LendingPoolError::PoolPaused);
+
+    // the new debt is recorded against an up to date index, fails on an open flash loan or bad debt
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // liquidity returned by repayments goes to queued withdrawals before new loans
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(unreserved_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    // utilization after this loan must stay within the rate model's cap
+    require!(
+        pool.projected_utilization(amount) <= ctx.accounts.interest_rate_model.max_utilization,
+        LendingPoolError::UtilizationTooHigh
+    );
+
+    let strategy_cpi = optional_strategy_cpi(
+        &ctx.accounts.yield_strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, ctx.accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), ctx.accounts.pool_token_account.amount, amount, clock.unix_timestamp)?;
+
+    if recalled > 0 {
+        ctx.accounts.pool_token_account.reload()?;
+    }
+
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    let lender = match ctx.accounts.lender_account.as_mut() {
+        Some(lender_account) => {
+            // the offer reservation the loan was matched against is replaced by the loan itself
+            lender_account.reserved_amount = lender_account.reserved_amount.checked_sub(reserved).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+            let lender_shares = ctx.accounts.lender_share_account.as_ref().ok_or(LendingPoolError::LenderShareAccountRequired)?.amount;
+
+            let senior_value = pool.shares_to_amount(Tranche::Senior, lender_shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            require!(lender_account.lendable_amount(senior_value)>=amount,LendingPoolError::InsufficientAvailableBalance);
+
+            lender_account.lent_amount = lender_account.lent_amount.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            lender_account.lender
+        }
+        None => {
+            require!(reserved == 0,LendingPoolError::UnauthorizedAccess);
+
+            // every depositor carries this principal through the share price
+            pool.pool_funded_principal = pool.pool_funded_principal.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            pool.key()
+        }
+    };
+
+    pool.available_liquidity = pool.available_liquidity.checked_sub(amount).ok_or(LendingPoolError::InsufficientPoolLiquidity)?;
+
+    pool.total_borrowed = pool.total_borrowed.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    pool.active_loans_count = pool.active_loans_count.checked_add(1).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.pool_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.borrower_token_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), amount, ctx.accounts.token_mint.decimals)?;
+
+    emit!(LiquidityLocked{
+        pool: pool.key(),
+        lender,
+        principal: amount,
+        // interest accrues through the borrow index from here on
+        interest: 0,
+        loan_id,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Loan #{} disbursed: {} tokens", loan_id, amount);
+    msg!("Available liquidity: {}", pool.available_liquidity);
+    msg!("Total borrowed: {}", pool.total_borrowed);
+    Ok(pool.borrow_index)
+}
+
+/// Book a repayment LoanManager has already transferred into pool_token_account
+/// `closed` is set when it paid the loan off
+pub fn repayment_handler<'info>(ctx:Context<'_, '_, 'info, 'info, RecordLoanRepayment<'info>>,loan_id:u64,principal:u64,interest:u64,closed:bool)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(!pool.pause_flags.repayments,LendingPoolError::PoolPaused);
+
+    require!(pool.flash_loan_amount == 0,LendingPoolError::FlashLoanActive);
+
+    // repayments fill queued withdrawals at share prices, which wait for absorb_bad_debt
+    require!(pool.bad_debt == 0,LendingPoolError::BadDebtPending);
+
+    let repaid = principal.checked_add(interest).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    // split as it accrued, the protocol's part becomes collectable reserves and lenders' part redeemable
+    let (lender_interest, reserve_portion) = pool.settle_unpaid_interest(interest);
+
+    pool.total_reserves = pool.total_reserves.checked_add(reserve_portion).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    pool.available_liquidity = pool.available_liquidity.checked_add(repaid).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    // per-loan rounding can leave the loan's debt a unit above the pool aggregate
+    pool.total_borrowed = pool.total_borrowed.saturating_sub(repaid);
+
+    pool.cumulative_interest = pool.cumulative_interest.checked_add(lender_interest).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let lender = match ctx.accounts.lender_account.as_mut() {
+        Some(lender_account) => {
+            lender_account.lent_amount = lender_account.lent_amount.checked_sub(principal).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+            lender_account.lender
+        }
+        None => {
+            pool.pool_funded_principal = pool.pool_funded_principal.saturating_sub(principal);
+
+            // every lender earns the interest pro-rata to their stake
+            pool.credit_pool_funded_interest(lender_interest).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            pool.key()
+        }
+    };
+
+    if closed {
+        pool.active_loans_count = pool.active_loans_count.saturating_sub(1);
+    }
+
+    emit!(LiquidityReleased{
+        pool: pool.key(),
+        lender,
+        principal,
+        interest,
+        loan_id,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Loan #{} repayment booked", loan_id);
+    msg!("Principal: {}", principal);
+    msg!("Interest: {}", interest);
+
+    // queued withdrawals have first claim on repaid liquidity
+    if pool.queued_withdrawal_shares > 0 && !pool.pause_flags.withdrawals {
+        let accounts = &mut *ctx.accounts;
+
+        let (Some(queue), Some(share_mint), Some(escrow_share_account), Some(token_mint), Some(pool_token_account), Some(token_program)) = (
+            accounts.withdrawal_queue.as_mut(),
+            accounts.share_mint.as_ref(),
+            accounts.escrow_share_account.as_ref(),
+            accounts.token_mint.as_ref(),
+            accounts.pool_token_account.as_ref(),
+            accounts.token_program.as_ref(),
+        ) else {
+            msg!("Withdrawal queue accounts not passed, queued withdrawals left to the crank");
+            return Ok(());
+        };
+
+        let fill = QueueFill{
+            share_mint: share_mint.to_account_info(),
+            escrow_share_account: escrow_share_account.to_account_info(),
+            token_mint: token_mint.to_account_info(),
+            token_decimals: token_mint.decimals,
+            pool_token_account: pool_token_account.to_account_info(),
+            token_program: token_program.to_account_info(),
+            strategy: None,
+            best_effort: true,
+        };
+
+        // the repayment just arrived in the pool account, process_withdrawal_queue recalls from the strategy
+        // a repayment never fails on the queue, a destination it cannot pay stops the fills
+        let fills = fill_queue(&mut accounts.pool, queue, &fill, None, ctx.remaining_accounts, REPAYMENT_QUEUE_FILLS, clock.unix_timestamp)?;
+
+        msg!("Queued withdrawals filled: {}", fills);
+    }
+
+    Ok(())
+}
+
+/// Book a written-off loan: `loss` (its debt) leaves total_borrowed and all but its unpaid
+/// reserves wait in bad_debt for absorb_bad_debt, `principal` is the part of it the lender or the pool had lent
+pub fn write_off_handler(ctx:Context<RecordLoanOutcome>,loan_id:u64,principal:u64,loss:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(!pool.pause_flags.liquidations,LendingPoolError::PoolPaused);
+
+    require!(pool.flash_loan_amount == 0,LendingPoolError::FlashLoanActive);
+
+    let loss = loss.min(pool.total_borrowed);
+
+    pool.total_borrowed -= loss;
+
+    pool.active_loans_count = pool.active_loans_count.saturating_sub(1);
+
+    // the default's loss is known now, junior can leave once none are pending
+    pool.pending_defaults = pool.pending_defaults.saturating_sub(1);
+
+    // the loan's accrued interest will never be paid, reserves on it are dropped
+    // and depositors lose the principal plus their part of the interest
+    let (_, unpaid_reserves) = pool.settle_unpaid_interest(loss.saturating_sub(principal));
+    let loss = loss - unpaid_reserves;
+
+    // covered from the insurance fund first, then socialized through the share price
+    pool.record_bad_debt(loss).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let lender = match ctx.accounts.lender_account.as_mut() {
+        Some(lender_account) => {
+            lender_account.lent_amount = lender_account.lent_amount.saturating_sub(principal);
+
+            lender_account.lender
+        }
+        None => {
+            pool.pool_funded_principal = pool.pool_funded_principal.saturating_sub(principal);
+
+            // charged to lender stakes once absorb_bad_debt knows the uncovered part
+            pool.bad_debt_stake_loss = pool.bad_debt_stake_loss.checked_add(principal).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+            pool.key()
+        }
+    };
+
+    emit!(LoanLossRecorded{
+        pool: pool.key(),
+        lender,
+        principal,
+        loss,
+        bad_debt: pool.bad_debt,
+        loan_id,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Loan #{} written off: {} tokens", loan_id, loss);
+    msg!("Pending bad debt: {}", pool.bad_debt);
+    Ok(())
+}
+
+/// Count a loan LoanManager has defaulted, until its write-off junior exits are frozen
+pub fn default_handler(ctx:Context<RecordLoanOutcome>,loan_id:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(!pool.pause_flags.liquidations,LendingPoolError::PoolPaused);
+
+    pool.pending_defaults = pool.pending_defaults.checked_add(1).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(LoanDefaultRecorded{
+        pool: pool.key(),
+        loan_id,
+        pending_defaults: pool.pending_defaults,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Loan #{} defaulted", loan_id);
+    msg!("Defaults pending write-off: {}", pool.pending_defaults);
+    Ok(())
+}
+
+/// Reserve `amount` of the lender's lendable funds for a standing offer
+pub fn reserve_handler(ctx:Context<ReserveLenderFunds>,amount:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &ctx.accounts.pool;
+    let lender_account = &mut ctx.accounts.lender_account;
+
+    let lender_shares = ctx.accounts.lender_share_account.as_ref().ok_or(LendingPoolError::LenderShareAccountRequired)?.amount;
+
+    let senior_value = pool.shares_to_amount(Tranche::Senior, lender_shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(lender_account.lendable_amount(senior_value)>=amount,LendingPoolError::InsufficientAvailableBalance);
+
+    lender_account.reserved_amount = lender_account.reserved_amount.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(LenderFundsReserved{
+        pool: pool.key(),
+        lender: lender_account.lender,
+        amount,
+        reserved_amount: lender_account.reserved_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Reserved {} for offers", amount);
+    msg!("Total reserved: {}", lender_account.reserved_amount);
+    Ok(())
+}
+
+/// Give back `amount` of the lender's offer reservation
+pub fn release_reservation_handler(ctx:Context<ReserveLenderFunds>,amount:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let lender_account = &mut ctx.accounts.lender_account;
+
+    lender_account.reserved_amount = lender_account.reserved_amount.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    emit!(LenderFundsReleased{
+        pool: ctx.accounts.pool.key(),
+        lender: lender_account.lender,
+        amount,
+        reserved_amount: lender_account.reserved_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Released {} of offer reservations", amount);
+    msg!("Total reserved: {}", lender_account.reserved_amount);
+    Ok(())
+}
@@ -0,0 +1,278 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::ACCOUNT_VERSION, errors::LendingPoolError, events::{DepositLocked, DepositUnlocked}, instructions::accrue_interest::accrue, states::{InterestRateModel, LendingPool, LockPosition, Tranche}};
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct LockDeposit<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        init,
+        payer = lender,
+        space = LockPosition::LEN,
+        seeds = [
+            LockPosition::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+            position_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub lock_position : Account<'info,LockPosition>,
+
+    #[account(
+        constraint = share_mint.key() == pool.share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = lender,
+        seeds = [LockPosition::ESCROW_SEED,pool.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = pool,
+    )]
+    pub lock_escrow : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender.key()
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct UnlockDeposit<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // closed on unlock, rent goes back to the lender
+    #[account(
+        mut,
+        close = lender,
+        seeds = [
+            LockPosition::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+            position_id.to_le_bytes().as_ref(),
+        ],
+        bump = lock_position.bump,
+        constraint = lock_position.lender == lender.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub lock_position : Account<'info,LockPosition>,
+
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
+        seeds = [LockPosition::ESCROW_SEED,pool.key().as_ref()],
+        bump,
+    )]
+    pub lock_escrow : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender.key()
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn lock_handler(ctx:Context<LockDeposit>,position_id:u64,shares:u64,lock_days:u16)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    let position = &mut ctx.accounts.lock_position;
+
+    require!(shares>0,LendingPoolError::ZeroLockAmount);
+
+    let multiplier_bps = LockPosition::multiplier_for_term(lock_days).ok_or(LendingPoolError::InvalidLockTerm)?;
+
+    require!(ctx.accounts.lender_share_account.amount>=shares,LendingPoolError::InsufficientShares);
+
+    // interest up to now belongs to the unlocked shares, boost only applies from here
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let value_at_lock = pool.shares_to_amount(Tranche::Senior, shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.lender_share_account.to_account_info(),
+        mint : ctx.accounts.share_mint.to_account_info(),
+        to : ctx.accounts.lock_escrow.to_account_info(),
+        authority : ctx.accounts.lender.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account), shares, ctx.accounts.share_mint.decimals)?;
+
+    let lock_seconds = (lock_days as i64) * 24 * 60 * 60;
+
+    position.lender = ctx.accounts.lender.key();
+    position.pool = pool.key();
+    position.position_id = position_id;
+    position.shares = shares;
+    position.value_at_lock = value_at_lock;
+    position.lock_days = lock_days;
+    position.multiplier_bps = multiplier_bps;
+    position.locked_at = clock.unix_timestamp;
+    position.unlocks_at = clock.unix_timestamp.checked_add(lock_seconds).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    position.version = ACCOUNT_VERSION;
+    position.bump = ctx.bumps.lock_position;
+
+    emit!(DepositLocked{
+        pool: pool.key(),
+        lender: position.lender,
+        position_id,
+        shares,
+        value_at_lock,
+        lock_days,
+        multiplier_bps,
+        unlocks_at: position.unlocks_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Deposit locked for {} days", lock_days);
+    msg!("Shares locked: {}", shares);
+    msg!("Value at lock: {} tokens", value_at_lock);
+    msg!("Yield multiplier: {} bps", multiplier_bps);
+    msg!("Unlocks at: {}", position.unlocks_at);
+    Ok(())
+}
+
+pub fn unlock_handler(ctx:Context<UnlockDeposit>,position_id:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    let position = &ctx.accounts.lock_position;
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let early = !position.is_matured(clock.unix_timestamp);
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let mut bonus = 0u64;
+    let mut bonus_shares = 0u64;
+    let mut penalty_shares = 0u64;
+
+    if early {
+        // forfeit the boost and burn the penalty, which raises the exchange rate for everyone else
+        penalty_shares = position.early_unlock_penalty().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        if penalty_shares > 0 {
+            let cpi_account = Burn{
+                mint : ctx.accounts.share_mint.to_account_info(),
+                from : ctx.accounts.lock_escrow.to_account_info(),
+                authority : pool.to_account_info()
+            };
+
+            token_interface::burn(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), penalty_shares)?;
+        }
+    } else {
+        // boosted yield comes out of protocol reserves: they move into the senior tranche
+        // and the lender gets shares for them, so other lenders aren't diluted
+        let current_value = pool.shares_to_amount(Tranche::Senior, position.shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+        bonus = position.bonus_amount(current_value, clock.unix_timestamp).ok_or(LendingPoolError::ArithmeticOverflow)?.min(pool.total_reserves);
+
+        bonus_shares = pool.amount_to_shares(Tranche::Senior, bonus).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        if bonus_shares > 0 {
+            let cpi_account = MintTo{
+                mint : ctx.accounts.share_mint.to_account_info(),
+                to : ctx.accounts.lender_share_account.to_account_info(),
+                authority : pool.to_account_info()
+            };
+
+            token_interface::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), bonus_shares)?;
+        }
+    }
+
+    let shares_returned = position.shares.checked_sub(penalty_shares).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.lock_escrow.to_account_info(),
+        mint : ctx.accounts.share_mint.to_account_info(),
+        to : ctx.accounts.lender_share_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), shares_returned, ctx.accounts.share_mint.decimals)?;
+
+    pool.total_reserves -= bonus;
+    pool.total_deposits = pool.total_deposits.checked_add(bonus).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    pool.total_shares = pool
+        .total_shares
+        .checked_add(bonus_shares)
+        .and_then(|v| v.checked_sub(penalty_shares))
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(DepositUnlocked{
+        pool: pool.key(),
+        lender: position.lender,
+        position_id,
+        shares_returned,
+        bonus_shares,
+        penalty_shares,
+        early,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if early {
+        msg!(" Early unlock, penalty: {} shares returned to the pool", penalty_shares);
+    } else {
+        msg!(" Matured unlock, bonus: {} tokens in {} shares", bonus, bonus_shares);
+    }
+    msg!("Shares returned: {}", shares_returned);
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::{ACCOUNT_VERSION, BORROW_INDEX_PRECISION, DEFAULT_CONFIG_DECIMALS, DEFAULT_FLASH_LOAN_FEE_BPS, DEFAULT_JUNIOR_PREMIUM_BPS, DEFAULT_RESERVE_FACTOR_BPS}, errors::LendingPoolError, events::AccountMigrated, migration::{migrate_account, Migrate}, states::{AccessPolicy, LenderAccount, LendingPool, PauseFlags, PoolConfig}};
+
+// permissionless: the new layout is derived from the account's own data only
+#[derive(Accounts)]
+pub struct MigrateAccount<'info>{
+    // tops up the rent for the larger layout
+    #[account(mut)]
+    pub payer : Signer<'info>,
+
+    /// CHECK: baseline layouts do not deserialize as the current one, discriminator and size are checked in migrate
+    #[account(
+        mut,
+        owner = crate::ID @ LendingPoolError::InvalidAccountLayout
+    )]
+    pub account : UncheckedAccount<'info>,
+
+    pub system_program : Program<'info,System>,
+}
+
+/// LendingPool as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct LendingPoolV0{
+    pub authority : Pubkey,
+
+    pub token_mint : Pubkey,
+
+    pub pool_token_account: Pubkey,
+
+    pub pool_name : String,
+
+    pub total_deposits : u64,
+
+    pub available_liquidity : u64,
+
+    pub total_borrowed : u64,
+
+    pub cumulative_interest : u64,
+
+    pub active_loans_count : u32,
+
+    pub total_loans_count : u32,
+
+    pub paused : bool,
+
+    pub created_at : i64,
+
+    pub bump : u8
+}
+
+impl Migrate for LendingPool {
+    type V0 = LendingPoolV0;
+    const V0_SPACE: usize = 208;
+    const SPACE: usize = LendingPool::LEN;
+
+    // baseline deposits were never tokenized, so the pool has no share mints and no shares
+    // until the authority sets them up. The token mint is not passed, its limits start
+    // at the DEFAULT_CONFIG_DECIMALS defaults for the risk manager to rescale
+    fn from_v0(old: LendingPoolV0, now: i64) -> Result<Self> {
+        let config = PoolConfig::default_for_decimals(DEFAULT_CONFIG_DECIMALS).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        Ok(LendingPool{
+            version: ACCOUNT_VERSION,
+            authority: old.authority,
+            token_mint: old.token_mint,
+            pool_token_account: old.pool_token_account,
+            share_mint: Pubkey::default(),
+            pool_name: old.pool_name,
+            total_deposits: old.total_deposits,
+            total_shares: 0,
+            available_liquidity: old.available_liquidity,
+            total_borrowed: old.total_borrowed,
+            cumulative_interest: old.cumulative_interest,
+            borrow_index: BORROW_INDEX_PRECISION,
+            last_accrual_timestamp: now,
+            treasury: Pubkey::default(),
+            reserve_factor_bps: DEFAULT_RESERVE_FACTOR_BPS,
+            total_reserves: 0,
+            queued_withdrawal_shares: 0,
+            junior_share_mint: Pubkey::default(),
+            junior_deposits: 0,
+            junior_shares: 0,
+            junior_premium_bps: DEFAULT_JUNIOR_PREMIUM_BPS,
+            pending_authority: Pubkey::default(),
+            access_policy: AccessPolicy::default(),
+            flash_loan_fee_bps: DEFAULT_FLASH_LOAN_FEE_BPS,
+            flash_loan_amount: 0,
+            pool_funded_principal: 0,
+            total_lender_stake: 0,
+            interest_per_stake: 0,
+            loss_per_stake: 0,
+            bad_debt: 0,
+            bad_debt_stake_loss: 0,
+            active_loans_count: old.active_loans_count,
+            total_loans_count: old.total_loans_count,
+            // the single baseline pause flag stopped everything
+            pause_flags: PauseFlags{
+                deposits: old.paused,
+                withdrawals: old.paused,
+                loan_requests: old.paused,
+                disbursements: old.paused,
+                repayments: old.paused,
+                liquidations: old.paused,
+                flash_loans: old.paused,
+            },
+            created_at: old.created_at,
+            unpaid_interest: 0,
+            unpaid_junior_interest: 0,
+            unpaid_reserves: 0,
+            pending_defaults: 0,
+            config,
+            bump: old.bump,
+        })
+    }
+}
+
+/// LenderAccount as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct LenderAccountV0{
+    pub lender : Pubkey,
+
+    pub pool : Pubkey,
+
+    pub deposited_amount : u64,
+
+    pub available_amount : u64,
+
+    pub lent_amount : u64,
+
+    pub interest_earned : u64,
+
+    pub total_withdrawals : u64,
+
+    pub last_deposited_time : i64,
+
+    pub bump : u8
+}
+
+impl Migrate for LenderAccount {
+    type V0 = LenderAccountV0;
+    const V0_SPACE: usize = 121;
+    const SPACE: usize = LenderAccount::LEN;
+
+    // the balance is the lender's share balance now, available_amount and interest_earned
+    // have no counterpart and are dropped
+    fn from_v0(old: LenderAccountV0, _now: i64) -> Result<Self> {
+        Ok(LenderAccount{
+            version: ACCOUNT_VERSION,
+            lender: old.lender,
+            pool: old.pool,
+            deposited_amount: old.deposited_amount,
+            lent_amount: old.lent_amount,
+            total_withdrawals: old.total_withdrawals,
+            last_deposited_time: old.last_deposited_time,
+            reserved_amount: 0,
+            senior_rate_checkpoint: 0,
+            junior_rate_checkpoint: 0,
+            stake: 0,
+            interest_checkpoint: 0,
+            loss_checkpoint: 0,
+            pool_funded_interest: 0,
+            pool_funded_loss: 0,
+            bump: old.bump,
+        })
+    }
+}
+
+/// Upgrade a baseline account of type T to the current layout in place
+pub fn migrate<T: Migrate>(ctx:Context<MigrateAccount>)->Result<()>{
+    let clock = Clock::get()?;
+    let account = ctx.accounts.account.to_account_info();
+
+    migrate_account::<T>(
+        &ctx.accounts.payer.to_account_info(),
+        &account,
+        &ctx.accounts.system_program.to_account_info(),
+        clock.unix_timestamp,
+        LendingPoolError::InvalidAccountLayout.into(),
+        LendingPoolError::AccountAlreadyMigrated.into(),
+    )?;
+
+    emit!(AccountMigrated{
+        account: account.key(),
+        from_version: 0,
+        to_version: ACCOUNT_VERSION,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Account migrated: v0 -> v{}", ACCOUNT_VERSION);
+    Ok(())
+}
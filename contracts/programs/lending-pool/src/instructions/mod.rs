@@ -2,12 +2,50 @@ pub mod initialize;
 pub mod deposit;
 pub mod withdraw;
 pub mod pause;
+pub mod interest_rate;
+pub mod accrue_interest;
+pub mod reserves;
+pub mod withdrawal_queue;
+pub mod lock_deposit;
+pub mod tranche;
+pub mod registry;
+pub mod pool_config;
+pub mod authority;
+pub mod roles;
+pub mod attestation;
+pub mod flash_loan;
+pub mod insurance;
+pub mod close_lender_account;
+pub mod migrate;
+pub mod stats;
+pub mod strategy;
+pub mod loans;
+pub mod claim_interest;
 
 // Re-export for easier access
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
 pub use pause::*;
+pub use interest_rate::*;
+pub use accrue_interest::*;
+pub use reserves::*;
+pub use withdrawal_queue::*;
+pub use lock_deposit::*;
+pub use tranche::*;
+pub use registry::*;
+pub use pool_config::*;
+pub use authority::*;
+pub use roles::*;
+pub use attestation::*;
+pub use flash_loan::*;
+pub use insurance::*;
+pub use close_lender_account::*;
+pub use migrate::*;
+pub use stats::*;
+pub use strategy::*;
+pub use loans::*;
+pub use claim_interest::*;
 
 
 
@@ -7,54 +7,71 @@ use crate::events::*;
 
 #[derive(Accounts)]
 pub struct PausePool<'info> {
-    /// Pool authority (only they can pause)
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Pauser (guardian) role holder (only they can pause)
+    pub pauser: Signer<'info>,
 
-    /// Lending pool account
+    /// Protocol roles account
     #[account(
-        mut,
-        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::Pauser, &pauser.key()) @ LendingPoolError::MissingRole,
     )]
+    pub roles: Account<'info, ProtocolRoles>,
+
+    /// Lending pool account
+    #[account(mut)]
     pub pool: Account<'info, LendingPool>,
 }
 
 #[derive(Accounts)]
 pub struct UnpausePool<'info> {
-    /// Pool authority (only they can unpause)
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// Pauser (guardian) role holder (only they can unpause)
+    pub pauser: Signer<'info>,
 
-    /// Lending pool account
+    /// Protocol roles account
     #[account(
-        mut,
-        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::Pauser, &pauser.key()) @ LendingPoolError::MissingRole,
     )]
+    pub roles: Account<'info, ProtocolRoles>,
+
+    /// Lending pool account
+    #[account(mut)]
     pub pool: Account<'info, LendingPool>,
 }
 
 pub fn handler(
     ctx: Context<PausePool>,
+    flags: PauseFlags,
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
 
-    // Check if already paused
-    require!(!pool.paused, LendingPoolError::PoolPaused);
+    require!(flags.any(), LendingPoolError::NoPauseFlagsSelected);
+
+    // Guardians can freeze everything else, blocking repayments needs an admin
+    if flags.repayments {
+        require!(
+            ctx.accounts.roles.has_role(Role::Admin, &ctx.accounts.pauser.key()),
+            LendingPoolError::RepaymentPauseRequiresAdmin
+        );
+    }
 
-    // Pause the pool
-    pool.paused = true;
+    // Pause the selected operations, others keep their state
+    pool.pause_flags = pool.pause_flags.with(&flags, true);
 
     // Emit event
     emit!(PoolPaused {
         pool: pool.key(),
-        authority: ctx.accounts.authority.key(),
+        authority: ctx.accounts.pauser.key(),
+        paused: flags,
+        pause_flags: pool.pause_flags,
         timestamp: clock.unix_timestamp,
     });
 
-    msg!("⚠️  Pool paused successfully");
-    msg!("No new deposits or loans allowed");
-    msg!("Withdrawals still enabled");
+    msg!("⚠️  Pool operations paused successfully");
+    msg!("Pause flags: {:?}", pool.pause_flags);
 
     Ok(())
 }
@@ -62,25 +79,28 @@ pub fn handler(
 /// Instruction handler for unpausing the pool
 pub fn unpause_handler(
     ctx: Context<UnpausePool>,
+    flags: PauseFlags,
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
 
-    // Check if paused
-    require!(pool.paused, LendingPoolError::PoolNotPaused);
+    // Check at least one selected operation is paused
+    require!(pool.pause_flags.intersects(&flags), LendingPoolError::PoolNotPaused);
 
-    // Unpause the pool
-    pool.paused = false;
+    // Unpause the selected operations
+    pool.pause_flags = pool.pause_flags.with(&flags, false);
 
     // Emit event
     emit!(PoolUnpaused {
         pool: pool.key(),
-        authority: ctx.accounts.authority.key(),
+        authority: ctx.accounts.pauser.key(),
+        unpaused: flags,
+        pause_flags: pool.pause_flags,
         timestamp: clock.unix_timestamp,
     });
 
-    msg!(" Pool unpaused successfully");
-    msg!("Deposits and loans re-enabled");
+    msg!(" Pool operations unpaused successfully");
+    msg!("Pause flags: {:?}", pool.pause_flags);
 
     Ok(())
 }
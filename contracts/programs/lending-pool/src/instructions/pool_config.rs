@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::LendingPoolError, events::PoolConfigUpdated, states::{LendingPool, PoolConfig, ProtocolRoles, Role}};
+
+#[derive(Accounts)]
+pub struct UpdatePoolConfig<'info>{
+    /// Risk manager role holder (only they can change pool limits)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(mut)]
+    pub pool : Account<'info,LendingPool>,
+}
+
+pub fn handler(ctx:Context<UpdatePoolConfig>,config:PoolConfig)->Result<()>{
+    require!(config.is_valid(),LendingPoolError::InvalidPoolConfig);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    let old_config = pool.config;
+
+    pool.config = config;
+
+    emit!(PoolConfigUpdated{
+        pool: pool.key(),
+        authority: ctx.accounts.risk_manager.key(),
+        old_config,
+        new_config: config,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Pool config updated");
+    msg!("Min deposit: {}", config.min_deposit_amount);
+    msg!("Min withdrawal: {}", config.min_withdrawal_amount);
+    msg!("Min remaining balance: {}", config.min_remaining_balance);
+    msg!("Supply cap: {}", config.supply_cap);
+    msg!("Lender cap: {}", config.lender_cap);
+    Ok(())
+}
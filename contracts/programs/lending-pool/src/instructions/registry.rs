@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{constants::ACCOUNT_VERSION, errors::LendingPoolError, events::{MarketDelisted, MarketListed, PoolRegistryInitialized}, states::{LendingPool, MarketEntry, MarketStatus, PoolRegistry, ProtocolRoles, Role}};
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info>{
+    /// Pays for the registry, listings are gated by the RiskManager role
+    #[account(mut)]
+    pub authority : Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = PoolRegistry::LEN,
+        seeds = [PoolRegistry::SEED_PREFIX],
+        bump
+    )]
+    pub pool_registry : Account<'info,PoolRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListMarket<'info>{
+    /// Risk manager role holder (only they can list markets)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        seeds = [PoolRegistry::SEED_PREFIX],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry : Account<'info,PoolRegistry>,
+
+    #[account(
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+}
+
+#[derive(Accounts)]
+pub struct DelistMarket<'info>{
+    /// Risk manager role holder (only they can delist markets)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        seeds = [PoolRegistry::SEED_PREFIX],
+        bump = pool_registry.bump,
+    )]
+    pub pool_registry : Account<'info,PoolRegistry>,
+
+    pub pool : Account<'info,LendingPool>,
+}
+
+pub fn initialize_handler(ctx:Context<InitializeRegistry>)->Result<()>{
+    let clock = Clock::get()?;
+    let registry = &mut ctx.accounts.pool_registry;
+
+    registry.authority = ctx.accounts.authority.key();
+    registry.markets = vec![];
+    registry.version = ACCOUNT_VERSION;
+    registry.bump = ctx.bumps.pool_registry;
+
+    emit!(PoolRegistryInitialized{
+        registry: registry.key(),
+        authority: registry.authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Pool registry initialized");
+    msg!("Registry authority: {}", registry.authority);
+    Ok(())
+}
+
+/// Lists a pool, or updates the risk params of (and relists) an existing entry
+pub fn list_handler(ctx:Context<ListMarket>,max_loan_amount:u64,min_credit_score:u16)->Result<()>{
+    require!(max_loan_amount>0,LendingPoolError::InvalidRiskParams);
+
+    let clock = Clock::get()?;
+    let registry = &mut ctx.accounts.pool_registry;
+    let pool_key = ctx.accounts.pool.key();
+    let token_mint = ctx.accounts.token_mint.key();
+    let decimals = ctx.accounts.token_mint.decimals;
+
+    match registry.find_mut(&pool_key) {
+        Some(entry) => {
+            entry.status = MarketStatus::Listed;
+            entry.max_loan_amount = max_loan_amount;
+            entry.min_credit_score = min_credit_score;
+            entry.updated_at = clock.unix_timestamp;
+        }
+        None => {
+            require!(!registry.is_full(),LendingPoolError::MarketRegistryFull);
+
+            registry.markets.push(MarketEntry{
+                pool: pool_key,
+                token_mint,
+                decimals,
+                status: MarketStatus::Listed,
+                max_loan_amount,
+                min_credit_score,
+                listed_at: clock.unix_timestamp,
+                updated_at: clock.unix_timestamp,
+            });
+        }
+    }
+
+    emit!(MarketListed{
+        pool: pool_key,
+        token_mint,
+        decimals,
+        max_loan_amount,
+        min_credit_score,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Market listed: {}", pool_key);
+    msg!("Token mint: {} ({} decimals)", token_mint, decimals);
+    msg!("Max loan amount: {}", max_loan_amount);
+    msg!("Min credit score: {}", min_credit_score);
+    msg!("Registered markets: {}", registry.markets.len());
+    Ok(())
+}
+
+/// Stops new loans against a pool, existing loans and deposits are unaffected
+pub fn delist_handler(ctx:Context<DelistMarket>)->Result<()>{
+    let clock = Clock::get()?;
+    let registry = &mut ctx.accounts.pool_registry;
+    let pool_key = ctx.accounts.pool.key();
+
+    let entry = registry.find_mut(&pool_key).ok_or(LendingPoolError::MarketNotListed)?;
+
+    require!(entry.status == MarketStatus::Listed,LendingPoolError::MarketNotListed);
+
+    entry.status = MarketStatus::Delisted;
+    entry.updated_at = clock.unix_timestamp;
+
+    emit!(MarketDelisted{
+        pool: pool_key,
+        token_mint: entry.token_mint,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Market delisted: {}", pool_key);
+    Ok(())
+}
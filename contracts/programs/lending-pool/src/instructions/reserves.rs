@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::MAX_RESERVE_FACTOR_BPS, errors::LendingPoolError, events::{ReserveConfigUpdated, ReservesCollected}, instructions::{accrue_interest::accrue, strategy::{optional_strategy_cpi, recall_shortfall}}, states::{InterestRateModel, LendingPool, YieldStrategy}};
+
+#[derive(Accounts)]
+pub struct SetReserveConfig<'info>{
+    /// Pool authority (only they can change the reserve factor)
+    pub authority : Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    // interest up to now is split at the old factor
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = treasury_token_account.mint == pool.token_mint @ LendingPoolError::InvalidTreasury
+    )]
+    pub treasury_token_account : InterfaceAccount<'info,TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CollectReserves<'info>{
+    /// Pool authority (only they can collect reserves)
+    pub authority : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.key() == pool.treasury @ LendingPoolError::InvalidTreasury
+    )]
+    pub treasury_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn set_config_handler(ctx:Context<SetReserveConfig>,reserve_factor_bps:u16)->Result<()>{
+    require!(reserve_factor_bps<=MAX_RESERVE_FACTOR_BPS,LendingPoolError::ReserveFactorTooHigh);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let old_reserve_factor_bps = pool.reserve_factor_bps;
+
+    pool.reserve_factor_bps = reserve_factor_bps;
+    pool.treasury = ctx.accounts.treasury_token_account.key();
+
+    emit!(ReserveConfigUpdated{
+        pool: pool.key(),
+        treasury: pool.treasury,
+        old_reserve_factor_bps,
+        new_reserve_factor_bps: reserve_factor_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Reserve factor: {} bps", reserve_factor_bps);
+    msg!("Treasury: {}", pool.treasury);
+    Ok(())
+}
+
+pub fn collect_handler(ctx:Context<CollectReserves>,amount:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(pool.treasury != Pubkey::default(),LendingPoolError::TreasuryNotSet);
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // only reserves borrowers have paid, those accrued on loans not yet repaid sit in unpaid_reserves
+    require!(amount<=pool.total_reserves,LendingPoolError::InsufficientReserves);
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+    require!(unreserved_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    // funds deployed in the yield strategy come back when the pool account is short
+    let strategy_cpi = optional_strategy_cpi(
+        &ctx.accounts.yield_strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, ctx.accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), ctx.accounts.pool_token_account.amount, amount, clock.unix_timestamp)?;
+
+    if recalled > 0 {
+        ctx.accounts.pool_token_account.reload()?;
+    }
+
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        pool.token_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.pool_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
+        to : ctx.accounts.treasury_token_account.to_account_info(),
+        authority : pool.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(),
+                                         cpi_account, signer_seeds);
+
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+    pool.total_reserves = pool.total_reserves.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+    pool.available_liquidity = pool.available_liquidity.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    emit!(ReservesCollected{
+        pool: pool.key(),
+        treasury: pool.treasury,
+        amount,
+        remaining_reserves: pool.total_reserves,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Reserves collected: {} tokens", amount);
+    msg!("Remaining reserves: {}", pool.total_reserves);
+    Ok(())
+}
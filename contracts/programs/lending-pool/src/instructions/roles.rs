@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::ACCOUNT_VERSION, errors::LendingPoolError, events::{ProtocolRolesInitialized, RoleGranted, RoleRevoked}, states::{ProtocolRoles, Role, RoleGrant}};
+
+#[derive(Accounts)]
+pub struct InitializeRoles<'info>{
+    /// First admin, grants every other role
+    #[account(mut)]
+    pub admin : Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolRoles::LEN,
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GrantRole<'info>{
+    /// Admin role holder (only they can grant roles)
+    pub admin : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::Admin, &admin.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info>{
+    /// Admin role holder (only they can revoke roles)
+    pub admin : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::Admin, &admin.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+}
+
+pub fn initialize_roles_handler(ctx:Context<InitializeRoles>)->Result<()>{
+    let clock = Clock::get()?;
+    let roles = &mut ctx.accounts.roles;
+    let admin = ctx.accounts.admin.key();
+
+    roles.grants = vec![RoleGrant{
+        role: Role::Admin,
+        member: admin,
+        granted_at: clock.unix_timestamp,
+    }];
+    roles.version = ACCOUNT_VERSION;
+    roles.bump = ctx.bumps.roles;
+
+    emit!(ProtocolRolesInitialized{
+        roles: roles.key(),
+        admin,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Protocol roles initialized");
+    msg!("Admin: {}", admin);
+    Ok(())
+}
+
+pub fn grant_handler(ctx:Context<GrantRole>,role:Role,member:Pubkey)->Result<()>{
+    let clock = Clock::get()?;
+    let roles = &mut ctx.accounts.roles;
+
+    require!(!roles.has_role(role, &member),LendingPoolError::RoleAlreadyGranted);
+    require!(!roles.is_full(),LendingPoolError::RoleRegistryFull);
+
+    roles.grants.push(RoleGrant{
+        role,
+        member,
+        granted_at: clock.unix_timestamp,
+    });
+
+    emit!(RoleGranted{
+        role,
+        member,
+        granted_by: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Role {:?} granted to {}", role, member);
+    Ok(())
+}
+
+pub fn revoke_handler(ctx:Context<RevokeRole>,role:Role,member:Pubkey)->Result<()>{
+    let clock = Clock::get()?;
+    let roles = &mut ctx.accounts.roles;
+
+    require!(roles.has_role(role, &member),LendingPoolError::RoleNotGranted);
+
+    // without an admin no role could ever be granted again
+    require!(
+        role != Role::Admin || roles.role_count(Role::Admin) > 1,
+        LendingPoolError::LastAdminRevoke
+    );
+
+    roles.grants.retain(|g| !(g.role == role && g.member == member));
+
+    emit!(RoleRevoked{
+        role,
+        member,
+        revoked_by: ctx.accounts.admin.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Role {:?} revoked from {}", role, member);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{errors::LendingPoolError, states::{InterestRateModel, LenderAccount, LenderPosition, LendingPool, PoolStats, Tranche}};
+
+// read-only: meant to be simulated, nothing is written back
+#[derive(Accounts)]
+pub struct GetPoolStats<'info>{
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+}
+
+#[derive(Accounts)]
+pub struct GetLenderPosition<'info>{
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender_account.lender.as_ref(),
+        ],
+        bump = lender_account.bump,
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // optional, the lender's senior shares are valued when passed
+    #[account(
+        constraint = senior_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = senior_share_account.owner == lender_account.lender
+    )]
+    pub senior_share_account : Option<InterfaceAccount<'info,TokenAccount>>,
+
+    // optional, the lender's junior shares are valued when passed
+    #[account(
+        constraint = junior_share_account.mint == pool.junior_share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = junior_share_account.owner == lender_account.lender
+    )]
+    pub junior_share_account : Option<InterfaceAccount<'info,TokenAccount>>,
+}
+
+/// Copy of the pool with interest accrued to `now`, as the next instruction touching it would see it
+fn projected_pool(pool:&LendingPool,model:&InterestRateModel,now:i64)->Result<LendingPool>{
+    let mut pool = pool.clone();
+
+    // accrual waits for pending bad debt to be absorbed, so does the projection
+    if pool.bad_debt == 0 {
+        pool.accrue_interest(model, now).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    }
+
+    Ok(pool)
+}
+
+pub fn pool_stats_handler(ctx:Context<GetPoolStats>)->Result<PoolStats>{
+    let clock = Clock::get()?;
+    let model = &ctx.accounts.interest_rate_model;
+
+    let pool = projected_pool(&ctx.accounts.pool, model, clock.unix_timestamp)?;
+
+    let utilization = pool.calculate_utilization();
+
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    Ok(PoolStats {
+        total_deposits: pool.total_deposits,
+        senior_deposits: pool.senior_deposits(),
+        junior_deposits: pool.junior_deposits,
+        available_liquidity: pool.available_liquidity,
+        unreserved_liquidity,
+        total_borrowed: pool.total_borrowed,
+        cumulative_interest: pool.cumulative_interest,
+        unpaid_interest: pool.unpaid_interest,
+        total_reserves: pool.total_reserves,
+        unpaid_reserves: pool.unpaid_reserves,
+        pool_funded_principal: pool.pool_funded_principal,
+        bad_debt: pool.bad_debt,
+        utilization,
+        borrow_rate_bps: model.borrow_rate(utilization),
+        apy_bps: pool.calculate_apy(),
+        senior_exchange_rate: pool.exchange_rate(Tranche::Senior),
+        junior_exchange_rate: pool.exchange_rate(Tranche::Junior),
+        active_loans: pool.active_loans_count,
+        total_loans: pool.total_loans_count,
+    })
+}
+
+pub fn lender_position_handler(ctx:Context<GetLenderPosition>)->Result<LenderPosition>{
+    let clock = Clock::get()?;
+
+    let pool = projected_pool(&ctx.accounts.pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // pool-funded interest and losses the lender's next instruction would settle
+    let mut lender_account = (*ctx.accounts.lender_account).clone();
+    let stake = lender_account.stake;
+    lender_account.settle_pool_funded(&pool, stake).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let pool_funded_exposure = pool.pool_funded_exposure(stake).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let senior_shares = ctx.accounts.senior_share_account.as_ref().map_or(0, |a| a.amount);
+    let junior_shares = ctx.accounts.junior_share_account.as_ref().map_or(0, |a| a.amount);
+
+    let senior_value = pool.shares_to_amount(Tranche::Senior, senior_shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    let junior_value = pool.shares_to_amount(Tranche::Junior, junior_shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let total_balance = senior_value.checked_add(junior_value).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let claimable_interest = lender_account
+        .claimable_interest(Tranche::Senior, senior_shares, pool.exchange_rate(Tranche::Senior))
+        .zip(lender_account.claimable_interest(Tranche::Junior, junior_shares, pool.exchange_rate(Tranche::Junior)))
+        .and_then(|(senior, junior)| senior.checked_add(junior))
+        .ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    Ok(LenderPosition {
+        lender: lender_account.lender,
+        pool: lender_account.pool,
+        deposited_amount: lender_account.deposited_amount,
+        lent_amount: lender_account.lent_amount,
+        total_balance,
+        lendable_amount: lender_account.lendable_amount(senior_value),
+        interest_earned: lender_account.interest_earned(total_balance),
+        claimable_interest,
+        pool_funded_interest: lender_account.pool_funded_interest,
+        pool_funded_loss: lender_account.pool_funded_loss,
+        pool_funded_exposure,
+        roi: lender_account.calculate_roi(total_balance),
+        senior_shares,
+        senior_value,
+        junior_shares,
+        junior_value,
+    })
+}
@@ -0,0 +1,655 @@
+use anchor_lang::{prelude::*, solana_program::{instruction::{AccountMeta, Instruction}, program::{get_return_data, invoke, invoke_signed}}};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::{ACCOUNT_VERSION, MAX_STRATEGY_ALLOCATION_BPS}, errors::LendingPoolError, events::{StrategyAllocationUpdated, StrategyDeposited, StrategyHarvested, StrategyLossRealized, StrategyRecalled, YieldStrategyInitialized}, instructions::accrue_interest::accrue, states::{InterestRateModel, LendingPool, YieldStrategy}};
+
+// Strategy interface, Anchor instruction discriminators (sha256("global:<name>")[..8]).
+// The pool is known to the strategy by its YieldStrategy PDA, which holds no tokens and
+// has no authority over the pool's funds or share mints. The pool PDA never signs a strategy call.
+// deposit(amount) takes [yield_strategy, strategy_state (mut), strategy_token_account (mut)],
+//   the pool has already moved `amount` into strategy_token_account
+// withdraw(amount) takes [yield_strategy (signer), strategy_state (mut), strategy_token_account (mut),
+//   pool_token_account (mut), token_mint, token_program], the strategy pays `amount` into
+//   pool_token_account under its own authority
+// report_balance() takes [yield_strategy, strategy_state, strategy_token_account] and returns,
+//   as u64 return data, the tokens the pool could withdraw right now
+// Every call is checked against the pool token balance, it has to move by exactly `amount`
+const STRATEGY_DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+const STRATEGY_WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+const STRATEGY_REPORT_BALANCE_DISCRIMINATOR: [u8; 8] = [150, 193, 190, 162, 222, 242, 50, 117];
+
+#[derive(Accounts)]
+pub struct InitializeYieldStrategy<'info>{
+    /// Pool authority (only they can route pool funds into a strategy)
+    #[account(mut)]
+    pub authority : Signer<'info>,
+
+    #[account(
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = YieldStrategy::LEN,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump
+    )]
+    pub yield_strategy : Account<'info,YieldStrategy>,
+
+    /// CHECK: any program implementing the strategy interface
+    #[account(executable)]
+    pub strategy_program : UncheckedAccount<'info>,
+
+    /// CHECK: the strategy's state for this pool, only its owner is checked here
+    #[account(
+        constraint = strategy_state.owner == strategy_program.key @ LendingPoolError::InvalidStrategyAccounts
+    )]
+    pub strategy_state : UncheckedAccount<'info>,
+
+    #[account(
+        constraint = strategy_token_account.mint == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub strategy_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub system_program : Program<'info,System>,
+}
+
+#[derive(Accounts)]
+pub struct SetStrategyAllocation<'info>{
+    /// Pool authority (only they can change the allocation cap)
+    pub authority : Signer<'info>,
+
+    #[account(
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Account<'info,YieldStrategy>,
+}
+
+// allocate_to_strategy and recall_from_strategy move funds the same way
+#[derive(Accounts)]
+pub struct MoveStrategyFunds<'info>{
+    /// Pool authority (only they can move funds in and out of the strategy)
+    pub authority : Signer<'info>,
+
+    #[account(
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Account<'info,YieldStrategy>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(address = yield_strategy.strategy_program @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_program : UncheckedAccount<'info>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(mut, address = yield_strategy.strategy_state @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_state : UncheckedAccount<'info>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(mut, address = yield_strategy.strategy_token_account @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_token_account : UncheckedAccount<'info>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless crank, books strategy yield as pool interest
+#[derive(Accounts)]
+pub struct HarvestStrategy<'info>{
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Account<'info,YieldStrategy>,
+
+    // yield is split at the current tranche balances, accrue first
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(address = yield_strategy.strategy_program @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_program : UncheckedAccount<'info>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(mut, address = yield_strategy.strategy_state @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_state : UncheckedAccount<'info>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(mut, address = yield_strategy.strategy_token_account @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_token_account : UncheckedAccount<'info>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// A strategy reporting less than was deployed in it lost funds, reporting is left
+/// to the pool authority so a temporary dip is not booked as a loss by anyone
+#[derive(Accounts)]
+pub struct RealizeStrategyLoss<'info>{
+    /// Pool authority (only they can book a strategy loss)
+    pub authority : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ LendingPoolError::UnauthorizedAccess,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Account<'info,YieldStrategy>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(address = yield_strategy.strategy_program @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_program : UncheckedAccount<'info>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(address = yield_strategy.strategy_state @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_state : UncheckedAccount<'info>,
+
+    /// CHECK: checked against yield_strategy
+    #[account(address = yield_strategy.strategy_token_account @ LendingPoolError::InvalidStrategyAccounts)]
+    pub strategy_token_account : UncheckedAccount<'info>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Accounts of a strategy call, the pool's YieldStrategy PDA signs withdrawals
+pub struct StrategyCpi<'info>{
+    pub strategy_program : AccountInfo<'info>,
+    pub strategy_state : AccountInfo<'info>,
+    pub strategy_token_account : AccountInfo<'info>,
+    pub yield_strategy : AccountInfo<'info>,
+    pub pool_token_account : AccountInfo<'info>,
+    pub token_mint : AccountInfo<'info>,
+    pub token_program : AccountInfo<'info>,
+}
+
+impl<'info> StrategyCpi<'info> {
+    /// Fails unless these are the strategy accounts recorded for the pool
+    pub fn check(&self, strategy:&YieldStrategy)->Result<()>{
+        require_keys_eq!(self.strategy_program.key(),strategy.strategy_program,LendingPoolError::InvalidStrategyAccounts);
+        require_keys_eq!(self.strategy_state.key(),strategy.strategy_state,LendingPoolError::InvalidStrategyAccounts);
+        require_keys_eq!(self.strategy_token_account.key(),strategy.strategy_token_account,LendingPoolError::InvalidStrategyAccounts);
+        Ok(())
+    }
+
+    /// Move exactly `amount` from the pool into the strategy, the pool signs its own transfer
+    /// and the strategy is only told about it
+    pub fn deposit(&self, pool:&AccountInfo<'info>, pool_data:&LendingPool, token_decimals:u8, amount:u64)->Result<()>{
+        let balance_before = token_balance(&self.pool_token_account)?;
+
+        let seeds = &[
+            LendingPool::SEED_PREFIX,
+            pool_data.token_mint.as_ref(),
+            &[pool_data.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_account = TransferChecked{
+            from : self.pool_token_account.clone(),
+            mint : self.token_mint.clone(),
+            to : self.strategy_token_account.clone(),
+            authority : pool.clone()
+        };
+
+        token_interface::transfer_checked(CpiContext::new_with_signer(self.token_program.clone(), cpi_account, signer_seeds), amount, token_decimals)?;
+
+        let ix = Instruction{
+            program_id: self.strategy_program.key(),
+            accounts: vec![
+                AccountMeta::new_readonly(self.yield_strategy.key(), false),
+                AccountMeta::new(self.strategy_state.key(), false),
+                AccountMeta::new(self.strategy_token_account.key(), false),
+            ],
+            data: Self::instruction_data(STRATEGY_DEPOSIT_DISCRIMINATOR, amount),
+        };
+
+        invoke(&ix, &[
+            self.yield_strategy.clone(),
+            self.strategy_state.clone(),
+            self.strategy_token_account.clone(),
+            self.strategy_program.clone(),
+        ])?;
+
+        let moved = balance_before.checked_sub(token_balance(&self.pool_token_account)?);
+
+        require!(moved == Some(amount),LendingPoolError::InvalidStrategyTransfer);
+        Ok(())
+    }
+
+    /// Have the strategy pay exactly `amount` back into the pool token account
+    pub fn withdraw(&self, strategy:&YieldStrategy, amount:u64)->Result<()>{
+        let balance_before = token_balance(&self.pool_token_account)?;
+
+        let ix = Instruction{
+            program_id: self.strategy_program.key(),
+            accounts: vec![
+                AccountMeta::new_readonly(self.yield_strategy.key(), true),
+                AccountMeta::new(self.strategy_state.key(), false),
+                AccountMeta::new(self.strategy_token_account.key(), false),
+                AccountMeta::new(self.pool_token_account.key(), false),
+                AccountMeta::new_readonly(self.token_mint.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data: Self::instruction_data(STRATEGY_WITHDRAW_DISCRIMINATOR, amount),
+        };
+
+        let seeds = &[
+            YieldStrategy::SEED_PREFIX,
+            strategy.pool.as_ref(),
+            &[strategy.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        invoke_signed(&ix, &[
+            self.yield_strategy.clone(),
+            self.strategy_state.clone(),
+            self.strategy_token_account.clone(),
+            self.pool_token_account.clone(),
+            self.token_mint.clone(),
+            self.token_program.clone(),
+            self.strategy_program.clone(),
+        ], signer_seeds)?;
+
+        let moved = token_balance(&self.pool_token_account)?.checked_sub(balance_before);
+
+        require!(moved == Some(amount),LendingPoolError::InvalidStrategyTransfer);
+        Ok(())
+    }
+
+    /// Tokens the pool could withdraw from the strategy, principal plus yield
+    pub fn report_balance(&self)->Result<u64>{
+        let ix = Instruction{
+            program_id: self.strategy_program.key(),
+            accounts: vec![
+                AccountMeta::new_readonly(self.yield_strategy.key(), false),
+                AccountMeta::new_readonly(self.strategy_state.key(), false),
+                AccountMeta::new_readonly(self.strategy_token_account.key(), false),
+            ],
+            data: STRATEGY_REPORT_BALANCE_DISCRIMINATOR.to_vec(),
+        };
+
+        invoke(&ix, &[
+            self.yield_strategy.clone(),
+            self.strategy_state.clone(),
+            self.strategy_token_account.clone(),
+            self.strategy_program.clone(),
+        ])?;
+
+        let (program_id, data) = get_return_data().ok_or(LendingPoolError::InvalidStrategyReport)?;
+
+        require_keys_eq!(program_id,self.strategy_program.key(),LendingPoolError::InvalidStrategyReport);
+
+        u64::try_from_slice(&data).map_err(|_| error!(LendingPoolError::InvalidStrategyReport))
+    }
+
+    fn instruction_data(discriminator:[u8;8], amount:u64)->Vec<u8>{
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data
+    }
+}
+
+impl<'info> MoveStrategyFunds<'info> {
+    fn strategy_cpi(&self)->StrategyCpi<'info>{
+        StrategyCpi{
+            strategy_program: self.strategy_program.to_account_info(),
+            strategy_state: self.strategy_state.to_account_info(),
+            strategy_token_account: self.strategy_token_account.to_account_info(),
+            yield_strategy: self.yield_strategy.to_account_info(),
+            pool_token_account: self.pool_token_account.to_account_info(),
+            token_mint: self.token_mint.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+}
+
+/// Pull `amount` of deployed funds back into the pool token account
+/// Shared by recall_from_strategy and the automatic recall of instructions paying out liquidity
+pub fn recall_deployed(pool:&Account<LendingPool>,strategy:&mut YieldStrategy,cpi:&StrategyCpi,amount:u64,automatic:bool,now:i64)->Result<()>{
+    require!(amount<=strategy.deployed,LendingPoolError::InsufficientStrategyBalance);
+
+    cpi.check(strategy)?;
+    cpi.withdraw(strategy, amount)?;
+
+    strategy.deployed -= amount;
+
+    emit!(StrategyRecalled{
+        pool: pool.key(),
+        amount,
+        deployed: strategy.deployed,
+        automatic,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Strategy accounts of an instruction taking them as optional accounts,
+/// None unless all four were passed
+pub fn optional_strategy_cpi<'info>(
+    yield_strategy:&Option<Account<'info,YieldStrategy>>,
+    strategy_program:&Option<UncheckedAccount<'info>>,
+    strategy_state:&Option<UncheckedAccount<'info>>,
+    strategy_token_account:&Option<UncheckedAccount<'info>>,
+    pool_token_account:AccountInfo<'info>,
+    token_mint:AccountInfo<'info>,
+    token_program:AccountInfo<'info>,
+)->Option<StrategyCpi<'info>>{
+    Some(StrategyCpi{
+        strategy_program: strategy_program.as_ref()?.to_account_info(),
+        strategy_state: strategy_state.as_ref()?.to_account_info(),
+        strategy_token_account: strategy_token_account.as_ref()?.to_account_info(),
+        yield_strategy: yield_strategy.as_ref()?.to_account_info(),
+        pool_token_account,
+        token_mint,
+        token_program,
+    })
+}
+
+/// Recall what `pool_token_account` lacks to pay out `amount`, when the pool has a strategy
+/// Returns the amount recalled
+pub fn recall_shortfall(pool:&Account<LendingPool>,strategy:Option<&mut YieldStrategy>,cpi:Option<&StrategyCpi>,token_balance:u64,amount:u64,now:i64)->Result<u64>{
+    let shortfall = amount.saturating_sub(token_balance);
+
+    let strategy = match strategy {
+        Some(strategy) if shortfall > 0 && strategy.deployed > 0 => strategy,
+        _ => return Ok(0),
+    };
+
+    let cpi = cpi.ok_or(LendingPoolError::InvalidStrategyAccounts)?;
+
+    let recalled = shortfall.min(strategy.deployed);
+
+    recall_deployed(pool, strategy, cpi, recalled, true, now)?;
+
+    Ok(recalled)
+}
+
+pub fn initialize_strategy_handler(ctx:Context<InitializeYieldStrategy>,max_allocation_bps:u16)->Result<()>{
+    let clock = Clock::get()?;
+
+    require!(max_allocation_bps<=MAX_STRATEGY_ALLOCATION_BPS,LendingPoolError::InvalidStrategyAllocation);
+
+    let strategy = &mut ctx.accounts.yield_strategy;
+
+    strategy.pool = ctx.accounts.pool.key();
+    strategy.strategy_program = ctx.accounts.strategy_program.key();
+    strategy.strategy_state = ctx.accounts.strategy_state.key();
+    strategy.strategy_token_account = ctx.accounts.strategy_token_account.key();
+    strategy.max_allocation_bps = max_allocation_bps;
+    strategy.deployed = 0;
+    strategy.total_harvested = 0;
+    strategy.last_harvest_at = clock.unix_timestamp;
+    strategy.created_at = clock.unix_timestamp;
+    strategy.version = ACCOUNT_VERSION;
+    strategy.bump = ctx.bumps.yield_strategy;
+
+    emit!(YieldStrategyInitialized{
+        pool: strategy.pool,
+        strategy_program: strategy.strategy_program,
+        strategy_state: strategy.strategy_state,
+        max_allocation_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Yield strategy initialized");
+    msg!("Strategy program: {}", strategy.strategy_program);
+    msg!("Max allocation: {} bps", max_allocation_bps);
+    Ok(())
+}
+
+pub fn set_allocation_handler(ctx:Context<SetStrategyAllocation>,max_allocation_bps:u16)->Result<()>{
+    let clock = Clock::get()?;
+
+    require!(max_allocation_bps<=MAX_STRATEGY_ALLOCATION_BPS,LendingPoolError::InvalidStrategyAllocation);
+
+    let strategy = &mut ctx.accounts.yield_strategy;
+
+    let old_allocation_bps = strategy.max_allocation_bps;
+
+    // lowering the cap does not recall anything, the authority recalls separately
+    strategy.max_allocation_bps = max_allocation_bps;
+
+    emit!(StrategyAllocationUpdated{
+        pool: strategy.pool,
+        old_allocation_bps,
+        new_allocation_bps: max_allocation_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Max allocation: {} -> {} bps", old_allocation_bps, max_allocation_bps);
+    Ok(())
+}
+
+pub fn allocate_handler(ctx:Context<MoveStrategyFunds>,amount:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let cpi = ctx.accounts.strategy_cpi();
+    let pool = &ctx.accounts.pool;
+    let strategy = &mut ctx.accounts.yield_strategy;
+
+    require!(amount>0,LendingPoolError::ZeroStrategyAmount);
+
+    require!(pool.flash_loan_amount == 0,LendingPoolError::FlashLoanActive);
+
+    let deployed = strategy.deployed.checked_add(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let allocation_cap = strategy.allocation_cap(pool.available_liquidity).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(deployed<=allocation_cap,LendingPoolError::StrategyAllocationExceeded);
+
+    // only idle funds go out, never what is earmarked for queued withdrawals
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(deployed<=unreserved_liquidity,LendingPoolError::InsufficientPoolLiquidity);
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    cpi.deposit(&pool.to_account_info(), pool, ctx.accounts.token_mint.decimals, amount)?;
+
+    // deployed funds stay in available_liquidity, they are recalled when payouts need them
+    strategy.deployed = deployed;
+
+    emit!(StrategyDeposited{
+        pool: pool.key(),
+        amount,
+        deployed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Allocated to strategy: {} tokens", amount);
+    msg!("Deployed: {}", deployed);
+    Ok(())
+}
+
+pub fn recall_handler(ctx:Context<MoveStrategyFunds>,amount:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let cpi = ctx.accounts.strategy_cpi();
+
+    require!(amount>0,LendingPoolError::ZeroStrategyAmount);
+
+    recall_deployed(&ctx.accounts.pool, &mut ctx.accounts.yield_strategy, &cpi, amount, false, clock.unix_timestamp)?;
+
+    msg!(" Recalled from strategy: {} tokens", amount);
+    msg!("Deployed: {}", ctx.accounts.yield_strategy.deployed);
+    Ok(())
+}
+
+pub fn harvest_handler(ctx:Context<HarvestStrategy>)->Result<()>{
+    let clock = Clock::get()?;
+    let cpi = StrategyCpi{
+        strategy_program: ctx.accounts.strategy_program.to_account_info(),
+        strategy_state: ctx.accounts.strategy_state.to_account_info(),
+        strategy_token_account: ctx.accounts.strategy_token_account.to_account_info(),
+        yield_strategy: ctx.accounts.yield_strategy.to_account_info(),
+        pool_token_account: ctx.accounts.pool_token_account.to_account_info(),
+        token_mint: ctx.accounts.token_mint.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+    };
+    let pool = &mut ctx.accounts.pool;
+    let strategy = &mut ctx.accounts.yield_strategy;
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let reported_balance = cpi.report_balance()?;
+
+    // a strategy below its principal has nothing to harvest, realize_strategy_loss books the shortfall
+    let strategy_yield = reported_balance.saturating_sub(strategy.deployed);
+
+    let mut harvested = 0;
+    let mut reserves = 0;
+
+    if strategy_yield > 0 {
+        // withdraw fails unless exactly the yield arrived in the pool account
+        cpi.withdraw(strategy, strategy_yield)?;
+
+        harvested = strategy_yield;
+
+        reserves = pool.credit_income(harvested).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        strategy.total_harvested = strategy.total_harvested.checked_add(harvested).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    }
+
+    strategy.last_harvest_at = clock.unix_timestamp;
+
+    emit!(StrategyHarvested{
+        pool: pool.key(),
+        amount: harvested,
+        reserves,
+        reported_balance,
+        deployed: strategy.deployed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Strategy harvested: {} tokens", harvested);
+    msg!("Protocol reserves: {}", reserves);
+    msg!("Cumulative interest: {}", pool.cumulative_interest);
+    Ok(())
+}
+
+pub fn realize_loss_handler(ctx:Context<RealizeStrategyLoss>)->Result<()>{
+    let clock = Clock::get()?;
+    let cpi = StrategyCpi{
+        strategy_program: ctx.accounts.strategy_program.to_account_info(),
+        strategy_state: ctx.accounts.strategy_state.to_account_info(),
+        strategy_token_account: ctx.accounts.strategy_token_account.to_account_info(),
+        yield_strategy: ctx.accounts.yield_strategy.to_account_info(),
+        pool_token_account: ctx.accounts.pool_token_account.to_account_info(),
+        token_mint: ctx.accounts.token_mint.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+    };
+    let pool = &mut ctx.accounts.pool;
+    let strategy = &mut ctx.accounts.yield_strategy;
+
+    // interest up to now is owed on the old balances, also waits for earlier bad debt to be absorbed
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let reported_balance = cpi.report_balance()?;
+
+    let loss = strategy.deployed.saturating_sub(reported_balance);
+
+    require!(loss>0,LendingPoolError::NoStrategyLoss);
+
+    strategy.deployed = reported_balance;
+
+    // the lost funds leave available liquidity and wait in bad_debt, like a written-off loan
+    pool.available_liquidity = pool.available_liquidity.checked_sub(loss).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+    pool.record_bad_debt(loss).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    emit!(StrategyLossRealized{
+        pool: pool.key(),
+        loss,
+        reported_balance,
+        deployed: strategy.deployed,
+        pending_bad_debt: pool.bad_debt,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Strategy loss realized: {} tokens", loss);
+    msg!("Deployed: {}", strategy.deployed);
+    msg!("Next step: absorb_bad_debt covers it from insurance, then depositors");
+    Ok(())
+}
+
+/// Token balance of an account passed as AccountInfo
+pub fn token_balance(token_account:&AccountInfo)->Result<u64>{
+    Ok(TokenAccount::try_deserialize(&mut &token_account.try_borrow_data()?[..])?.amount)
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::MAX_JUNIOR_PREMIUM_BPS, errors::LendingPoolError, events::TrancheConfigUpdated, instructions::accrue_interest::accrue, states::{InterestRateModel, LendingPool, ProtocolRoles, Role}};
+
+#[derive(Accounts)]
+pub struct SetTrancheConfig<'info>{
+    /// Risk manager role holder (only they can change the tranche waterfall)
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LendingPoolError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(mut)]
+    pub pool : Account<'info,LendingPool>,
+
+    // interest up to now is split at the old premium
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+}
+
+pub fn handler(ctx:Context<SetTrancheConfig>,junior_premium_bps:u16)->Result<()>{
+    require!(junior_premium_bps<=MAX_JUNIOR_PREMIUM_BPS,LendingPoolError::JuniorPremiumTooHigh);
+
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let old_junior_premium_bps = pool.junior_premium_bps;
+
+    pool.junior_premium_bps = junior_premium_bps;
+
+    emit!(TrancheConfigUpdated{
+        pool: pool.key(),
+        old_junior_premium_bps,
+        new_junior_premium_bps: junior_premium_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Tranche config updated");
+    msg!("Junior premium: {} bps -> {} bps", old_junior_premium_bps, junior_premium_bps);
+    msg!("Junior deposits: {}", pool.junior_deposits);
+    msg!("Senior deposits: {}", pool.senior_deposits());
+    Ok(())
+}
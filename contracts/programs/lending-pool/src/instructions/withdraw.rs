@@ -1,9 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
 
-use crate::{constants::{MIN_REMAINING_BALANCE, MIN_WITHDRAWAL_AMOUNT}, errors::LendingPoolError, events::Withdrawal, states::{LenderAccount, LendingPool}};
+use crate::{errors::LendingPoolError, events::Withdrawal, instructions::{accrue_interest::{accrue, settle_pool_funded}, strategy::{optional_strategy_cpi, recall_shortfall}}, states::{InterestRateModel, LenderAccount, LendingPool, Tranche, YieldStrategy}};
 
 #[derive(Accounts)]
+#[instruction(amount: u64, tranche: Tranche)]
 pub struct Withdraw<'info>{
     #[account(mut)]
     pub lender : Signer<'info>,
@@ -18,6 +19,7 @@ pub struct Withdraw<'info>{
     )]
     pub pool : Account<'info,LendingPool>,
 
+    // holders of transferred shares create theirs with open_lender_account
     #[account(
         mut,
         seeds = [
@@ -26,48 +28,142 @@ pub struct Withdraw<'info>{
             lender.key().as_ref(),
         ],
         bump = lender_account.bump,
-        constraint= lender_account.lender == lender.key(),
-        constraint = lender_account.pool == pool.key()
     )]
-
     pub lender_account : Account<'info,LenderAccount>,
 
+    // interest is accrued before shares are priced
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
     #[account(
+        mut,
         constraint = lender_token_account.mint == pool.token_mint, //it checks for this pool contain same type of token like usdc then only usdc 
         constraint = lender_token_account.owner == lender.key() // and owner is who call this withdraw and lender is signer also 
     )]
-    pub lender_token_account : Account<'info,TokenAccount>,
+    pub lender_token_account : InterfaceAccount<'info,TokenAccount>,
 
     #[account(
         mut ,
         constraint = pool_token_account.key() == pool.pool_token_account
     )]
-    pub pool_token_account : Account<'info,TokenAccount>,
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    // share mint of the tranche being withdrawn from
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.tranche_share_mint(tranche) @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    // shares are burned from here, so the lender must own it
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == share_mint.key() @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender.key()
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 
-pub fn handler(ctx:Context<Withdraw>,amount:u64)->Result<()>{
+/// Fails unless `shares` can leave a `share_balance` without the senior shares
+/// backing the lender's loans and offer reservations, lent funds come back only as loans are repaid
+pub fn require_redeemable(pool:&LendingPool,lender_account:&LenderAccount,tranche:Tranche,share_balance:u64,shares:u64)->Result<()>{
+    if tranche == Tranche::Senior && lender_account.committed_amount() > 0 {
+        let committed_shares = pool.shares_for_withdrawal(Tranche::Senior, lender_account.committed_amount()).ok_or(LendingPoolError::FundsCurrentlyLent)?;
+
+        let redeemable_shares = share_balance.checked_sub(committed_shares).ok_or(LendingPoolError::FundsCurrentlyLent)?;
+
+        require!(shares<=redeemable_shares,LendingPoolError::FundsCurrentlyLent);
+    }
+    Ok(())
+}
+
+pub fn handler(ctx:Context<Withdraw>,amount:u64,tranche:Tranche)->Result<()>{
     let pool = &mut ctx.accounts.pool;
     let lender_account = &mut ctx.accounts.lender_account;
     let clock = Clock::get()?;
-    // Min amoutn to withdraw is 1USDC 
-    require!(amount>=MIN_WITHDRAWAL_AMOUNT,LendingPoolError::WithdrawalBelowMinimum);
-    // available amount shows how much amount is remaning after giving to the lent if given 
-    require!(lender_account.available_amount>=amount,LendingPoolError::InsufficientAvailableBalance);
 
-    // Check pool has sufficient balane 
+    require!(!pool.pause_flags.withdrawals,LendingPoolError::PoolPaused);
+
+    require!(tranche == Tranche::Senior || !pool.junior_exits_frozen(),LendingPoolError::DefaultPending);
+
+    // Min amount to withdraw is set per pool (pool.config)
+    require!(amount>=pool.config.min_withdrawal_amount,LendingPoolError::WithdrawalBelowMinimum);
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    // Check pool has sufficient balane, liquidity earmarked for queued withdrawals cannot be jumped
+    let unreserved_liquidity = pool.unreserved_liquidity().ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(unreserved_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
+    // shares are the lender's claim on the pool, they include interest accrued since deposit
+    let shares = pool.shares_for_withdrawal(tranche, amount).ok_or(LendingPoolError::InsufficientShares)?;
+
+    let share_balance = ctx.accounts.lender_share_account.amount;
+
+    require!(share_balance>=shares,LendingPoolError::InsufficientShares);
+
+    require_redeemable(pool, lender_account, tranche, share_balance, shares)?;
 
-    require!(pool.available_liquidity>=amount,LendingPoolError::InsufficientPoolLiquidity);
+    let remaning_shares = share_balance.checked_sub(shares).ok_or(LendingPoolError::InsufficientShares)?;
 
-    let remaning_available = lender_account.available_amount.checked_sub(amount).ok_or(LendingPoolError::BelowMinimumBalance)?;
+    let remaning_available = pool.shares_to_amount(tranche, remaning_shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
 
     if remaning_available > 0{
-        require!(remaning_available>=MIN_REMAINING_BALANCE,LendingPoolError::BelowMinimumBalance);
+        require!(remaning_available>=pool.config.min_remaining_balance,LendingPoolError::BelowMinimumBalance);
     }
 
-    let pool_key = pool.key();
+    // funds deployed in the yield strategy come back when the pool account is short
+    let strategy_cpi = optional_strategy_cpi(
+        &ctx.accounts.yield_strategy,
+        &ctx.accounts.strategy_program,
+        &ctx.accounts.strategy_state,
+        &ctx.accounts.strategy_token_account,
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    );
+
+    let recalled = recall_shortfall(pool, ctx.accounts.yield_strategy.as_deref_mut(), strategy_cpi.as_ref(), ctx.accounts.pool_token_account.amount, amount, clock.unix_timestamp)?;
+
+    if recalled > 0 {
+        ctx.accounts.pool_token_account.reload()?;
+    }
+
+    require!(ctx.accounts.pool_token_account.amount>=amount,LendingPoolError::InsufficientPoolLiquidity);
+
     let seeds = &[
         LendingPool::SEED_PREFIX,
         pool.token_mint.as_ref(),
@@ -76,8 +172,9 @@ pub fn handler(ctx:Context<Withdraw>,amount:u64)->Result<()>{
     let signer_seeds = &[&seeds[..]];
 
 
-    let cpi_account = Transfer{
+    let cpi_account = TransferChecked{
         from : ctx.accounts.pool_token_account.to_account_info(),
+        mint : ctx.accounts.token_mint.to_account_info(),
         to : ctx.accounts.lender_token_account.to_account_info(),
         authority : pool.to_account_info()
     };
@@ -85,18 +182,30 @@ pub fn handler(ctx:Context<Withdraw>,amount:u64)->Result<()>{
     let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(),
                                          cpi_account, signer_seeds);
 
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
 
+    let cpi_account = Burn{
+        mint : ctx.accounts.share_mint.to_account_info(),
+        from : ctx.accounts.lender_share_account.to_account_info(),
+        authority : ctx.accounts.lender.to_account_info()
+    };
 
-    // update lender account 
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account);
 
-    lender_account.available_amount = lender_account.available_amount.checked_sub(amount).ok_or(LendingPoolError::ArithmeticOverflow)?;
+    token_interface::burn(cpi_ctx, shares)?;
+
+
+    // update lender account 
 
     lender_account.total_withdrawals = lender_account
     .total_withdrawals
     .checked_add(amount)
     .ok_or(LendingPoolError::ArithmeticOverflow)?;
 
+    // pool-funded interest and losses are booked on the old stake
+    let stake = lender_account.net_deposits();
+    settle_pool_funded(pool, lender_account, stake, clock.unix_timestamp)?;
+
     // Update pool state
     pool.available_liquidity = pool
         .available_liquidity
@@ -107,19 +216,42 @@ pub fn handler(ctx:Context<Withdraw>,amount:u64)->Result<()>{
         .total_deposits
         .checked_sub(amount)
         .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+    match tranche {
+        Tranche::Senior => {
+            pool.total_shares = pool
+                .total_shares
+                .checked_sub(shares)
+                .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        }
+        Tranche::Junior => {
+            pool.junior_deposits = pool
+                .junior_deposits
+                .checked_sub(amount)
+                .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+            pool.junior_shares = pool
+                .junior_shares
+                .checked_sub(shares)
+                .ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        }
+    }
     
     emit!(Withdrawal{
         lender: ctx.accounts.lender.key(),
         pool: pool.key(),
+        tranche,
         amount,
-        remaining_available: lender_account.available_amount,
+        shares_burned: shares,
+        remaining_value: remaning_available,
         remaining_lent: lender_account.lent_amount,
         timestamp: clock.unix_timestamp,
     });
     
     msg!(" Withdrawal successful");
+    msg!("Tranche: {:?}", tranche);
     msg!("Amount withdrawn: {} tokens", amount);
-    msg!("Remaining available: {}", lender_account.available_amount);
+    msg!("Shares burned: {}", shares);
+    msg!("Remaining value: {}", remaning_available);
     msg!("Currently lent out: {}", lender_account.lent_amount);
 
     if lender_account.lent_amount > 0 {
@@ -0,0 +1,393 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{constants::{ACCOUNT_VERSION, MAX_WITHDRAWAL_REQUESTS_PER_LENDER}, errors::LendingPoolError, events::{WithdrawalFilled, WithdrawalQueued}, instructions::{accrue_interest::accrue, strategy::{optional_strategy_cpi, recall_shortfall, token_balance, StrategyCpi}, withdraw::require_redeemable}, states::{InterestRateModel, LenderAccount, LendingPool, Tranche, WithdrawalQueue, WithdrawalRequest, YieldStrategy}};
+
+// Senior shares only: junior is first-loss capital, a queued junior claim would be paid
+// from repaid principal ahead of senior lenders while losses may still be pending.
+// Junior lenders withdraw directly when liquidity is there.
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info>{
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    // senior shares backing the lender's loans cannot be queued
+    #[account(
+        seeds = [
+            LenderAccount::SEED_PREFIX,
+            pool.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = lender_account.bump,
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        init_if_needed,
+        payer = lender,
+        space = WithdrawalQueue::LEN,
+        seeds = [WithdrawalQueue::SEED_PREFIX,pool.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_queue : Account<'info,WithdrawalQueue>,
+
+    #[account(
+        constraint = share_mint.key() == pool.share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    // queued shares are held here until the request is filled
+    #[account(
+        init_if_needed,
+        payer = lender,
+        seeds = [WithdrawalQueue::ESCROW_SEED,pool.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = pool,
+    )]
+    pub escrow_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lender_share_account.mint == pool.share_mint @ LendingPoolError::InvalidShareMint,
+        constraint = lender_share_account.owner == lender.key()
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    // where the withdrawal is paid once filled
+    #[account(
+        constraint = lender_token_account.mint == pool.token_mint,
+        constraint = lender_token_account.owner == lender.key()
+    )]
+    pub lender_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless crank, destination token accounts of the requests
+/// being filled are passed as remaining accounts in queue order
+#[derive(Accounts)]
+pub struct ProcessWithdrawalQueue<'info>{
+    #[account(
+        mut,
+        seeds = [
+            LendingPool::SEED_PREFIX,
+            pool.token_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        seeds = [InterestRateModel::SEED_PREFIX,pool.key().as_ref()],
+        bump = interest_rate_model.bump
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    #[account(
+        mut,
+        seeds = [WithdrawalQueue::SEED_PREFIX,pool.key().as_ref()],
+        bump = withdrawal_queue.bump
+    )]
+    pub withdrawal_queue : Account<'info,WithdrawalQueue>,
+
+    #[account(
+        mut,
+        constraint = share_mint.key() == pool.share_mint @ LendingPoolError::InvalidShareMint
+    )]
+    pub share_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut,
+        seeds = [WithdrawalQueue::ESCROW_SEED,pool.key().as_ref()],
+        bump,
+    )]
+    pub escrow_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LendingPoolError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    #[account(
+        mut ,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    // pools with a yield strategy pass it, funds are recalled when pool_token_account is short
+    #[account(
+        mut,
+        seeds = [YieldStrategy::SEED_PREFIX,pool.key().as_ref()],
+        bump = yield_strategy.bump,
+    )]
+    pub yield_strategy : Option<Account<'info,YieldStrategy>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against yield_strategy before it is called
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn request_handler(ctx:Context<RequestWithdrawal>,shares:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    let queue = &mut ctx.accounts.withdrawal_queue;
+
+    require!(!pool.pause_flags.withdrawals,LendingPoolError::PoolPaused);
+
+    if queue.pool == Pubkey::default() {
+        queue.pool = pool.key();
+        queue.next_request_id = 0;
+        queue.requests = vec![];
+        queue.version = ACCOUNT_VERSION;
+        queue.bump = ctx.bumps.withdrawal_queue;
+    }
+
+    require!(!queue.is_full(),LendingPoolError::WithdrawalQueueFull);
+
+    require!(queue.requests_of(&ctx.accounts.lender.key())<MAX_WITHDRAWAL_REQUESTS_PER_LENDER,LendingPoolError::TooManyWithdrawalRequests);
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let estimated_amount = pool.shares_to_amount(Tranche::Senior, shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    require!(estimated_amount>=pool.config.min_withdrawal_amount,LendingPoolError::WithdrawalBelowMinimum);
+    require!(ctx.accounts.lender_share_account.amount>=shares,LendingPoolError::InsufficientShares);
+
+    require_redeemable(pool, &ctx.accounts.lender_account, Tranche::Senior, ctx.accounts.lender_share_account.amount, shares)?;
+
+    // escrow the shares so they cannot be transferred or withdrawn twice
+
+    let cpi_account = TransferChecked{
+        from : ctx.accounts.lender_share_account.to_account_info(),
+        mint : ctx.accounts.share_mint.to_account_info(),
+        to : ctx.accounts.escrow_share_account.to_account_info(),
+        authority : ctx.accounts.lender.to_account_info()
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_account);
+
+    token_interface::transfer_checked(cpi_ctx, shares, ctx.accounts.share_mint.decimals)?;
+
+    let request_id = queue.next_request_id;
+
+    queue.next_request_id = queue.next_request_id.checked_add(1).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    queue.requests.push(WithdrawalRequest{
+        request_id,
+        lender: ctx.accounts.lender.key(),
+        destination: ctx.accounts.lender_token_account.key(),
+        shares,
+        requested_at: clock.unix_timestamp,
+    });
+
+    pool.queued_withdrawal_shares = pool.queued_withdrawal_shares.checked_add(shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+    let queue_position = queue.requests.len() as u32;
+
+    emit!(WithdrawalQueued{
+        pool: pool.key(),
+        lender: ctx.accounts.lender.key(),
+        request_id,
+        shares,
+        estimated_amount,
+        queue_position,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Withdrawal queued");
+    msg!("Request ID: {}", request_id);
+    msg!("Shares escrowed: {}", shares);
+    msg!("Estimated amount: {} tokens", estimated_amount);
+    msg!("Queue position: {}", queue_position);
+    Ok(())
+}
+
+pub fn process_handler<'info>(ctx:Context<'_, '_, 'info, 'info, ProcessWithdrawalQueue<'info>>,max_fills:u8)->Result<()>{
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    let queue = &mut ctx.accounts.withdrawal_queue;
+
+    require!(!pool.pause_flags.withdrawals,LendingPoolError::PoolPaused);
+
+    accrue(pool, &ctx.accounts.interest_rate_model, clock.unix_timestamp)?;
+
+    let fill = QueueFill{
+        share_mint: ctx.accounts.share_mint.to_account_info(),
+        escrow_share_account: ctx.accounts.escrow_share_account.to_account_info(),
+        token_mint: ctx.accounts.token_mint.to_account_info(),
+        token_decimals: ctx.accounts.token_mint.decimals,
+        pool_token_account: ctx.accounts.pool_token_account.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        strategy: optional_strategy_cpi(
+            &ctx.accounts.yield_strategy,
+            &ctx.accounts.strategy_program,
+            &ctx.accounts.strategy_state,
+            &ctx.accounts.strategy_token_account,
+            ctx.accounts.pool_token_account.to_account_info(),
+            ctx.accounts.token_mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ),
+        best_effort: false,
+    };
+
+    let fills = fill_queue(pool, queue, &fill, ctx.accounts.yield_strategy.as_deref_mut(), ctx.remaining_accounts, max_fills, clock.unix_timestamp)?;
+
+    msg!(" Withdrawal queue processed");
+    msg!("Requests filled: {}", fills);
+    msg!("Requests pending: {}", queue.requests.len());
+    Ok(())
+}
+
+/// Accounts a queue fill moves tokens and escrowed shares between
+pub struct QueueFill<'info>{
+    pub share_mint : AccountInfo<'info>,
+    pub escrow_share_account : AccountInfo<'info>,
+    pub token_mint : AccountInfo<'info>,
+    pub token_decimals : u8,
+    pub pool_token_account : AccountInfo<'info>,
+    pub token_program : AccountInfo<'info>,
+    // the pool's yield strategy, deployed funds are recalled when pool_token_account is short
+    pub strategy : Option<StrategyCpi<'info>>,
+    // stop at a destination that cannot be paid instead of failing (loan repayments)
+    pub best_effort : bool,
+}
+
+/// Fill up to `max_fills` queued withdrawals in FIFO order from available liquidity,
+/// shared by the crank and loan repayments. `destinations` are the token accounts of
+/// the requests being filled, in queue order. A missing or unusable destination fails the
+/// crank, a best effort fill (loan repayments) stops there instead and leaves the rest to
+/// the crank. Returns the number of fills
+pub fn fill_queue<'info>(pool:&mut Account<'info,LendingPool>,queue:&mut WithdrawalQueue,fill:&QueueFill<'info>,mut strategy:Option<&mut YieldStrategy>,destinations:&[AccountInfo<'info>],max_fills:u8,now:i64)->Result<usize>{
+    // pool is mutated inside the loop, so the signer seeds cannot borrow it
+    let token_mint = pool.token_mint;
+    let pool_bump = pool.bump;
+    let seeds = &[
+        LendingPool::SEED_PREFIX,
+        token_mint.as_ref(),
+        &[pool_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let mut fills = 0usize;
+
+    while fills < max_fills as usize && !queue.requests.is_empty() && pool.available_liquidity > 0 {
+        let request = &mut queue.requests[0];
+
+        let destination = match destinations.get(fills) {
+            Some(destination) if payable_destination(destination, request.destination, &token_mint, &fill.token_program) => destination,
+            _ if fill.best_effort => break,
+            _ => return err!(LendingPoolError::InvalidQueueDestination),
+        };
+
+        // fill the head request as far as liquidity allows, partially if needed
+        let full_amount = pool.shares_to_amount(Tranche::Senior, request.shares).ok_or(LendingPoolError::ArithmeticOverflow)?;
+
+        let wanted = full_amount.min(pool.available_liquidity);
+
+        // deployed liquidity is recalled first, without the strategy accounts fills stop at what the pool account holds
+        let balance = token_balance(&fill.pool_token_account)?;
+
+        let recalled = recall_shortfall(pool, strategy.as_deref_mut(), fill.strategy.as_ref(), balance, wanted, now)?;
+
+        let payable = if recalled > 0 { wanted.min(token_balance(&fill.pool_token_account)?) } else { wanted.min(balance) };
+
+        if payable == 0 {
+            break;
+        }
+
+        let (amount, shares) = if payable == full_amount {
+            (full_amount, request.shares)
+        } else {
+            let shares = pool.shares_for_withdrawal(Tranche::Senior, payable).ok_or(LendingPoolError::ArithmeticOverflow)?.min(request.shares);
+            (payable, shares)
+        };
+
+        let cpi_account = TransferChecked{
+            from : fill.pool_token_account.clone(),
+            mint : fill.token_mint.clone(),
+            to : destination.to_account_info(),
+            authority : pool.to_account_info()
+        };
+
+        token_interface::transfer_checked(CpiContext::new_with_signer(fill.token_program.clone(), cpi_account, signer_seeds), amount, fill.token_decimals)?;
+
+        let cpi_account = Burn{
+            mint : fill.share_mint.clone(),
+            from : fill.escrow_share_account.clone(),
+            authority : pool.to_account_info()
+        };
+
+        token_interface::burn(CpiContext::new_with_signer(fill.token_program.clone(), cpi_account, signer_seeds), shares)?;
+
+        pool.available_liquidity = pool.available_liquidity.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        pool.total_deposits = pool.total_deposits.checked_sub(amount).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        pool.total_shares = pool.total_shares.checked_sub(shares).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+        pool.queued_withdrawal_shares = pool.queued_withdrawal_shares.checked_sub(shares).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+        request.shares = request.shares.checked_sub(shares).ok_or(LendingPoolError::ArithmeticUnderflow)?;
+
+        emit!(WithdrawalFilled{
+            pool: pool.key(),
+            lender: request.lender,
+            request_id: request.request_id,
+            amount,
+            shares_burned: shares,
+            remaining_shares: request.shares,
+            timestamp: now,
+        });
+
+        msg!("Filled request #{}: {} tokens", request.request_id, amount);
+
+        fills += 1;
+
+        if request.shares == 0 {
+            queue.requests.remove(0);
+        } else {
+            // partially filled, wait for more liquidity
+            break;
+        }
+    }
+
+    Ok(fills)
+}
+
+/// `destination` is the request's token account and can receive the pool token
+fn payable_destination(destination:&AccountInfo,expected:Pubkey,token_mint:&Pubkey,token_program:&AccountInfo)->bool{
+    if destination.key() != expected || destination.owner != token_program.key {
+        return false;
+    }
+
+    let Ok(data) = destination.try_borrow_data() else {
+        return false;
+    };
+
+    TokenAccount::try_deserialize(&mut &data[..]).is_ok_and(|account| account.mint == *token_mint && !account.is_frozen())
+}
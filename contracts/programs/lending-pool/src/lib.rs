@@ -11,6 +11,7 @@ pub mod instructions;
 pub mod errors;
 pub mod events;
 pub mod constants;
+pub mod migration;
 
 
 pub use states::*;
@@ -36,14 +37,16 @@ pub mod lending_pool {
     /// # Arguments
     /// * `ctx` - Context with lender, pool, and token accounts
     /// * `amount` - Amount to deposit (in base units)
+    /// * `tranche` - Senior, or Junior to take first loss for a higher yield
     /// 
     /// # Returns
     /// * `Result<()>` - Success or error
     pub fn deposit(
         ctx: Context<Deposit>,
         amount: u64,
+        tranche: Tranche,
     ) -> Result<()> {
-        instructions::deposit::handler(ctx, amount)
+        instructions::deposit::handler(ctx, amount, tranche)
     }
 
     /// Withdraw tokens from the lending pool
@@ -51,39 +54,820 @@ pub mod lending_pool {
     /// # Arguments
     /// * `ctx` - Context with lender, pool, and token accounts
     /// * `amount` - Amount to withdraw (in base units)
+    /// * `tranche` - Tranche whose shares are redeemed
     /// 
     /// # Returns
     /// * `Result<()>` - Success or error
     pub fn withdraw(
         ctx: Context<Withdraw>,
         amount: u64,
+        tranche: Tranche,
     ) -> Result<()> {
-        instructions::withdraw::handler(ctx, amount)
+        instructions::withdraw::handler(ctx, amount, tranche)
     }
 
-    /// Emergency pause pool (only authority can call)
+    /// Emergency pause selected pool operations (only Pauser role can call)
     /// 
     /// # Arguments
-    /// * `ctx` - Context with authority and pool accounts
+    /// * `ctx` - Context with pauser, roles and pool accounts
+    /// * `flags` - Operations to pause, repayments additionally need the Admin role
     /// 
     /// # Returns
     /// * `Result<()>` - Success or error
     pub fn pause_pool(
         ctx: Context<PausePool>,
+        flags: PauseFlags,
     ) -> Result<()> {
-        instructions::pause::handler(ctx)
+        instructions::pause::handler(ctx, flags)
     }
 
-    /// Unpause pool (only authority can call)
+    /// Unpause selected pool operations (only Pauser role can call)
     /// 
     /// # Arguments
-    /// * `ctx` - Context with authority and pool accounts
+    /// * `ctx` - Context with pauser, roles and pool accounts
+    /// * `flags` - Operations to unpause
     /// 
     /// # Returns
     /// * `Result<()>` - Success or error
     pub fn unpause_pool(
         ctx: Context<UnpausePool>,
+        flags: PauseFlags,
+    ) -> Result<()> {
+        instructions::pause::unpause_handler(ctx, flags)
+    }
+
+    /// Update the pool's kinked interest rate model (only RiskManager role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles, pool and rate model accounts
+    /// * `base_rate_bps` - Borrow rate at 0% utilization
+    /// * `slope1_bps` - Rate increase from 0% up to the kink
+    /// * `kink_utilization` - Utilization percentage of the kink
+    /// * `slope2_bps` - Rate increase from the kink up to 100%
+    /// * `max_utilization` - Utilization percentage at which new loans are rejected
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn set_interest_rate_model(
+        ctx: Context<SetInterestRateModel>,
+        base_rate_bps: u16,
+        slope1_bps: u16,
+        kink_utilization: u64,
+        slope2_bps: u16,
+        max_utilization: u64,
+    ) -> Result<()> {
+        instructions::interest_rate::handler(
+            ctx,
+            base_rate_bps,
+            slope1_bps,
+            kink_utilization,
+            slope2_bps,
+            max_utilization,
+        )
+    }
+
+    /// Advance the pool borrow index (permissionless)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool and rate model accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn accrue_interest(
+        ctx: Context<AccrueInterest>,
+    ) -> Result<()> {
+        instructions::accrue_interest::handler(ctx)
+    }
+
+    /// Set the protocol reserve factor and treasury account (only authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool and treasury token account
+    /// * `reserve_factor_bps` - Share of accrued interest kept as reserves
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn set_reserve_config(
+        ctx: Context<SetReserveConfig>,
+        reserve_factor_bps: u16,
+    ) -> Result<()> {
+        instructions::reserves::set_config_handler(ctx, reserve_factor_bps)
+    }
+
+    /// Transfer collected protocol reserves to the treasury (only authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool and token accounts
+    /// * `amount` - Amount of reserves to collect (in base units)
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn collect_reserves(
+        ctx: Context<CollectReserves>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::reserves::collect_handler(ctx, amount)
+    }
+
+    /// Queue a withdrawal when pool liquidity is lent out, filled as loans are repaid
+    /// Senior shares only, junior first-loss shares cannot jump ahead of senior claims
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool, queue and share accounts
+    /// * `shares` - Senior pool shares to redeem once liquidity returns
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn request_withdrawal(
+        ctx: Context<RequestWithdrawal>,
+        shares: u64,
+    ) -> Result<()> {
+        instructions::withdrawal_queue::request_handler(ctx, shares)
+    }
+
+    /// Fill queued withdrawals in FIFO order from available liquidity (permissionless)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool and queue accounts, destination token accounts as remaining accounts
+    /// * `max_fills` - Maximum number of requests to fill
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn process_withdrawal_queue<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessWithdrawalQueue<'info>>,
+        max_fills: u8,
+    ) -> Result<()> {
+        instructions::withdrawal_queue::process_handler(ctx, max_fills)
+    }
+
+    /// Lock pool shares for a fixed term in exchange for boosted yield
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool, lock position and share accounts
+    /// * `position_id` - Lender-chosen id for the lock position
+    /// * `shares` - Pool shares to lock
+    /// * `lock_days` - Lock term in days (30, 90 or 180)
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn lock_deposit(
+        ctx: Context<LockDeposit>,
+        position_id: u64,
+        shares: u64,
+        lock_days: u16,
+    ) -> Result<()> {
+        instructions::lock_deposit::lock_handler(ctx, position_id, shares, lock_days)
+    }
+
+    /// Unlock a lock position, paying the bonus from protocol reserves at maturity or the penalty before it
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool, lock position and share accounts
+    /// * `position_id` - Id of the lock position to close
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn unlock_deposit(
+        ctx: Context<UnlockDeposit>,
+        position_id: u64,
+    ) -> Result<()> {
+        instructions::lock_deposit::unlock_handler(ctx, position_id)
+    }
+
+    /// Set the junior tranche's premium on senior interest (only RiskManager role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles, pool and rate model accounts
+    /// * `junior_premium_bps` - Cut of senior interest paid to the junior tranche
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn set_tranche_config(
+        ctx: Context<SetTrancheConfig>,
+        junior_premium_bps: u16,
+    ) -> Result<()> {
+        instructions::tranche::handler(ctx, junior_premium_bps)
+    }
+
+    /// Create the protocol-wide pool registry
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority and registry accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn initialize_registry(
+        ctx: Context<InitializeRegistry>,
+    ) -> Result<()> {
+        instructions::registry::initialize_handler(ctx)
+    }
+
+    /// List a pool in the registry or update its risk params (only RiskManager role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles, registry, pool and token mint accounts
+    /// * `max_loan_amount` - Largest principal accepted against the pool (in base units)
+    /// * `min_credit_score` - Lowest credit score accepted against the pool
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn list_market(
+        ctx: Context<ListMarket>,
+        max_loan_amount: u64,
+        min_credit_score: u16,
+    ) -> Result<()> {
+        instructions::registry::list_handler(ctx, max_loan_amount, min_credit_score)
+    }
+
+    /// Delist a pool so no new loans can be requested against it (only RiskManager role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles, registry and pool accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn delist_market(
+        ctx: Context<DelistMarket>,
+    ) -> Result<()> {
+        instructions::registry::delist_handler(ctx)
+    }
+
+    /// Update the pool's deposit and withdrawal limits and supply caps (only RiskManager role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles and pool accounts
+    /// * `config` - New limits and caps (0 = uncapped), in base units of the pool token
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn update_pool_config(
+        ctx: Context<UpdatePoolConfig>,
+        config: PoolConfig,
+    ) -> Result<()> {
+        instructions::pool_config::handler(ctx, config)
+    }
+
+    /// Propose a new pool authority, it takes over once it accepts (only authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority and pool accounts
+    /// * `new_authority` - Proposed authority, e.g. a multisig
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::authority::propose_handler(ctx, new_authority)
+    }
+
+    /// Accept a pending authority transfer (only the pending authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with new authority and pool accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn accept_authority(
+        ctx: Context<AcceptAuthority>,
+    ) -> Result<()> {
+        instructions::authority::accept_handler(ctx)
+    }
+
+    /// Cancel a pending authority transfer (only authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority and pool accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn cancel_authority_transfer(
+        ctx: Context<CancelAuthorityTransfer>,
+    ) -> Result<()> {
+        instructions::authority::cancel_handler(ctx)
+    }
+
+    /// Create the protocol roles account, the signer becomes the first admin
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with admin and roles accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn initialize_roles(
+        ctx: Context<InitializeRoles>,
+    ) -> Result<()> {
+        instructions::roles::initialize_roles_handler(ctx)
+    }
+
+    /// Grant a protocol role to an account (only Admin role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with admin and roles accounts
+    /// * `role` - Role to grant
+    /// * `member` - Account receiving the role
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn grant_role(
+        ctx: Context<GrantRole>,
+        role: Role,
+        member: Pubkey,
+    ) -> Result<()> {
+        instructions::roles::grant_handler(ctx, role, member)
+    }
+
+    /// Revoke a protocol role from an account (only Admin role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with admin and roles accounts
+    /// * `role` - Role to revoke
+    /// * `member` - Account losing the role
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn revoke_role(
+        ctx: Context<RevokeRole>,
+        role: Role,
+        member: Pubkey,
+    ) -> Result<()> {
+        instructions::roles::revoke_handler(ctx, role, member)
+    }
+
+    /// Issue or renew an attestation for a wallet, signed by the issuer
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with issuer and attestation accounts
+    /// * `subject` - Wallet the credential is issued to
+    /// * `credential_type` - Issuer-defined credential type (non-zero)
+    /// * `expires_at` - Expiry timestamp, 0 = never expires
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        subject: Pubkey,
+        credential_type: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::attestation::issue_attestation_handler(ctx, subject, credential_type, expires_at)
+    }
+
+    /// Revoke an attestation (only its issuer can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with issuer and attestation accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn revoke_attestation(
+        ctx: Context<RevokeAttestation>,
+    ) -> Result<()> {
+        instructions::attestation::revoke_attestation_handler(ctx)
+    }
+
+    /// Set the attestations required to deposit into or borrow from a pool (only RiskManager role can call)
+    /// Share transfers and redemptions are not gated, see AccessPolicy
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles and pool accounts
+    /// * `policy` - Trusted issuer and required credential types
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn set_access_policy(
+        ctx: Context<SetAccessPolicy>,
+        policy: AccessPolicy,
+    ) -> Result<()> {
+        instructions::attestation::set_policy_handler(ctx, policy)
+    }
+
+    /// Borrow pool liquidity for the rest of the transaction, a flash_repay on the
+    /// same pool must follow with no other pool instruction in between
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with borrower, pool, token and instructions sysvar accounts
+    /// * `amount` - Amount to borrow
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn flash_borrow(
+        ctx: Context<FlashBorrow>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::flash_loan::borrow_handler(ctx, amount)
+    }
+
+    /// Repay the open flash loan plus fee, the fee is paid to depositors
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with borrower, pool and token accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn flash_repay(
+        ctx: Context<FlashRepay>,
+    ) -> Result<()> {
+        instructions::flash_loan::repay_handler(ctx)
+    }
+
+    /// Set the pool's flash loan fee (only RiskManager role can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with risk manager, roles and pool accounts
+    /// * `fee_bps` - Fee in basis points of the borrowed amount
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn set_flash_loan_fee(
+        ctx: Context<SetFlashLoanFee>,
+        fee_bps: u16,
+    ) -> Result<()> {
+        instructions::flash_loan::set_fee_handler(ctx, fee_bps)
+    }
+
+    /// Claim interest paid into the pool for the lender's shares, burning shares worth it
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool, share and token accounts
+    /// * `amount` - Interest to claim (in base units)
+    /// * `tranche` - Tranche whose shares earned the interest
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn claim_interest(
+        ctx: Context<ClaimInterest>,
+        amount: u64,
+        tranche: Tranche,
+    ) -> Result<()> {
+        instructions::claim_interest::claim_handler(ctx, amount, tranche)
+    }
+
+    /// Compound interest earned in the other tranche into shares of `tranche`
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool and both tranches' share accounts
+    /// * `amount` - Interest to compound (in base units)
+    /// * `tranche` - Tranche the interest is compounded into
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn compound_interest(
+        ctx: Context<CompoundInterest>,
+        amount: u64,
+        tranche: Tranche,
+    ) -> Result<()> {
+        instructions::claim_interest::compound_handler(ctx, amount, tranche)
+    }
+
+    /// Create the pool's insurance fund and its vault (only authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool, fund and vault accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn initialize_insurance_fund(
+        ctx: Context<InitializeInsuranceFund>,
+    ) -> Result<()> {
+        instructions::insurance::initialize_insurance_handler(ctx)
+    }
+
+    /// Stake tokens into the pool's insurance fund (not withdrawable)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with contributor, fund and token accounts
+    /// * `amount` - Amount to contribute (in base units)
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn contribute_insurance(
+        ctx: Context<ContributeInsurance>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::insurance::contribute_handler(ctx, amount)
+    }
+
+    /// Move protocol reserves into the insurance fund (only authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool, fund and token accounts
+    /// * `amount` - Amount of reserves to move (in base units)
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn fund_insurance_from_reserves(
+        ctx: Context<FundInsuranceFromReserves>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::insurance::fund_from_reserves_handler(ctx, amount)
+    }
+
+    /// Realize written-off debt: insurance fund first, the rest socialized
+    /// across depositors, junior tranche first (permissionless)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool, rate model, fund PDA, its vault (required once the fund exists) and token accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn absorb_bad_debt(
+        ctx: Context<AbsorbBadDebt>,
+    ) -> Result<()> {
+        instructions::insurance::absorb_bad_debt_handler(ctx)
+    }
+
+    /// Open a lender account without depositing, for holders of transferred shares
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool and the new lender account
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn open_lender_account(
+        ctx: Context<OpenLenderAccount>,
+    ) -> Result<()> {
+        instructions::close_lender_account::open_handler(ctx)
+    }
+
+    /// Close a lender account with nothing lent, reserved or queued and return its rent to the
+    /// lender. Shares are not tied to the account, whatever the lender holds stays theirs
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool, lender account and the queue
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn close_lender_account(
+        ctx: Context<CloseLenderAccount>,
+    ) -> Result<()> {
+        instructions::close_lender_account::close_handler(ctx)
+    }
+
+    /// Withdraw all senior and junior shares, then close the lender account
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with lender, pool, lender account, both tranches' share accounts, queue and token accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn exit_pool(
+        ctx: Context<ExitPool>,
+    ) -> Result<()> {
+        instructions::close_lender_account::exit_handler(ctx)
+    }
+
+    /// Upgrade a LendingPool account to the current layout version
+    /// Permissionless, the payer funds any extra rent
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with payer and the account to migrate
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn migrate_lending_pool(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<LendingPool>(ctx)
+    }
+
+    /// Upgrade a LenderAccount account to the current layout version
+    /// Permissionless, the payer funds any extra rent
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with payer and the account to migrate
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn migrate_lender_account(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<LenderAccount>(ctx)
+    }
+
+    /// Pool balances, rates and exchange rates with interest projected to now
+    /// Read-only, simulate it to read the return data
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool and interest rate model
+    /// 
+    /// # Returns
+    /// * `Result<PoolStats>` - Pool snapshot
+    pub fn get_pool_stats(
+        ctx: Context<GetPoolStats>,
+    ) -> Result<PoolStats> {
+        instructions::stats::pool_stats_handler(ctx)
+    }
+
+    /// A lender's balances and share value with interest projected to now
+    /// Read-only, simulate it to read the return data
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool, lender account, rate model and optional share accounts
+    /// 
+    /// # Returns
+    /// * `Result<LenderPosition>` - Lender snapshot
+    pub fn get_lender_position(
+        ctx: Context<GetLenderPosition>,
+    ) -> Result<LenderPosition> {
+        instructions::stats::lender_position_handler(ctx)
+    }
+
+    /// Attach a yield strategy program the pool can park idle liquidity in
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool, strategy program, state and token account
+    /// * `max_allocation_bps` - Cap on deployed funds as a share of available liquidity
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn initialize_yield_strategy(
+        ctx: Context<InitializeYieldStrategy>,
+        max_allocation_bps: u16,
+    ) -> Result<()> {
+        instructions::strategy::initialize_strategy_handler(ctx, max_allocation_bps)
+    }
+
+    /// Change the cap on funds deployed in the yield strategy (pool authority)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool and yield strategy
+    /// * `max_allocation_bps` - New cap as a share of available liquidity
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn set_strategy_allocation(
+        ctx: Context<SetStrategyAllocation>,
+        max_allocation_bps: u16,
+    ) -> Result<()> {
+        instructions::strategy::set_allocation_handler(ctx, max_allocation_bps)
+    }
+
+    /// Move idle pool liquidity into the yield strategy, within the allocation cap (pool authority)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool, yield strategy and strategy accounts
+    /// * `amount` - Tokens to deploy
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn allocate_to_strategy(
+        ctx: Context<MoveStrategyFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::strategy::allocate_handler(ctx, amount)
+    }
+
+    /// Move deployed funds back from the yield strategy into the pool (pool authority)
+    /// Withdrawals recall what they need on their own
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool, yield strategy and strategy accounts
+    /// * `amount` - Tokens to recall
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn recall_from_strategy(
+        ctx: Context<MoveStrategyFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::strategy::recall_handler(ctx, amount)
+    }
+
+    /// Collect strategy yield above the deployed principal into the pool as interest
+    /// Permissionless crank
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool, yield strategy, rate model and strategy accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn harvest_strategy(
+        ctx: Context<HarvestStrategy>,
+    ) -> Result<()> {
+        instructions::strategy::harvest_handler(ctx)
+    }
+
+    /// Book what the strategy lost below its deployed principal as pool bad debt,
+    /// absorb_bad_debt then covers it from insurance and socializes the rest (only pool authority can call)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with authority, pool, yield strategy, rate model and strategy accounts
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn realize_strategy_loss(
+        ctx: Context<RealizeStrategyLoss>,
+    ) -> Result<()> {
+        instructions::strategy::realize_loss_handler(ctx)
+    }
+
+    /// Lend pool liquidity to a borrower (called by LoanManager)
+    /// The lender account is left out for pool-funded loans
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with loan authority, pool, rate model, token and optional strategy accounts
+    /// * `loan_id` - Loan being disbursed
+    /// * `amount` - Principal to transfer
+    /// * `reserved` - Offer reservation of the lender the loan was matched against, released in full
+    /// 
+    /// # Returns
+    /// * `Result<u128>` - Borrow index the loan starts at
+    pub fn disburse_loan_funds(
+        ctx: Context<DisburseLoanFunds>,
+        loan_id: u64,
+        amount: u64,
+        reserved: u64,
+    ) -> Result<u128> {
+        instructions::loans::disburse_handler(ctx, loan_id, amount, reserved)
+    }
+
+    /// Book a loan repayment already transferred into the pool (called by LoanManager)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with loan authority, pool, optional lender account and the optional
+    ///   withdrawal queue accounts, destination token accounts of queued requests as remaining
+    ///   accounts. Queued withdrawals are filled best effort, the repayment never fails on them
+    /// * `loan_id` - Loan being repaid
+    /// * `principal` - Principal part of the repayment
+    /// * `interest` - Interest part of the repayment
+    /// * `closed` - Whether the repayment paid the loan off
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn record_loan_repayment<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordLoanRepayment<'info>>,
+        loan_id: u64,
+        principal: u64,
+        interest: u64,
+        closed: bool,
+    ) -> Result<()> {
+        instructions::loans::repayment_handler(ctx, loan_id, principal, interest, closed)
+    }
+
+    /// Book a written-off loan's debt as pool bad debt (called by LoanManager)
+    /// absorb_bad_debt then covers it from insurance and socializes the rest
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with loan authority, pool and optional lender account
+    /// * `loan_id` - Loan being written off
+    /// * `principal` - Principal still outstanding
+    /// * `loss` - Debt lost, principal and accrued interest
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn record_loan_write_off(
+        ctx: Context<RecordLoanOutcome>,
+        loan_id: u64,
+        principal: u64,
+        loss: u64,
+    ) -> Result<()> {
+        instructions::loans::write_off_handler(ctx, loan_id, principal, loss)
+    }
+
+    /// Record that LoanManager defaulted a loan, junior exits stay frozen until it is written off
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with loan authority and pool
+    /// * `loan_id` - Loan defaulted
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn record_loan_default(
+        ctx: Context<RecordLoanOutcome>,
+        loan_id: u64,
+    ) -> Result<()> {
+        instructions::loans::default_handler(ctx, loan_id)
+    }
+
+    /// Reserve lendable funds of a lender for a standing offer (called by LoanManager)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with loan authority, pool, lender account and the lender's senior shares
+    /// * `amount` - Amount to reserve
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn reserve_lender_funds(
+        ctx: Context<ReserveLenderFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::loans::reserve_handler(ctx, amount)
+    }
+
+    /// Release part of a lender's offer reservation (called by LoanManager)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with loan authority, pool and lender account
+    /// * `amount` - Amount to release
+    /// 
+    /// # Returns
+    /// * `Result<()>` - Success or error
+    pub fn release_lender_funds(
+        ctx: Context<ReserveLenderFunds>,
+        amount: u64,
     ) -> Result<()> {
-        instructions::pause::unpause_handler(ctx)
+        instructions::loans::release_reservation_handler(ctx, amount)
     }
-}
\ No newline at end of file
+}
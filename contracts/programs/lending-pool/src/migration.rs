@@ -0,0 +1,72 @@
+use anchor_lang::{prelude::*, system_program::{self, Transfer}};
+
+/// Account type whose baseline layout (version 0, no version byte) is upgraded in place
+/// Shared by every program of the protocol, each maps its own baseline structs
+///
+/// Every account of the protocol starts with a `version` byte set to ACCOUNT_VERSION when it
+/// is created. A layout change bumps ACCOUNT_VERSION and adds the step from the previous
+/// layout, accounts that never had a baseline layout have no migration until then
+pub trait Migrate: AccountSerialize + AccountDeserialize + Discriminator + Sized {
+    /// baseline struct, the account data after the discriminator
+    type V0: AnchorDeserialize;
+    /// size the baseline program allocated, version 0 accounts are told apart by it
+    const V0_SPACE: usize;
+    /// size of the current layout
+    const SPACE: usize;
+
+    /// Current layout of a baseline account, fields the baseline did not have start
+    /// as a freshly initialized account would
+    fn from_v0(old: Self::V0, now: i64) -> Result<Self>;
+}
+
+/// Rewrite a version 0 `account` in the current layout of T, `payer` tops up the rent
+/// for the larger layout. The errors are the calling program's own
+pub fn migrate_account<'info, T: Migrate>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    now: i64,
+    invalid_layout: Error,
+    already_migrated: Error,
+) -> Result<()> {
+    let old = {
+        let data = account.try_borrow_data()?;
+
+        if data.len() <= 8 || data[..8] != *T::DISCRIMINATOR {
+            return Err(invalid_layout);
+        }
+
+        if data.len() == T::SPACE {
+            return Err(already_migrated);
+        }
+
+        if data.len() != T::V0_SPACE {
+            return Err(invalid_layout);
+        }
+
+        T::V0::deserialize(&mut &data[8..]).map_err(|_| invalid_layout)?
+    };
+
+    let migrated = T::from_v0(old, now)?;
+
+    let rent_needed = Rent::get()?.minimum_balance(T::SPACE).saturating_sub(account.lamports());
+
+    if rent_needed > 0 {
+        let cpi_account = Transfer{
+            from : payer.clone(),
+            to : account.clone(),
+        };
+
+        system_program::transfer(CpiContext::new(system_program.clone(), cpi_account), rent_needed)?;
+    }
+
+    account.resize(T::SPACE)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+
+    // the serialized layout can be shorter than the account, nothing of the old bytes is kept
+    data.fill(0);
+    migrated.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
@@ -1,50 +1,141 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{ACCOUNT_VERSION, BASIS_POINTS_DIVISOR, BORROW_INDEX_PRECISION, DEFAULT_CONFIG_DECIMALS, EARLY_UNLOCK_PENALTY_BPS, LOCK_MULTIPLIER_LONG_BPS, LOCK_MULTIPLIER_MEDIUM_BPS, LOCK_MULTIPLIER_SHORT_BPS, LOCK_TERM_LONG_DAYS, LOCK_TERM_MEDIUM_DAYS, LOCK_TERM_SHORT_DAYS, MAX_REGISTERED_MARKETS, MAX_ROLE_GRANTS, MIN_DEPOSIT_AMOUNT, NO_CREDENTIAL, MIN_REMAINING_BALANCE, MIN_WITHDRAWAL_AMOUNT, MAX_WITHDRAWAL_QUEUE_LENGTH, SECONDS_PER_YEAR, SHARE_PRICE_PRECISION, STAKE_ACCUMULATOR_PRECISION};
+
 
 #[account]
 
 pub struct  LendingPool{
+    // layout version
+    pub version : u8,
+
     // Authority to pause and resume the pool 
     pub authority : Pubkey,
     // address for the token that need to mint (USDC Address)
     pub token_mint : Pubkey,
     // account that hold all deposited token 
     pub pool_token_account: Pubkey,
+    // SPL mint for the pool share (LP receipt) token
+    pub share_mint : Pubkey,
 
     pub pool_name : String,
     // 
     pub total_deposits : u64,
+    // total supply of share tokens minted against total_deposits
+    pub total_shares : u64,
 
     pub available_liquidity : u64,
 
     pub total_borrowed : u64,
 
     pub cumulative_interest : u64,
+    // compounding borrow index (BORROW_INDEX_PRECISION = 1.0), loans snapshot it at origination
+    pub borrow_index : u128,
+    // last time borrow_index was advanced
+    pub last_accrual_timestamp : i64,
+    // token account that receives collected protocol reserves
+    pub treasury : Pubkey,
+    // share of accrued interest kept by the protocol (basis points)
+    pub reserve_factor_bps : u16,
+    // reserves paid into pool_token_account, not owned by depositors, collectable
+    pub total_reserves : u64,
+    // shares escrowed in the withdrawal queue, their value has first claim on liquidity
+    pub queued_withdrawal_shares : u64,
+    // first-loss tranche share token, senior depositors hold share_mint
+    pub junior_share_mint : Pubkey,
+    // part of total_deposits owned by the junior tranche, the rest is senior
+    pub junior_deposits : u64,
+    // total supply of junior share tokens
+    pub junior_shares : u64,
+    // cut of the senior tranche's interest paid to junior for taking first loss (basis points)
+    pub junior_premium_bps : u16,
+    // proposed new authority, takes over only once it accepts (default = none)
+    pub pending_authority : Pubkey,
+    // attestations lenders and borrowers need to use the pool
+    pub access_policy : AccessPolicy,
+    // fee on flash loans, paid to depositors like interest
+    pub flash_loan_fee_bps : u16,
+    // principal of the flash loan open in the current transaction, 0 = none
+    // while non-zero every other instruction touching pool balances fails
+    pub flash_loan_amount : u64,
+    // outstanding principal of loans funded by the pool as a whole rather than one lender
+    pub pool_funded_principal : u64,
+    // sum of lender stakes (net deposits), weight of the accumulators below
+    pub total_lender_stake : u64,
+    // pool-funded loan interest per unit of stake (STAKE_ACCUMULATOR_PRECISION = 1.0)
+    pub interest_per_stake : u128,
+    // pool-funded loan write-offs per unit of stake
+    pub loss_per_stake : u128,
+    // written-off debt not yet absorbed, covered by the insurance fund first and then
+    // socialized across depositors by absorb_bad_debt. Shares cannot be priced while non-zero
+    pub bad_debt : u64,
+    // pool-funded principal inside bad_debt, charged to lender stakes as far as it is not covered
+    pub bad_debt_stake_loss : u64,
 
     pub active_loans_count : u32,
 
     pub total_loans_count : u32,
-    pub paused : bool,
+    // each operation can be paused on its own
+    pub pause_flags : PauseFlags,
 
     pub created_at : i64,
+    // depositors' part of accrued interest borrowers haven't paid yet, counted in total_deposits
+    // for pricing new shares but not paid out on redemption until it is paid
+    pub unpaid_interest : u64,
+    // junior tranche's part of unpaid_interest
+    pub unpaid_junior_interest : u64,
+    // protocol's part of accrued interest borrowers haven't paid yet, moves to total_reserves when paid
+    pub unpaid_reserves : u64,
+    // loans LoanManager has defaulted but not written off yet, junior exits are frozen meanwhile
+    pub pending_defaults : u32,
+    // per-pool deposit and withdrawal limits, in base units of token_mint
+    pub config : PoolConfig,
 
     pub bump : u8
 }
 
 impl LendingPool {
     pub const LEN: usize = 8 + // discriminator
+    1 + // version
     32 + // authority
     32 + // token_mint
     32 + // pool_token_account
+    32 + // share_mint
     (4 + 50) + // pool_name (max 50 chars)
     8 + // total_deposits
+    8 + // total_shares
     8 + // available_liquidity
     8 + // total_borrowed
     8 + // cumulative_interest
+    16 + // borrow_index
+    8 + // last_accrual_timestamp
+    32 + // treasury
+    2 + // reserve_factor_bps
+    8 + // total_reserves
+    8 + // queued_withdrawal_shares
+    32 + // junior_share_mint
+    8 + // junior_deposits
+    8 + // junior_shares
+    2 + // junior_premium_bps
+    32 + // pending_authority
+    AccessPolicy::LEN + // access_policy
+    2 + // flash_loan_fee_bps
+    8 + // flash_loan_amount
+    8 + // pool_funded_principal
+    8 + // total_lender_stake
+    16 + // interest_per_stake
+    16 + // loss_per_stake
+    8 + // bad_debt
+    8 + // bad_debt_stake_loss
     4 + // active_loans_count
     4 + // total_loans_count
-    1 + // paused
+    PauseFlags::LEN + // pause_flags
     8 + // created_at
+    8 + // unpaid_interest
+    8 + // unpaid_junior_interest
+    8 + // unpaid_reserves
+    4 + // pending_defaults
+    PoolConfig::LEN + // config
     1; // bump
 
     pub fn calculate_apy(&self) -> u64 {
@@ -63,11 +154,473 @@ impl LendingPool {
         }
         (self.total_borrowed * 100) / self.total_deposits
     }
-    
+
+    /// Utilization if `additional_borrow` more were lent out
+    pub fn projected_utilization(&self, additional_borrow: u64) -> u64 {
+        if self.total_deposits == 0 {
+            return 100;
+        }
+        ((self.total_borrowed as u128 + additional_borrow as u128) * 100
+            / self.total_deposits as u128) as u64
+    }
+
+    /// Senior tranche value, everything in total_deposits not owned by junior
+    pub fn senior_deposits(&self) -> u64 {
+        self.total_deposits.saturating_sub(self.junior_deposits)
+    }
+
+    /// (value, share supply) of a tranche, shares are minted at this value
+    pub fn tranche_totals(&self, tranche: Tranche) -> (u64, u64) {
+        match tranche {
+            Tranche::Senior => (self.senior_deposits(), self.total_shares),
+            Tranche::Junior => (self.junior_deposits, self.junior_shares),
+        }
+    }
+
+    pub fn tranche_share_mint(&self, tranche: Tranche) -> Pubkey {
+        match tranche {
+            Tranche::Senior => self.share_mint,
+            Tranche::Junior => self.junior_share_mint,
+        }
+    }
+
+    /// (value, share supply) of a tranche without its unpaid interest, shares are redeemed
+    /// at this value so nobody withdraws interest a borrower has yet to pay
+    pub fn redeemable_totals(&self, tranche: Tranche) -> (u64, u64) {
+        match tranche {
+            Tranche::Senior => (
+                self.senior_deposits().saturating_sub(self.unpaid_interest.saturating_sub(self.unpaid_junior_interest)),
+                self.total_shares,
+            ),
+            Tranche::Junior => (
+                self.junior_deposits.saturating_sub(self.unpaid_junior_interest),
+                self.junior_shares,
+            ),
+        }
+    }
+
+    /// Shares of `tranche` to mint for a deposit of `amount` tokens
+    /// First deposit is minted 1:1, later deposits at the tranche's value including unpaid interest
+    pub fn amount_to_shares(&self, tranche: Tranche, amount: u64) -> Option<u64> {
+        let (value, shares) = self.tranche_totals(tranche);
+        if shares == 0 || value == 0 {
+            return Some(amount);
+        }
+        (amount as u128)
+            .checked_mul(shares as u128)
+            .and_then(|v| v.checked_div(value as u128))
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Tokens redeemable for `shares` of `tranche` at its exchange rate (rounded down)
+    pub fn shares_to_amount(&self, tranche: Tranche, shares: u64) -> Option<u64> {
+        let (value, total_shares) = self.redeemable_totals(tranche);
+        if total_shares == 0 {
+            return Some(0);
+        }
+        (shares as u128)
+            .checked_mul(value as u128)
+            .and_then(|v| v.checked_div(total_shares as u128))
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Shares of `tranche` to burn for withdrawing `amount` tokens (rounded up, in favour of the pool)
+    pub fn shares_for_withdrawal(&self, tranche: Tranche, amount: u64) -> Option<u64> {
+        let (value, shares) = self.redeemable_totals(tranche);
+        if value == 0 {
+            return None;
+        }
+        let numerator = (amount as u128).checked_mul(shares as u128)?;
+        let denominator = value as u128;
+        numerator
+            .checked_add(denominator - 1)
+            .and_then(|v| v.checked_div(denominator))
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// A tranche whose value was wiped out by losses but still has shares outstanding
+    /// cannot take new deposits, they would be diluted by the worthless shares
+    pub fn is_tranche_wiped_out(&self, tranche: Tranche) -> bool {
+        let (value, shares) = self.tranche_totals(tranche);
+        value == 0 && shares > 0
+    }
+
+    /// Advance borrow_index to `now` at the model's current borrow rate
+    /// Accrued interest is added to total_borrowed and split between depositors
+    /// (total_deposits) and the protocol (unpaid_reserves) by reserve_factor_bps.
+    /// Both parts stay unpaid until borrowers pay them, see settle_unpaid_interest.
+    /// The depositors' part is then split between the tranches, see junior_interest_share.
+    /// Returns (interest, reserves) accrued.
+    pub fn accrue_interest(&mut self, model: &InterestRateModel, now: i64) -> Option<(u64, u64)> {
+        let elapsed = now.checked_sub(self.last_accrual_timestamp)?;
+        if elapsed <= 0 {
+            return Some((0, 0));
+        }
+        let rate = model.borrow_rate(self.calculate_utilization());
+
+        // simple interest over the elapsed period, compounded into the index on every accrual
+        let period_factor = (rate as u128)
+            .checked_mul(BORROW_INDEX_PRECISION)?
+            .checked_mul(elapsed as u128)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128 * SECONDS_PER_YEAR as u128)?;
+
+        let index_increase = self
+            .borrow_index
+            .checked_mul(period_factor)?
+            .checked_div(BORROW_INDEX_PRECISION)?;
+
+        let interest = (self.total_borrowed as u128)
+            .checked_mul(period_factor)?
+            .checked_div(BORROW_INDEX_PRECISION)
+            .and_then(|v| u64::try_from(v).ok())?;
+
+        let reserves = self.reserve_share(interest)?;
+        let junior_interest = self.junior_interest_share(interest - reserves)?;
+
+        self.borrow_index = self.borrow_index.checked_add(index_increase)?;
+        self.total_borrowed = self.total_borrowed.checked_add(interest)?;
+        self.total_deposits = self.total_deposits.checked_add(interest - reserves)?;
+        self.junior_deposits = self.junior_deposits.checked_add(junior_interest)?;
+        self.unpaid_interest = self.unpaid_interest.checked_add(interest - reserves)?;
+        self.unpaid_junior_interest = self.unpaid_junior_interest.checked_add(junior_interest)?;
+        self.unpaid_reserves = self.unpaid_reserves.checked_add(reserves)?;
+        self.last_accrual_timestamp = now;
+
+        Some((interest, reserves))
+    }
+
+    /// Protocol's cut of `interest` at the current reserve factor
+    pub fn reserve_share(&self, interest: u64) -> Option<u64> {
+        (interest as u128)
+            .checked_mul(self.reserve_factor_bps as u128)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Flash loan fee on `amount`, rounded up so small loans are not free
+    pub fn flash_loan_fee(&self, amount: u64) -> Option<u64> {
+        (amount as u128)
+            .checked_mul(self.flash_loan_fee_bps as u128)?
+            .checked_add(BASIS_POINTS_DIVISOR as u128 - 1)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Book income received outside loan interest (flash loan fees, strategy yield) as
+    /// interest: reserve cut first, then the tranche waterfall. Returns the reserve cut.
+    pub fn credit_income(&mut self, income: u64) -> Option<u64> {
+        let reserves = self.reserve_share(income)?;
+        let junior_interest = self.junior_interest_share(income - reserves)?;
+
+        self.total_deposits = self.total_deposits.checked_add(income - reserves)?;
+        self.junior_deposits = self.junior_deposits.checked_add(junior_interest)?;
+        self.total_reserves = self.total_reserves.checked_add(reserves)?;
+        self.available_liquidity = self.available_liquidity.checked_add(income)?;
+        self.cumulative_interest = self.cumulative_interest.checked_add(income)?;
+
+        Some(reserves)
+    }
+
+    /// Interest waterfall: junior gets its pro-rata part of `depositor_interest`
+    /// plus junior_premium_bps of the senior part, senior keeps the rest
+    pub fn junior_interest_share(&self, depositor_interest: u64) -> Option<u64> {
+        if self.junior_deposits == 0 || self.total_deposits == 0 {
+            return Some(0);
+        }
+        let pro_rata = (depositor_interest as u128)
+            .checked_mul(self.junior_deposits as u128)?
+            .checked_div(self.total_deposits as u128)?;
+        let premium = (depositor_interest as u128 - pro_rata)
+            .checked_mul(self.junior_premium_bps as u128)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128)?;
+        pro_rata
+            .checked_add(premium)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Write `loss` off depositor value, junior absorbs it first and senior
+    /// only once the junior tranche is exhausted. Returns (junior_loss, senior_loss).
+    pub fn allocate_loss(&mut self, loss: u64) -> Option<(u64, u64)> {
+        let junior_loss = loss.min(self.junior_deposits);
+        let senior_loss = loss - junior_loss;
+
+        self.total_deposits = self.total_deposits.checked_sub(loss)?;
+        self.junior_deposits -= junior_loss;
+
+        Some((junior_loss, senior_loss))
+    }
+
+    /// Split loan `interest` that was paid or written off into (depositors, reserves)
+    /// in the proportions it accrued in, whatever reserve factor applied then,
+    /// and take both parts out of the unpaid totals
+    pub fn settle_unpaid_interest(&mut self, interest: u64) -> (u64, u64) {
+        let unpaid = self.unpaid_interest as u128 + self.unpaid_reserves as u128;
+        if unpaid == 0 {
+            return (interest, 0);
+        }
+        let reserves = ((interest as u128).min(unpaid) * self.unpaid_reserves as u128 / unpaid) as u64;
+
+        self.unpaid_reserves -= reserves;
+        self.release_unpaid_interest(interest - reserves);
+
+        (interest - reserves, reserves)
+    }
+
+    /// Depositors' `interest` was paid or written off, it leaves unpaid_interest
+    /// and junior's part of it leaves in proportion to junior's part of what was unpaid
+    fn release_unpaid_interest(&mut self, interest: u64) {
+        let released = interest.min(self.unpaid_interest);
+        if released == 0 {
+            return;
+        }
+        let junior_released = (released as u128 * self.unpaid_junior_interest as u128
+            / self.unpaid_interest as u128) as u64;
+
+        self.unpaid_interest -= released;
+        self.unpaid_junior_interest = self.unpaid_junior_interest.saturating_sub(junior_released);
+    }
+
+    /// Junior is first-loss capital, it cannot leave while a defaulted loan's loss is unknown
+    pub fn junior_exits_frozen(&self) -> bool {
+        self.pending_defaults > 0
+    }
+
+    /// Record written-off debt for absorb_bad_debt, which covers it from the insurance
+    /// fund and socializes the rest through the share price
+    pub fn record_bad_debt(&mut self, loss: u64) -> Option<()> {
+        self.bad_debt = self.bad_debt.checked_add(loss)?;
+        Some(())
+    }
+
+    /// Spread a pool-funded loan's lender `interest` over all lender stake
+    /// The accumulators attribute pool-funded loans to lenders, the value itself
+    /// moves through the share price
+    pub fn credit_pool_funded_interest(&mut self, interest: u64) -> Option<()> {
+        if self.total_lender_stake == 0 {
+            return Some(());
+        }
+        let per_stake = (interest as u128)
+            .checked_mul(STAKE_ACCUMULATOR_PRECISION)?
+            .checked_div(self.total_lender_stake as u128)?;
+        self.interest_per_stake = self.interest_per_stake.checked_add(per_stake)?;
+        Some(())
+    }
+
+    /// Charge lost pool-funded `principal` to all lender stake pro-rata
+    pub fn charge_pool_funded_loss(&mut self, principal: u64) -> Option<()> {
+        if self.total_lender_stake == 0 {
+            return Some(());
+        }
+        let per_stake = (principal as u128)
+            .checked_mul(STAKE_ACCUMULATOR_PRECISION)?
+            .checked_div(self.total_lender_stake as u128)?;
+        self.loss_per_stake = self.loss_per_stake.checked_add(per_stake)?;
+        Some(())
+    }
+
+    /// A lender's pro-rata part of the outstanding pool-funded principal
+    pub fn pool_funded_exposure(&self, stake: u64) -> Option<u64> {
+        if self.total_lender_stake == 0 {
+            return Some(0);
+        }
+        (self.pool_funded_principal as u128)
+            .checked_mul(stake as u128)?
+            .checked_div(self.total_lender_stake as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Liquidity not earmarked for queued withdrawals
+    pub fn unreserved_liquidity(&self) -> Option<u64> {
+        let queued_amount = self.shares_to_amount(Tranche::Senior, self.queued_withdrawal_shares)?;
+        Some(self.available_liquidity.saturating_sub(queued_amount))
+    }
+
+    /// Exchange rate of one `tranche` share in tokens, scaled by SHARE_PRICE_PRECISION
+    /// Redeemable value grows with every interest payment, so the rate rises
+    /// as borrowers pay interest
+    pub fn exchange_rate(&self, tranche: Tranche) -> u64 {
+        let (value, shares) = self.redeemable_totals(tranche);
+        if shares == 0 {
+            return SHARE_PRICE_PRECISION;
+        }
+        ((value as u128 * SHARE_PRICE_PRECISION as u128)
+            / shares as u128) as u64
+    }
+}
+
+/// Pool limits and caps set by the risk manager, so mints with any decimals and policy can be served
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PoolConfig{
+    // deposits must be larger than this
+    pub min_deposit_amount : u64,
+
+    pub min_withdrawal_amount : u64,
+    // a withdrawal may leave either nothing or at least this much
+    pub min_remaining_balance : u64,
+    // max total_deposits, 0 = uncapped
+    pub supply_cap : u64,
+    // max principal a single lender can have in the pool, 0 = uncapped
+    pub lender_cap : u64,
+}
+
+impl PoolConfig {
+    pub const LEN: usize = 8 + // min_deposit_amount
+    8 + // min_withdrawal_amount
+    8 + // min_remaining_balance
+    8 + // supply_cap
+    8; // lender_cap
+
+    /// Default limits rescaled from DEFAULT_CONFIG_DECIMALS to a mint with `decimals`
+    pub fn default_for_decimals(decimals: u8) -> Option<Self> {
+        let rescale = |amount: u64| -> Option<u64> {
+            if decimals >= DEFAULT_CONFIG_DECIMALS {
+                amount.checked_mul(10u64.checked_pow((decimals - DEFAULT_CONFIG_DECIMALS) as u32)?)
+            } else {
+                amount.checked_div(10u64.checked_pow((DEFAULT_CONFIG_DECIMALS - decimals) as u32)?)
+            }
+        };
+        Some(Self {
+            min_deposit_amount: rescale(MIN_DEPOSIT_AMOUNT)?,
+            min_withdrawal_amount: rescale(MIN_WITHDRAWAL_AMOUNT)?.max(1),
+            min_remaining_balance: rescale(MIN_REMAINING_BALANCE)?,
+            supply_cap: 0,
+            lender_cap: 0,
+        })
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.min_deposit_amount > 0
+            && self.min_withdrawal_amount > 0
+            && self.min_remaining_balance <= self.min_deposit_amount
+            && (self.supply_cap == 0 || self.supply_cap > self.min_deposit_amount)
+            && (self.lender_cap == 0 || self.lender_cap > self.min_deposit_amount)
+            && (self.supply_cap == 0 || self.lender_cap <= self.supply_cap)
+    }
+
+    /// Whether total deposits of `total_deposits` stay within the supply cap
+    pub fn within_supply_cap(&self, total_deposits: u64) -> bool {
+        self.supply_cap == 0 || total_deposits <= self.supply_cap
+    }
+
+    /// Whether a lender principal of `position` stays within the per-lender cap
+    pub fn within_lender_cap(&self, position: u64) -> bool {
+        self.lender_cap == 0 || position <= self.lender_cap
+    }
+}
+
+/// Pool operations that can be paused independently, true = paused
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PauseFlags{
+    pub deposits : bool,
+
+    pub withdrawals : bool,
+
+    pub loan_requests : bool,
+
+    pub disbursements : bool,
+    // borrowers must nearly always be able to pay down debt, only an admin can pause this
+    pub repayments : bool,
+
+    pub liquidations : bool,
+    // flash loans pause on their own, pausing disbursements leaves them running
+    pub flash_loans : bool,
+}
+
+impl PauseFlags {
+    pub const LEN: usize = 7;
+
+    pub fn any(&self) -> bool {
+        self.deposits
+            || self.withdrawals
+            || self.loan_requests
+            || self.disbursements
+            || self.repayments
+            || self.liquidations
+            || self.flash_loans
+    }
+
+    pub fn intersects(&self, other: &PauseFlags) -> bool {
+        (self.deposits && other.deposits)
+            || (self.withdrawals && other.withdrawals)
+            || (self.loan_requests && other.loan_requests)
+            || (self.disbursements && other.disbursements)
+            || (self.repayments && other.repayments)
+            || (self.liquidations && other.liquidations)
+            || (self.flash_loans && other.flash_loans)
+    }
+
+    /// Copy of these flags with every operation selected in `selected` set to `paused`
+    pub fn with(&self, selected: &PauseFlags, paused: bool) -> PauseFlags {
+        let set = |current: bool, select: bool| if select { paused } else { current };
+        PauseFlags {
+            deposits: set(self.deposits, selected.deposits),
+            withdrawals: set(self.withdrawals, selected.withdrawals),
+            loan_requests: set(self.loan_requests, selected.loan_requests),
+            disbursements: set(self.disbursements, selected.disbursements),
+            repayments: set(self.repayments, selected.repayments),
+            liquidations: set(self.liquidations, selected.liquidations),
+            flash_loans: set(self.flash_loans, selected.flash_loans),
+        }
+    }
+}
+
+/// Attestations a pool requires, issued by a single trusted issuer
+///
+/// Only deposits and loan requests are gated: share tokens are plain SPL tokens and
+/// stay freely transferable, so an unattested wallet can receive shares and redeem them.
+/// A transfer also moves the shares out from under the sender's lent_amount, which is
+/// only checked against the lender's own share account. Pools that need closed share
+/// ownership have to restrict the share mints themselves (e.g. a Token-2022 transfer hook)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct AccessPolicy{
+    pub issuer : Pubkey,
+    // credential type required to deposit, NO_CREDENTIAL = open
+    pub lender_credential : u16,
+    // credential type required to request a loan, NO_CREDENTIAL = open
+    pub borrower_credential : u16,
+}
+
+impl AccessPolicy {
+    pub const LEN: usize = 32 + // issuer
+    2 + // lender_credential
+    2; // borrower_credential
+
+    pub fn requires_lender_attestation(&self) -> bool {
+        self.lender_credential != NO_CREDENTIAL
+    }
+
+    pub fn requires_borrower_attestation(&self) -> bool {
+        self.borrower_credential != NO_CREDENTIAL
+    }
+
+    pub fn is_valid(&self) -> bool {
+        // a gated pool needs an issuer to trust
+        (!self.requires_lender_attestation() && !self.requires_borrower_attestation())
+            || self.issuer != Pubkey::default()
+    }
+}
+
+/// Pool tranches: junior absorbs losses first in exchange for a cut of senior interest
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tranche {
+    Senior,
+    Junior,
+}
+
+impl Tranche {
+    pub fn other(self) -> Tranche {
+        match self {
+            Tranche::Senior => Tranche::Junior,
+            Tranche::Junior => Tranche::Senior,
+        }
+    }
 }
 
 #[account]
 pub struct LenderAccount{
+    // layout version
+    pub version : u8,
+
     // user 
     pub lender : Pubkey,
     //  pool address 
@@ -75,18 +628,30 @@ pub struct LenderAccount{
     // Total amount deposited by the lender and (and dont decrease on withdrawal)
     pub deposited_amount : u64,
 
-    // amount that is currently available to withdraw 
-    pub  available_amount : u64,
-
-    /// Amount currently lent out to active loans
-    /// When loan repaid, this decreases and available_amount increases
+    /// Amount currently lent out to active loans, backed by the lender's senior shares
+    /// The balance itself is the share balance, this only marks the part that can't be withdrawn
     pub lent_amount : u64,
 
-    pub interest_earned : u64,
-
     pub total_withdrawals : u64,
 
     pub last_deposited_time : i64,
+    // promised to the lender's standing offers in loan-manager's offer book, locked like lent funds
+    pub reserved_amount : u64,
+    // average exchange rate (SHARE_PRICE_PRECISION) the lender's senior shares were bought at,
+    // redeemable value above it is interest the lender can claim or compound
+    pub senior_rate_checkpoint : u64,
+    // same for the junior shares
+    pub junior_rate_checkpoint : u64,
+    // net deposits the pool-funded accumulators weight this account by, as of the last settle
+    pub stake : u64,
+    // pool's interest_per_stake / loss_per_stake when this account was last settled
+    pub interest_checkpoint : u128,
+
+    pub loss_checkpoint : u128,
+    // this lender's part of pool-funded loan interest and write-offs settled so far
+    pub pool_funded_interest : u64,
+
+    pub pool_funded_loss : u64,
 
     pub bump : u8
 
@@ -95,35 +660,720 @@ pub struct LenderAccount{
 
 impl LenderAccount {
     pub const LEN: usize = 8 + // discriminator
+    1 + // version
     32 + // lender
     32 + // pool
     8 + // deposited_amount
-    8 + // available_amount
     8 + // lent_amount
-    8 + // interest_earned
     8 + // total_withdrawals
     8 + // last_deposit_time
+    8 + // reserved_amount
+    8 + // senior_rate_checkpoint
+    8 + // junior_rate_checkpoint
+    8 + // stake
+    16 + // interest_checkpoint
+    16 + // loss_checkpoint
+    8 + // pool_funded_interest
+    8 + // pool_funded_loss
     1; 
 
-    pub fn total_balance(&self)->u64{
-        self.available_amount + self.lent_amount
+    /// Initialize a freshly created (init_if_needed) lender account
+    pub fn initialize_if_new(&mut self, lender: Pubkey, pool: Pubkey, bump: u8) {
+        if self.lender == Pubkey::default() {
+            self.version = ACCOUNT_VERSION;
+            self.lender = lender;
+            self.pool = pool;
+            self.deposited_amount = 0;
+            self.lent_amount = 0;
+            self.total_withdrawals = 0;
+            self.last_deposited_time = 0;
+            self.reserved_amount = 0;
+            self.senior_rate_checkpoint = 0;
+            self.junior_rate_checkpoint = 0;
+            self.stake = 0;
+            self.interest_checkpoint = 0;
+            self.loss_checkpoint = 0;
+            self.pool_funded_interest = 0;
+            self.pool_funded_loss = 0;
+            self.bump = bump;
+        }
+    }
+
+    /// Nothing lent out or reserved, the account can be closed (shares stay in the lender's token accounts)
+    pub fn is_empty(&self) -> bool {
+        self.committed_amount() == 0
+    }
+
+    /// Lent out plus reserved for offers, the senior value backing it cannot be withdrawn
+    pub fn committed_amount(&self) -> u64 {
+        self.lent_amount.saturating_add(self.reserved_amount)
+    }
+
+    /// Deposits not withdrawn yet
+    pub fn net_deposits(&self) -> u64 {
+        self.deposited_amount.saturating_sub(self.total_withdrawals)
+    }
+
+    /// Part of `senior_value` (what the lender's senior shares are worth) that isn't lent out or reserved
+    pub fn lendable_amount(&self, senior_value: u64) -> u64 {
+        senior_value.saturating_sub(self.committed_amount())
     }
 
-    pub fn calculate_roi(&self) -> u64 {
+    /// Earned so far: current share value plus withdrawals over what was deposited
+    pub fn interest_earned(&self, share_value: u64) -> u64 {
+        share_value.saturating_add(self.total_withdrawals).saturating_sub(self.deposited_amount)
+    }
+
+    pub fn rate_checkpoint(&self, tranche: Tranche) -> u64 {
+        match tranche {
+            Tranche::Senior => self.senior_rate_checkpoint,
+            Tranche::Junior => self.junior_rate_checkpoint,
+        }
+    }
+
+    fn set_rate_checkpoint(&mut self, tranche: Tranche, rate: u64) {
+        match tranche {
+            Tranche::Senior => self.senior_rate_checkpoint = rate,
+            Tranche::Junior => self.junior_rate_checkpoint = rate,
+        }
+    }
+
+    /// Interest in `shares` of `tranche` at `exchange_rate`: what they redeem for above the rate
+    /// they were bought at. Shares received by transfer count at this account's checkpoint,
+    /// without one (migrated accounts, transfers only) nothing is claimable
+    pub fn claimable_interest(&self, tranche: Tranche, shares: u64, exchange_rate: u64) -> Option<u64> {
+        let checkpoint = self.rate_checkpoint(tranche);
+        if checkpoint == 0 {
+            return Some(0);
+        }
+        let gain = exchange_rate.saturating_sub(checkpoint);
+        (shares as u128)
+            .checked_mul(gain as u128)?
+            .checked_div(SHARE_PRICE_PRECISION as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// `minted` shares of `tranche` were bought for `amount` on top of `held` ones,
+    /// the checkpoint moves to the average rate paid. Held shares without a checkpoint
+    /// count at the price paid now
+    pub fn record_shares_bought(&mut self, tranche: Tranche, held: u64, minted: u64, amount: u64) -> Option<()> {
+        let held_rate = match self.rate_checkpoint(tranche) {
+            0 => (amount as u128)
+                .checked_mul(SHARE_PRICE_PRECISION as u128)?
+                .checked_div(minted as u128)?,
+            checkpoint => checkpoint as u128,
+        };
+        let cost = (held as u128)
+            .checked_mul(held_rate)?
+            .checked_add((amount as u128).checked_mul(SHARE_PRICE_PRECISION as u128)?)?;
+        let rate = cost.checked_div((held as u128).checked_add(minted as u128)?)?;
+
+        self.set_rate_checkpoint(tranche, u64::try_from(rate).ok()?);
+        Some(())
+    }
+
+    /// Interest was taken out of `tranche`, the `remaining` shares keep `remaining_interest`
+    /// of it at `exchange_rate`
+    pub fn record_interest_taken(&mut self, tranche: Tranche, remaining: u64, remaining_interest: u64, exchange_rate: u64) -> Option<()> {
+        let rate = if remaining == 0 {
+            0
+        } else {
+            let unclaimed = (remaining_interest as u128)
+                .checked_mul(SHARE_PRICE_PRECISION as u128)?
+                .checked_div(remaining as u128)?;
+            (exchange_rate as u128).saturating_sub(unclaimed) as u64
+        };
+
+        self.set_rate_checkpoint(tranche, rate);
+        Some(())
+    }
+
+    /// Book this lender's share of pool-funded interest and losses since the last
+    /// checkpoint on the old stake, then weight it by `new_stake`. Returns (interest, loss)
+    pub fn settle_pool_funded(&mut self, pool: &LendingPool, new_stake: u64) -> Option<(u64, u64)> {
+        let stake = self.stake as u128;
+
+        let interest = stake
+            .checked_mul(pool.interest_per_stake.checked_sub(self.interest_checkpoint)?)?
+            .checked_div(STAKE_ACCUMULATOR_PRECISION)
+            .and_then(|v| u64::try_from(v).ok())?;
+
+        let loss = stake
+            .checked_mul(pool.loss_per_stake.checked_sub(self.loss_checkpoint)?)?
+            .checked_div(STAKE_ACCUMULATOR_PRECISION)
+            .and_then(|v| u64::try_from(v).ok())?;
+
+        self.pool_funded_interest = self.pool_funded_interest.checked_add(interest)?;
+        self.pool_funded_loss = self.pool_funded_loss.checked_add(loss)?;
+        self.interest_checkpoint = pool.interest_per_stake;
+        self.loss_checkpoint = pool.loss_per_stake;
+        self.stake = new_stake;
+
+        Some((interest, loss))
+    }
+
+    pub fn calculate_roi(&self, share_value: u64) -> u64 {
         if self.deposited_amount == 0 {
             return 0;
         }
-        (self.interest_earned * 100) / self.deposited_amount
+        (self.interest_earned(share_value) as u128 * 100 / self.deposited_amount as u128) as u64
     }
 }
 
+/// Pool snapshot returned by get_pool_stats, interest projected to the current time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolStats{
+    pub total_deposits : u64,
+    pub senior_deposits : u64,
+    pub junior_deposits : u64,
+    pub available_liquidity : u64,
+    // available liquidity not earmarked for queued withdrawals
+    pub unreserved_liquidity : u64,
+    pub total_borrowed : u64,
+    pub cumulative_interest : u64,
+    // accrued interest borrowers haven't paid yet, not redeemable until they do
+    pub unpaid_interest : u64,
+    pub total_reserves : u64,
+    // reserves accrued on loans not repaid yet, not collectable
+    pub unpaid_reserves : u64,
+    pub pool_funded_principal : u64,
+    pub bad_debt : u64,
+    // percentage (0-100)
+    pub utilization : u64,
+    // basis points at the current utilization
+    pub borrow_rate_bps : u16,
+    // cumulative interest over deposits, basis points
+    pub apy_bps : u64,
+    // tokens per share, scaled by SHARE_PRICE_PRECISION
+    pub senior_exchange_rate : u64,
+    pub junior_exchange_rate : u64,
+    pub active_loans : u32,
+    pub total_loans : u32,
+}
+
+/// Lender snapshot returned by get_lender_position, shares valued at the current time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LenderPosition{
+    pub lender : Pubkey,
+    pub pool : Pubkey,
+    pub deposited_amount : u64,
+    pub lent_amount : u64,
+    // senior plus junior share value
+    pub total_balance : u64,
+    // senior value not lent out
+    pub lendable_amount : u64,
+    pub interest_earned : u64,
+    // interest in the passed share accounts the lender can claim or compound, see claim_interest
+    pub claimable_interest : u64,
+    // pool-funded loan interest and write-offs attributed to the lender, settled up to now
+    pub pool_funded_interest : u64,
+    pub pool_funded_loss : u64,
+    // pro-rata part of the outstanding pool-funded principal
+    pub pool_funded_exposure : u64,
+    // percentage of deposits earned as interest
+    pub roi : u64,
+    // share balances and their value in tokens, zero for share accounts not passed
+    pub senior_shares : u64,
+    pub senior_value : u64,
+    pub junior_shares : u64,
+    pub junior_value : u64,
+}
+
+
+#[account]
+pub struct InterestRateModel{
+    // layout version
+    pub version : u8,
+
+    // pool this model prices loans for
+    pub pool : Pubkey,
+    // borrow rate at 0% utilization (basis points)
+    pub base_rate_bps : u16,
+    // rate added between 0% and the kink (basis points)
+    pub slope1_bps : u16,
+    // utilization (percentage) where the steep slope kicks in
+    pub kink_utilization : u64,
+    // rate added between the kink and 100% utilization (basis points)
+    pub slope2_bps : u16,
+    // no new loans once utilization reaches this (percentage)
+    pub max_utilization : u64,
+
+    pub updated_at : i64,
+
+    pub bump : u8
+}
+
+impl InterestRateModel {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // pool
+    2 + // base_rate_bps
+    2 + // slope1_bps
+    8 + // kink_utilization
+    2 + // slope2_bps
+    8 + // max_utilization
+    8 + // updated_at
+    1; // bump
+
+    /// Borrow rate (basis points) for a utilization percentage
+    /// Linear up to the kink, then slope2 on top of base + slope1
+    pub fn borrow_rate(&self, utilization: u64) -> u16 {
+        let utilization = utilization.min(100);
+        let rate = if utilization <= self.kink_utilization {
+            self.base_rate_bps as u64
+                + (self.slope1_bps as u64 * utilization)
+                    .checked_div(self.kink_utilization)
+                    .unwrap_or(0)
+        } else {
+            let excess = utilization - self.kink_utilization;
+            let range = 100 - self.kink_utilization;
+            self.base_rate_bps as u64
+                + self.slope1_bps as u64
+                + (self.slope2_bps as u64 * excess) / range
+        };
+        rate.min(u16::MAX as u64) as u16
+    }
+
+    pub fn is_utilization_allowed(&self, utilization: u64) -> bool {
+        utilization < self.max_utilization
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WithdrawalRequest{
+    pub request_id : u64,
+    pub lender : Pubkey,
+    // token account the withdrawal is paid to when filled
+    pub destination : Pubkey,
+    // shares still waiting to be redeemed (escrowed by the pool)
+    pub shares : u64,
+    pub requested_at : i64,
+}
+
+impl WithdrawalRequest {
+    pub const LEN: usize = 8 + // request_id
+    32 + // lender
+    32 + // destination
+    8 + // shares
+    8; // requested_at
+}
+
+/// Per-pool first-loss buffer, pays written-off debt before depositors take a loss
+#[account]
+pub struct InsuranceFund{
+    // layout version
+    pub version : u8,
+
+    pub pool : Pubkey,
+    // pool-owned token account holding the fund
+    pub vault : Pubkey,
+    // paid in by stakers and other contributors
+    pub total_contributed : u64,
+    // moved over from protocol reserves
+    pub total_from_reserves : u64,
+    // bad debt paid out of the fund so far
+    pub total_covered : u64,
+
+    pub created_at : i64,
+
+    pub bump : u8
+}
+
+impl InsuranceFund {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // pool
+    32 + // vault
+    8 + // total_contributed
+    8 + // total_from_reserves
+    8 + // total_covered
+    8 + // created_at
+    1; // bump
+}
+
+/// Strategy the pool parks part of its idle liquidity in, through the strategy interface
+/// (deposit, withdraw, report_balance) of `strategy_program`
+#[account]
+pub struct YieldStrategy{
+    // layout version
+    pub version : u8,
+
+    pub pool : Pubkey,
+    // program implementing the strategy interface
+    pub strategy_program : Pubkey,
+    // the strategy's state for this pool, passed to every strategy call
+    pub strategy_state : Pubkey,
+    // token account the strategy holds the pool's funds in
+    pub strategy_token_account : Pubkey,
+    // cap on deployed funds, share of available_liquidity (basis points)
+    pub max_allocation_bps : u16,
+    // principal currently in the strategy, anything above it is yield
+    pub deployed : u64,
+    // yield moved back into the pool as interest
+    pub total_harvested : u64,
+
+    pub last_harvest_at : i64,
+
+    pub created_at : i64,
+
+    pub bump : u8
+}
+
+impl YieldStrategy {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // pool
+    32 + // strategy_program
+    32 + // strategy_state
+    32 + // strategy_token_account
+    2 + // max_allocation_bps
+    8 + // deployed
+    8 + // total_harvested
+    8 + // last_harvest_at
+    8 + // created_at
+    1; // bump
+
+    /// Most that may be deployed while the pool holds `available_liquidity`
+    pub fn allocation_cap(&self, available_liquidity: u64) -> Option<u64> {
+        (available_liquidity as u128)
+            .checked_mul(self.max_allocation_bps as u128)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+}
+
+/// FIFO queue of withdrawals waiting for liquidity to return from loans (senior shares)
+#[account]
+pub struct WithdrawalQueue{
+    // layout version
+    pub version : u8,
+
+    pub pool : Pubkey,
+
+    pub next_request_id : u64,
+
+    pub requests : Vec<WithdrawalRequest>,
+
+    pub bump : u8
+}
+
+impl WithdrawalQueue {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // pool
+    8 + // next_request_id
+    (4 + WithdrawalRequest::LEN * MAX_WITHDRAWAL_QUEUE_LENGTH) + // requests
+    1; // bump
+
+    pub fn is_full(&self) -> bool {
+        self.requests.len() >= MAX_WITHDRAWAL_QUEUE_LENGTH
+    }
+
+    /// Pending requests of `lender`
+    pub fn requests_of(&self, lender: &Pubkey) -> usize {
+        self.requests.iter().filter(|r| r.lender == *lender).count()
+    }
+}
+
+/// Fixed-term deposit: senior pool shares escrowed until maturity for boosted yield
+#[account]
+pub struct LockPosition{
+    // layout version
+    pub version : u8,
+
+    pub lender : Pubkey,
+
+    pub pool : Pubkey,
+    // client chosen id, lets a lender hold several positions
+    pub position_id : u64,
+    // shares held in the lock escrow
+    pub shares : u64,
+    // token value of the shares when locked, interest is measured from here
+    pub value_at_lock : u64,
+
+    pub lock_days : u16,
+    // multiplier on the interest earned while locked (basis points)
+    pub multiplier_bps : u16,
+
+    pub locked_at : i64,
+
+    pub unlocks_at : i64,
+
+    pub bump : u8
+}
+
+impl LockPosition {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // lender
+    32 + // pool
+    8 + // position_id
+    8 + // shares
+    8 + // value_at_lock
+    2 + // lock_days
+    2 + // multiplier_bps
+    8 + // locked_at
+    8 + // unlocks_at
+    1; // bump
+
+    /// Yield multiplier for a supported lock term
+    pub fn multiplier_for_term(lock_days: u16) -> Option<u16> {
+        match lock_days {
+            LOCK_TERM_SHORT_DAYS => Some(LOCK_MULTIPLIER_SHORT_BPS),
+            LOCK_TERM_MEDIUM_DAYS => Some(LOCK_MULTIPLIER_MEDIUM_BPS),
+            LOCK_TERM_LONG_DAYS => Some(LOCK_MULTIPLIER_LONG_BPS),
+            _ => None,
+        }
+    }
+
+    pub fn is_matured(&self, now: i64) -> bool {
+        now >= self.unlocks_at
+    }
+
+    /// Extra tokens owed at maturity: interest earned up to unlocks_at times (multiplier - 1)
+    /// Interest after maturity is prorated out, the position's value there isn't recorded
+    pub fn bonus_amount(&self, current_value: u64, now: i64) -> Option<u64> {
+        let mut interest = current_value.saturating_sub(self.value_at_lock);
+        if now > self.unlocks_at {
+            let term = self.unlocks_at.checked_sub(self.locked_at)?;
+            let held = now.checked_sub(self.locked_at)?;
+            interest = u64::try_from((interest as u128).checked_mul(term as u128)? / held as u128).ok()?;
+        }
+        let boost_bps = self.multiplier_bps.saturating_sub(BASIS_POINTS_DIVISOR as u16);
+        (interest as u128)
+            .checked_mul(boost_bps as u128)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// Shares forfeited to the pool when unlocking before maturity
+    pub fn early_unlock_penalty(&self) -> Option<u64> {
+        (self.shares as u128)
+            .checked_mul(EARLY_UNLOCK_PENALTY_BPS as u128)?
+            .checked_div(BASIS_POINTS_DIVISOR as u128)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarketStatus {
+    Listed,
+    Delisted,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MarketEntry{
+    pub pool : Pubkey,
+
+    pub token_mint : Pubkey,
+
+    pub decimals : u8,
+    // only Listed markets can originate loans
+    pub status : MarketStatus,
+    // largest principal loan-manager accepts against this pool
+    pub max_loan_amount : u64,
+    // lowest credit score loan-manager accepts against this pool
+    pub min_credit_score : u16,
+
+    pub listed_at : i64,
+
+    pub updated_at : i64,
+}
+
+impl MarketEntry {
+    pub const LEN: usize = 32 + // pool
+    32 + // token_mint
+    1 + // decimals
+    1 + // status
+    8 + // max_loan_amount
+    2 + // min_credit_score
+    8 + // listed_at
+    8; // updated_at
+}
+
+/// Every pool known to the protocol, loan-manager only lends against listed ones
+#[account]
+pub struct PoolRegistry{
+    // layout version
+    pub version : u8,
+
+    // account that created the registry, listings are gated by the RiskManager role
+    pub authority : Pubkey,
+
+    pub markets : Vec<MarketEntry>,
+
+    pub bump : u8
+}
+
+impl PoolRegistry {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // authority
+    (4 + MarketEntry::LEN * MAX_REGISTERED_MARKETS) + // markets
+    1; // bump
+
+    pub fn find(&self, pool: &Pubkey) -> Option<&MarketEntry> {
+        self.markets.iter().find(|m| m.pool == *pool)
+    }
+
+    pub fn find_mut(&mut self, pool: &Pubkey) -> Option<&mut MarketEntry> {
+        self.markets.iter_mut().find(|m| m.pool == *pool)
+    }
+
+    pub fn is_listed(&self, pool: &Pubkey) -> bool {
+        self.find(pool).is_some_and(|m| m.status == MarketStatus::Listed)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.markets.len() >= MAX_REGISTERED_MARKETS
+    }
+}
+
+/// Credential issued by an issuer (e.g. a KYC provider) to a wallet, one per issuer, subject and type
+#[account]
+pub struct Attestation{
+    // layout version
+    pub version : u8,
+
+    pub issuer : Pubkey,
+
+    pub subject : Pubkey,
+
+    pub credential_type : u16,
+
+    pub issued_at : i64,
+    // 0 = never expires
+    pub expires_at : i64,
+
+    pub revoked : bool,
+
+    pub revoked_at : i64,
+
+    pub bump : u8
+}
+
+impl Attestation {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // issuer
+    32 + // subject
+    2 + // credential_type
+    8 + // issued_at
+    8 + // expires_at
+    1 + // revoked
+    8 + // revoked_at
+    1; // bump
+
+    pub fn is_valid(&self, now: i64) -> bool {
+        !self.revoked && (self.expires_at == 0 || now < self.expires_at)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    // grants and revokes roles
+    Admin,
+    // pauses and unpauses pools
+    Pauser,
+    // rate models, pool limits, tranche premium, market listings and loan write-offs
+    RiskManager,
+    // loan health factor updates and collateral release
+    HealthMonitor,
+    // collateral liquidation
+    LiquidatorKeeper,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RoleGrant{
+    pub role : Role,
+
+    pub member : Pubkey,
+
+    pub granted_at : i64,
+}
+
+impl RoleGrant {
+    pub const LEN: usize = 1 + // role
+    32 + // member
+    8; // granted_at
+}
+
+/// Protocol-wide roles, privileged instructions in every program check their role here
+#[account]
+pub struct ProtocolRoles{
+    // layout version
+    pub version : u8,
+
+    pub grants : Vec<RoleGrant>,
+
+    pub bump : u8
+}
+
+impl ProtocolRoles {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    (4 + RoleGrant::LEN * MAX_ROLE_GRANTS) + // grants
+    1; // bump
+
+    pub fn has_role(&self, role: Role, member: &Pubkey) -> bool {
+        self.grants.iter().any(|g| g.role == role && g.member == *member)
+    }
+
+    pub fn role_count(&self, role: Role) -> usize {
+        self.grants.iter().filter(|g| g.role == role).count()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.grants.len() >= MAX_ROLE_GRANTS
+    }
+}
 
 // seeds for PDA Derivation 
 
 impl LendingPool {
     pub const SEED_PREFIX: &'static [u8] = b"lending_pool";
+    pub const SHARE_MINT_SEED: &'static [u8] = b"share_mint";
+    pub const JUNIOR_SHARE_MINT_SEED: &'static [u8] = b"junior_share_mint";
 }
 
 impl LenderAccount {
     pub const SEED_PREFIX: &'static [u8] = b"lender_account";
+}
+
+impl InterestRateModel {
+    pub const SEED_PREFIX: &'static [u8] = b"interest_rate_model";
+}
+
+impl LockPosition {
+    pub const SEED_PREFIX: &'static [u8] = b"lock_position";
+    // pool-owned share account holding all locked shares
+    pub const ESCROW_SEED: &'static [u8] = b"lock_escrow";
+}
+
+impl PoolRegistry {
+    pub const SEED_PREFIX: &'static [u8] = b"pool_registry";
+}
+
+impl ProtocolRoles {
+    pub const SEED_PREFIX: &'static [u8] = b"protocol_roles";
+}
+
+impl Attestation {
+    pub const SEED_PREFIX: &'static [u8] = b"attestation";
+}
+
+impl InsuranceFund {
+    pub const SEED_PREFIX: &'static [u8] = b"insurance_fund";
+    // pool-owned token account holding the fund
+    pub const VAULT_SEED: &'static [u8] = b"insurance_vault";
+}
+
+impl YieldStrategy {
+    pub const SEED_PREFIX: &'static [u8] = b"yield_strategy";
+}
+
+impl WithdrawalQueue {
+    pub const SEED_PREFIX: &'static [u8] = b"withdrawal_queue";
+    // pool-owned share account holding shares of queued requests
+    pub const ESCROW_SEED: &'static [u8] = b"withdrawal_escrow";
 }
\ No newline at end of file
@@ -2,6 +2,8 @@
 // Location: programs/loan-manager/src/constants.rs
 // Purpose: Define all constant values
 
+use anchor_lang::prelude::*;
+
 /// Minimum loan amount (100 USDC with 6 decimals)
 pub const MIN_LOAN_AMOUNT: u64 = 100_000_000;
 
@@ -14,9 +16,6 @@ pub const MIN_LOAN_DURATION: i64 = 24 * 60 * 60;
 /// Maximum loan duration (365 days in seconds)
 pub const MAX_LOAN_DURATION: i64 = 365 * 24 * 60 * 60;
 
-/// Default interest rate (8% APR in basis points)
-pub const DEFAULT_INTEREST_RATE: u16 = 800;
-
 /// Maximum credit proof age (12 hours in seconds)
 /// Edge Case #5: Prevents stale credit proofs
 pub const MAX_CREDIT_PROOF_AGE: i64 = 12 * 60 * 60;
@@ -54,4 +53,17 @@ pub const HEALTH_FACTOR_LIQUIDATION: u16 = 90;
 pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
 
 /// Basis points divisor
-pub const BASIS_POINTS_DIVISOR: u64 = 10000;
\ No newline at end of file
+pub const BASIS_POINTS_DIVISOR: u64 = 10000;
+
+/// lending-pool program, owns the pool registry PDA
+pub const LENDING_POOL_PROGRAM_ID: Pubkey = pubkey!("8EJ5aeJVwQuZjftHywYjA1KUVGjaKwLYwUTJE8iLQbhr");
+
+/// Maximum standing offers in a pool's offer book
+pub const MAX_LOAN_OFFERS: usize = 32;
+
+/// Standing offers one lender can hold in a pool's book, so a few lenders cannot fill it
+pub const MAX_OFFERS_PER_LENDER: usize = 4;
+
+/// Layout version written into every account this program creates,
+/// bumped whenever an account layout changes (see the migrate_* instructions)
+pub const ACCOUNT_VERSION: u8 = 1;
@@ -68,4 +68,85 @@ pub enum LoanManagerError {
     
     #[msg("Merkle proof verification failed")]
     InvalidMerkleProof,
+
+    #[msg("Pool utilization is at its maximum. Wait for loan repayments.")]
+    UtilizationTooHigh,
+
+    #[msg("Interest rate model does not belong to this pool")]
+    InvalidInterestRateModel,
+
+    #[msg("Loan has already been written off")]
+    LoanAlreadyWrittenOff,
+
+    #[msg("Token mint does not match the pool or loan")]
+    InvalidTokenMint,
+
+    #[msg("Pool is not listed in the pool registry")]
+    PoolNotListed,
+
+    #[msg("Signer does not hold the role required for this action")]
+    MissingRole,
+
+    #[msg("This operation is currently paused on the pool")]
+    PoolOperationPaused,
+
+    #[msg("Pool requires an attestation for this action")]
+    AttestationRequired,
+
+    #[msg("Attestation is expired or revoked")]
+    AttestationInvalid,
+
+    #[msg("A flash loan is open on this pool")]
+    FlashLoanActive,
+
+    #[msg("Signer is neither the lender nor its operator")]
+    UnauthorizedOperator,
+
+    #[msg("Operator delegation has expired")]
+    OperatorExpired,
+
+    #[msg("Loan exceeds the operator's per-loan limit")]
+    OperatorLoanLimitExceeded,
+
+    #[msg("Loan exceeds the operator's total exposure limit")]
+    OperatorExposureExceeded,
+
+    #[msg("Invalid operator limits")]
+    InvalidOperatorLimits,
+
+    #[msg("Offer book is full")]
+    OfferBookFull,
+
+    #[msg("Offer not found")]
+    OfferNotFound,
+
+    #[msg("Invalid offer terms")]
+    InvalidOfferTerms,
+
+    #[msg("No offer in the book matches this loan")]
+    NoMatchingOffer,
+
+    #[msg("Lender account does not belong to the best matching offer")]
+    OfferLenderMismatch,
+
+    #[msg("Lender account is required for lender-funded loans")]
+    LenderAccountRequired,
+
+    #[msg("Account is not a migratable account of this type")]
+    InvalidAccountLayout,
+
+    #[msg("Account already uses the current layout version")]
+    AccountAlreadyMigrated,
+
+    #[msg("Recovered amount must be positive and at most the loan's debt")]
+    InvalidRecoveryAmount,
+
+    #[msg("Loan was approved by an operator, its lender_operator account is required")]
+    LenderOperatorRequired,
+
+    #[msg("Lender already holds the maximum number of offers in this book")]
+    TooManyOffers,
+
+    #[msg("Pool has written-off debt waiting for absorb_bad_debt")]
+    BadDebtPending,
 }
\ No newline at end of file
@@ -22,6 +22,8 @@ pub struct  LoanApproved{
     pub lender  : Pubkey,
     pub pool : Pubkey,
     pub amount : u64,
+    // lender or their operator
+    pub approved_by : Pubkey,
     pub timestamp : i64
 }
 #[event]
@@ -92,4 +94,99 @@ pub struct LoanDefaulted {
     pub principal : u64,
     pub collateral_seized : u64,
     pub timestamp : i64
-}
\ No newline at end of file
+}
+
+/// Defaulted debt realized as a loss, recorded as pool bad debt until lending-pool absorbs it
+#[event]
+pub struct LoanWrittenOff {
+    pub loan_id : u64,
+    pub pool : Pubkey,
+    pub lender : Pubkey,
+    pub loss : u64,
+    // pool's unabsorbed bad debt including this loss
+    pub pending_bad_debt : u64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct LenderOperatorSet {
+    pub lender : Pubkey,
+    pub lender_account : Pubkey,
+    pub operator : Pubkey,
+    pub max_principal_per_loan : u64,
+    pub max_total_exposure : u64,
+    pub min_credit_score : u16,
+    pub expires_at : i64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct LenderOperatorRevoked {
+    pub lender : Pubkey,
+    pub lender_account : Pubkey,
+    pub operator : Pubkey,
+    pub approved_principal : u64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct OfferPosted {
+    pub pool : Pubkey,
+    pub offer_id : u64,
+    pub lender : Pubkey,
+    pub max_amount : u64,
+    pub total_amount : u64,
+    pub min_credit_score : u16,
+    pub max_duration : i64,
+    pub min_rate_bps : u16,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct OfferCancelled {
+    pub pool : Pubkey,
+    pub offer_id : u64,
+    pub lender : Pubkey,
+    pub remaining_amount : u64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct CollateralRecovered {
+    pub loan_id : u64,
+    pub pool : Pubkey,
+    // tokens the sold collateral brought back to the pool
+    pub recovered : u64,
+    pub collateral_seized : u64,
+    // debt left for write_off_loan, 0 when the recovery covered it
+    pub remaining_debt : u64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct LoanMatched {
+    pub loan_id : u64,
+    pub pool : Pubkey,
+    pub offer_id : u64,
+    pub lender : Pubkey,
+    pub interest_rate : u16,
+    pub offer_remaining_amount : u64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct LoanPoolFunded {
+    pub loan_id : u64,
+    pub pool : Pubkey,
+    pub approved_by : Pubkey,
+    pub max_principal : u64,
+    pub timestamp : i64
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
@@ -1,6 +1,6 @@
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use solana_program::clock;
 
 use crate::{errors::LoanManagerError, events::CollateralAdded, state::{Loan, LoanStatus}, utils::{verify_nullifier_unused, verify_privacy_commitment}};
@@ -34,16 +34,21 @@ pub struct AddCollateral<'info> {
         constraint = borrower_collateral_account.owner == borrower.key(),
         constraint = borrower_collateral_account.mint == loan.collateral_mint,
     )]
-    pub borrower_collateral_account: Account<'info, TokenAccount>,
+    pub borrower_collateral_account: InterfaceAccount<'info, TokenAccount>,
     
     /// Collateral vault (destination)
     #[account(
         mut,
         constraint = collateral_vault.mint == loan.collateral_mint,
     )]
-    pub collateral_vault: Account<'info, TokenAccount>,
+    pub collateral_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = collateral_mint.key() == loan.collateral_mint @ LoanManagerError::InvalidTokenMint,
+    )]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 
@@ -98,17 +103,27 @@ pub fn handler(
     //  STEP 1: Transfer additional collateral to vault
     msg!(" Locking additional collateral...");
     
-    let cpi_accounts = Transfer {
+    let vault_balance_before = ctx.accounts.collateral_vault.amount;
+
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.borrower_collateral_account.to_account_info(),
+        mint: ctx.accounts.collateral_mint.to_account_info(),
         to: ctx.accounts.collateral_vault.to_account_info(),
         authority: ctx.accounts.borrower.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
     
-    token::transfer(cpi_ctx, actual_additional_amount)?;
+    token_interface::transfer_checked(cpi_ctx, actual_additional_amount, ctx.accounts.collateral_mint.decimals)?;
+
+    // transfer-fee mints deliver less than was sent, only count what the vault holds
+    ctx.accounts.collateral_vault.reload()?;
+
+    let additional_received = ctx.accounts.collateral_vault.amount
+        .checked_sub(vault_balance_before)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
     
-    msg!("Additional collateral locked: {} tokens", actual_additional_amount);
+    msg!("Additional collateral locked: {} tokens", additional_received);
     
     // Mark nullifier as used
     loan.used_nullifier.push(additional_nullifier);
@@ -116,12 +131,12 @@ pub fn handler(
 
     // Update the Loan collateral commitment 
 
-    loan.principal_range.0 = loan.principal_range.0.checked_add(actual_additional_amount)
+    loan.principal_range.0 = loan.principal_range.0.checked_add(additional_received)
                             .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
 
 
     loan.principal_range.1 = loan.principal_range.1
-        .checked_add(actual_additional_amount)
+        .checked_add(additional_received)
         .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
 
         let estimated_principal = (loan.principal_range.0 + loan.principal_range.1) / 2;
@@ -132,7 +147,7 @@ pub fn handler(
 
      // New collateral value (old + additional)
      let new_collateral_value = estimated_collateral
-     .checked_add(actual_additional_amount)
+     .checked_add(additional_received)
      .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
     
 
@@ -165,7 +180,7 @@ emit!(CollateralAdded {
 
 
 msg!("✅ Collateral added successfully!");
-    msg!("🔒 Additional {} tokens locked", actual_additional_amount);
+    msg!("🔒 Additional {} tokens locked", additional_received);
     msg!("📊 New health factor: {}.{}x", new_health_factor / 100, new_health_factor % 100);
     
     Ok(())
@@ -1,48 +1,17 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
 
-use crate::{errors::LoanManagerError, events::LoanApproved, state::{Loan, LoanStatus}};
-
-
-#[account]
-
-pub struct LendingPool{
-    pub authority : Pubkey,
-    pub token_mint : Pubkey,
-    pub pool_token_account : Pubkey,
-    pub pool_name : String,
-    pub total_deposits : u64,
-    pub available_liquidity : u64,
-    pub total_borrowed : u64,
-    pub cumalative_interest : u64,
-    pub active_loan_count : u32,
-    pub total_loan_count : u32,
-    pub paused : bool,
-    pub created_at : i64,
-    pub bump : u8
-}
-
-
+use lending_pool::states::{LenderAccount, LendingPool, Tranche};
 
-#[account]
-
-pub struct LenderAccount{
-    pub lender : Pubkey,
-    pub pool :  Pubkey,
-    pub deposited_amount : u64,
-    pub available_amount: u64,
-    pub lent_amount : u64,
-    pub interest_earned : u64,
-    pub total_withdrawal : u64,
-    pub last_deposited_time : i64,
-    pub bump : u8
-}
+use crate::{errors::LoanManagerError, events::LoanApproved, state::{LenderOperator, Loan, LoanStatus}};
 
 
 #[derive(Accounts)]
 #[instruction(loan_id:u64)]
 pub struct ApproveLoan<'info>{
+    /// Lender, or the lender's operator acting within its limits
     #[account(mut)]
-    pub lender : Signer<'info>,
+    pub approver : Signer<'info>,
 
       /// Loan account
       #[account(
@@ -54,25 +23,44 @@ pub struct ApproveLoan<'info>{
     pub loan: Account<'info, Loan>,
 
     #[account(
-        mut,
         constraint = pool.key() == loan.pool @ LoanManagerError::InvalidLoanStatus
     )]
     pub pool : Account<'info,LendingPool>,
 
     #[account(
         mut,
-        constraint = lender_account.lender == lender.key() @ LoanManagerError::UnauthorizedLender,
-        constraint = lender_account.key() == pool.key() @ LoanManagerError::InvalidLoanStatus
+        constraint = lender_account.pool == pool.key() @ LoanManagerError::InvalidLoanStatus
     )]
-    pub lender_account : Account<'info,LenderAccount>   
+    pub lender_account : Account<'info,LenderAccount>,
+
+    /// Lender's senior shares, their value is what the lender can lend
+    #[account(
+        constraint = lender_share_account.mint == pool.share_mint @ LoanManagerError::InvalidTokenMint,
+        constraint = lender_share_account.owner == lender_account.lender @ LoanManagerError::UnauthorizedLender
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    /// Only needed when an operator approves
+    #[account(
+        mut,
+        seeds = [LenderOperator::SEED_PREFIX, lender_account.key().as_ref()],
+        bump = lender_operator.bump,
+    )]
+    pub lender_operator : Option<Account<'info,LenderOperator>>,
 }
 
+/// What a lender can still lend: the value of their senior shares minus what is already lent
+pub fn lendable_amount(pool:&LendingPool,lender_account:&LenderAccount,lender_share_account:&TokenAccount)->Result<u64>{
+    let senior_value = pool.shares_to_amount(Tranche::Senior, lender_share_account.amount).ok_or(LoanManagerError::ArithmeticOverflow)?;
+
+    Ok(lender_account.lendable_amount(senior_value))
+}
 
 pub fn handler(
     ctx:Context<ApproveLoan>,loan_id:u64
 )->Result<()>{
     let loan = &mut ctx.accounts.loan;
-    let pool = &mut ctx.accounts.pool;
+    let pool = &ctx.accounts.pool;
     let lender_account = &mut ctx.accounts.lender_account;
     let clock = Clock::get()?;
 
@@ -98,20 +86,49 @@ pub fn handler(
 
     // check lender has sufficient liquidity 
 
-    require!(lender_account.available_amount >= max_principal , LoanManagerError::InsufficientPoolLiquidity);
+    require!(lendable_amount(pool, lender_account, &ctx.accounts.lender_share_account)? >= max_principal , LoanManagerError::InsufficientPoolLiquidity);
     msg!("Liquidity check passed");
-    
 
-    loan.lender = ctx.accounts.lender.key();
+    let approver = ctx.accounts.approver.key();
+
+    // the lender approves directly, anyone else must be its operator and stay within the delegation
+    if approver != lender_account.lender {
+        let operator = ctx
+            .accounts
+            .lender_operator
+            .as_mut()
+            .ok_or(error!(LoanManagerError::UnauthorizedOperator))?;
+
+        require!(operator.operator == approver,LoanManagerError::UnauthorizedOperator);
+        require!(!operator.is_expired(clock.unix_timestamp),LoanManagerError::OperatorExpired);
+        require!(max_principal <= operator.max_principal_per_loan,LoanManagerError::OperatorLoanLimitExceeded);
+        require!(loan.credit_score >= operator.min_credit_score,LoanManagerError::CreditScoreTooLow);
+
+        operator.approved_principal = operator
+            .approved_principal
+            .checked_add(max_principal)
+            .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+
+        require!(operator.approved_principal <= operator.max_total_exposure,LoanManagerError::OperatorExposureExceeded);
+
+        loan.approving_operator = operator.key();
+        loan.operator_created_at = operator.created_at;
+        loan.operator_exposure = max_principal;
+
+        msg!("Approved by operator {}", approver);
+    }
+
+    loan.lender = lender_account.lender;
     loan.status = LoanStatus::Approved;
 
 
     emit!(LoanApproved{
         loan_id: loan.loan_id,
         borrower: loan.borrower,
-        lender: ctx.accounts.lender.key(),
+        lender: lender_account.lender,
         pool: loan.pool,
         amount: 0, // Amount hidden for privacy
+        approved_by: approver,
         timestamp: clock.unix_timestamp,
     });
 
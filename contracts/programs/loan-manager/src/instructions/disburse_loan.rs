@@ -1,7 +1,8 @@
 use anchor_lang::{accounts, prelude::*, };
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use lending_pool::{constants::LOAN_AUTHORITY_SEED, cpi::accounts::DisburseLoanFunds, program::LendingPool as LendingPoolProgram, states::{InterestRateModel, LenderAccount, LendingPool}};
 
-use crate::{errors::LoanManagerError, events::{CollateralAdded, LoanDisbursed}, instructions::{LenderAccount, LendingPool}, state::{Loan, LoanStatus}, utils::{verify_nullifier_unused, verify_privacy_commitment}};
+use crate::{errors::LoanManagerError, events::{CollateralAdded, LoanDisbursed}, state::{Loan, LoanStatus}, utils::{verify_nullifier_unused, verify_privacy_commitment}};
 
 
 #[derive(Accounts)]
@@ -33,40 +34,87 @@ pub struct DisburseLoan<'info>{
     )]
     pub pool : Account<'info,LendingPool>,
 
+    #[account(
+        constraint = interest_rate_model.pool == pool.key() @ LoanManagerError::InvalidInterestRateModel
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // not needed for pool-funded loans, whose lender is the pool itself
     #[account(
         mut ,
         constraint  = lender_account.lender == loan.lender,
         constraint = lender_account.pool == pool.key()
     )]
-    pub lender_account : Account<'info,LenderAccount>,
+    pub lender_account : Option<Account<'info,LenderAccount>>,
+
+    // lender's senior shares, passed with lender_account, lending-pool checks their value covers the loan
+    pub lender_share_account : Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         constraint = borrower_collateral_account.owner == borrower.key(),
         constraint = borrower_collateral_account.mint == loan.collateral_mint
     )]
-    pub borrower_collateral_account : Account<'info,TokenAccount>,
+    pub borrower_collateral_account : InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = collateral_vault.mint == loan.collateral_mint,
     )]
-    pub collateral_vault: Account<'info, TokenAccount>,
+    pub collateral_vault: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
         constraint = borrower_token_account.owner == borrower.key(),
-        constraint = borrower_collateral_account.mint == pool.token_mint
+        constraint = borrower_token_account.mint == pool.token_mint
     )]
-    pub borrower_token_account : Account<'info,TokenAccount>,
+    pub borrower_token_account : InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = pool_token_account.key() == pool.pool_token_account
     )]
-    pub pool_token_account : Account<'info,TokenAccount>,
+    pub pool_token_account : InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LoanManagerError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info, Mint>,
+
+    #[account(
+        constraint = collateral_mint.key() == loan.collateral_mint @ LoanManagerError::InvalidTokenMint
+    )]
+    pub collateral_mint : InterfaceAccount<'info, Mint>,
+
+    pub token_program : Interface<'info, TokenInterface>,
+
+    // pools with a yield strategy pass it, lending-pool recalls funds when pool_token_account is short
+    /// CHECK: checked by lending-pool
+    #[account(mut)]
+    pub yield_strategy : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by lending-pool against yield_strategy
+    pub strategy_program : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by lending-pool against yield_strategy
+    #[account(mut)]
+    pub strategy_state : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked by lending-pool against yield_strategy
+    #[account(mut)]
+    pub strategy_token_account : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority : UncheckedAccount<'info>,
 
-    pub token_program : Program<'info,Token>
+    pub lending_pool_program : Program<'info, LendingPoolProgram>,
+
+    // collateral may live under a different token program than the pool token
+    pub collateral_token_program : Interface<'info, TokenInterface>
 }
 
 
@@ -81,8 +129,8 @@ pub struct DisburseLoan<'info>{
 )->Result<()>{
 
     let loan = &mut ctx.accounts.loan;
-    let pool = &mut ctx.accounts.pool;
-    let lender_account = &mut ctx.accounts.lender_account;
+    let pool = &ctx.accounts.pool;
+    let lender_account = &ctx.accounts.lender_account;
     let clock = Clock::get()?;
 
     msg!("Loan Disburse is started");
@@ -90,6 +138,13 @@ pub struct DisburseLoan<'info>{
 
     require!(loan.status == LoanStatus::Approved,LoanManagerError::InvalidLoanStatus);
 
+    require!(!pool.pause_flags.disbursements,LoanManagerError::PoolOperationPaused);
+
+    require!(pool.flash_loan_amount == 0,LoanManagerError::FlashLoanActive);
+
+    // share prices overstate pool value until the pool's bad debt is absorbed
+    require!(pool.bad_debt == 0,LoanManagerError::BadDebtPending);
+
     // validate borrower
 
     require!(loan.borrower == ctx.accounts.borrower.key(),LoanManagerError::UnauthorizedBorrower);
@@ -123,102 +178,66 @@ pub struct DisburseLoan<'info>{
 
     msg!(" Nullifiers verified (no double-spend)");
     
-    // CRITICAL: NOW lock the ACTUAL amount (not estimate!)
-    msg!(" Locking actual principal amount: {}", actual_principal);
-    
-    // Check pool has sufficient liquidity
-
-    require!(pool.available_liquidity >= actual_principal,LoanManagerError::InsufficientPoolLiquidity);
-
-    require!(lender_account.available_amount >=actual_principal,LoanManagerError::InsufficientPoolLiquidity);
-
-
-    // reduce pool liquidity 
-
-    pool.available_liquidity = pool.available_liquidity
-                                .checked_sub(actual_principal)
-                                .ok_or(error!(LoanManagerError::InsufficientPoolLiquidity))?;
-
-    
-    //  Increase pool total borrow 
-
-    pool.total_borrowed = pool.total_borrowed
-                          .checked_add(actual_principal)
-                          .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-    // added loan count +1
-
-    pool.active_loan_count = pool
-    .active_loan_count
-    .checked_add(1)
-    .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-    msg!("Pool updated with ACTUAL amount");
-    msg!("  Locked: {}", actual_principal);
-    msg!("  New available: {}", pool.available_liquidity);
-    msg!("  Total borrowed: {}", pool.total_borrowed);
-    
+    require!(
+        loan.pool_funded || lender_account.is_some(),
+        LoanManagerError::LenderAccountRequired
+    );
 
-    // decrease lender account avaiblae balance 
-    lender_account.available_amount = lender_account
-    .available_amount
-    .checked_sub(actual_principal)
-    .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
-    // increase lender accoutn lent amount 
-
-    lender_account.lent_amount = lender_account
-    .lent_amount
-    .checked_add(actual_principal)
-    .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-    msg!(" Lender account updated with ACTUAL amount");
-    msg!("  Lender available: {}", lender_account.available_amount);
-    msg!("  Lender lent: {}", lender_account.lent_amount);
-    
     //  STEP 1: Lock collateral in vault
     msg!("Locking collateral...");
 
     // Transfer collateral from Borrower collateral accoutn to collateral vault 
 
-    let cpi_accounts = Transfer {
+    let vault_balance_before = ctx.accounts.collateral_vault.amount;
+
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.borrower_collateral_account.to_account_info(),
+        mint: ctx.accounts.collateral_mint.to_account_info(),
         to: ctx.accounts.collateral_vault.to_account_info(),
         authority: ctx.accounts.borrower.to_account_info(),
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_program = ctx.accounts.collateral_token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
     
-    token::transfer(cpi_ctx, actual_collateral)?;
+    token_interface::transfer_checked(cpi_ctx, actual_collateral, ctx.accounts.collateral_mint.decimals)?;
+
+    // transfer-fee mints deliver less than was sent
+    ctx.accounts.collateral_vault.reload()?;
+
+    let collateral_received = ctx.accounts.collateral_vault.amount
+        .checked_sub(vault_balance_before)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
     
-    msg!(" Collateral locked: {} tokens", actual_collateral);
+    msg!(" Collateral locked: {} tokens", collateral_received);
 
     let collateral_nullifier  = loan.collateral_commitment.nullifier;
     loan.used_nullifier.push(collateral_nullifier);
-    // transfer fund Loan fund from pool to borrower 
-
+    // lending-pool moves the funds and books the new debt, recalling from its yield strategy if short
     msg!("Transfering Fund from pool to borrower");
+    msg!(" Locking actual principal amount: {}", actual_principal);
 
-    let pool_key = pool.key();
-
-    let seeds = &[
-                    b"lending_pool".as_ref(),
-                    pool_key.as_ref(),
-                    &[ctx.accounts.pool.bump]
-    ]; 
-
-    let signer_seeds = &[& seeds[..]];
-
-
-    let cpi_account = Transfer{
-        from : ctx.accounts.pool_token_account.to_account_info(),
-        to : ctx.accounts.borrower_token_account.to_account_info(),
-        authority : ctx.accounts.pool.to_account_info(),
+    let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+    let cpi_accounts = DisburseLoanFunds{
+        loan_authority : ctx.accounts.loan_authority.to_account_info(),
+        pool : ctx.accounts.pool.to_account_info(),
+        interest_rate_model : ctx.accounts.interest_rate_model.to_account_info(),
+        lender_account : ctx.accounts.lender_account.as_ref().map(|a| a.to_account_info()),
+        lender_share_account : ctx.accounts.lender_share_account.as_ref().map(|a| a.to_account_info()),
+        token_mint : ctx.accounts.token_mint.to_account_info(),
+        pool_token_account : ctx.accounts.pool_token_account.to_account_info(),
+        borrower_token_account : ctx.accounts.borrower_token_account.to_account_info(),
+        yield_strategy : ctx.accounts.yield_strategy.as_ref().map(|a| a.to_account_info()),
+        strategy_program : ctx.accounts.strategy_program.as_ref().map(|a| a.to_account_info()),
+        strategy_state : ctx.accounts.strategy_state.as_ref().map(|a| a.to_account_info()),
+        strategy_token_account : ctx.accounts.strategy_token_account.as_ref().map(|a| a.to_account_info()),
+        token_program : ctx.accounts.token_program.to_account_info(),
     };
 
-    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(),
-                 cpi_account,
-                 signer_seeds);
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds);
 
-    token::transfer(cpi_ctx, actual_principal)?;
+    // a matched loan draws on the lender's offer reservation, lending-pool releases all of it
+    let borrow_index = lending_pool::cpi::disburse_loan_funds(cpi_ctx, loan_id, actual_principal, loan.reserved_principal)?.get();
 
     msg!("Funds transferred: {} tokens", actual_principal);
 
@@ -229,6 +248,11 @@ pub struct DisburseLoan<'info>{
     // change loan status to active 
 
     loan.status = LoanStatus::Active;
+    loan.origination_borrow_index = borrow_index;
+    loan.borrow_index_snapshot = borrow_index;
+    loan.debt_snapshot = actual_principal;
+    loan.principal_outstanding = actual_principal;
+    loan.reserved_principal = 0;
     loan.start_time = clock.unix_timestamp;
     loan.end_time = clock.unix_timestamp * loan.duration;
 
@@ -237,7 +261,7 @@ pub struct DisburseLoan<'info>{
         borrower : loan.borrower,
         lender: loan.lender,
         amount : actual_principal,
-        collateral_locked : actual_collateral,
+        collateral_locked : collateral_received,
         timestamp: clock.unix_timestamp
     });
     Ok(())
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use lending_pool::states::{LendingPool, ProtocolRoles, Role};
+
+use crate::{constants::LENDING_POOL_PROGRAM_ID, errors::LoanManagerError, events::{LoanApproved, LoanPoolFunded}, state::{Loan, LoanStatus}};
+
+#[derive(Accounts)]
+#[instruction(loan_id:u64)]
+pub struct FundLoanFromPool<'info>{
+    /// Risk manager, the pool's lenders share the exposure so no single lender signs
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LoanManagerError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        seeds = [Loan::SEED_PREFIX, loan_id.to_le_bytes().as_ref()],
+        bump = loan.bump,
+        constraint = loan.status == LoanStatus::Pending @ LoanManagerError::InvalidLoanStatus,
+    )]
+    pub loan : Account<'info,Loan>,
+
+    #[account(
+        constraint = pool.key() == loan.pool @ LoanManagerError::InvalidLoanStatus
+    )]
+    pub pool : Account<'info,LendingPool>,
+}
+
+pub fn fund_from_pool_handler(ctx:Context<FundLoanFromPool>,loan_id:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let loan = &mut ctx.accounts.loan;
+    let pool = &ctx.accounts.pool;
+
+    msg!(" Approving loan #{} from the pool", loan_id);
+
+    let max_principal = loan.principal_range.1;
+
+    // liquidity earmarked for queued withdrawals is not lendable
+    let unreserved_liquidity = pool
+        .unreserved_liquidity()
+        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+
+    require!(unreserved_liquidity >= max_principal,LoanManagerError::InsufficientPoolLiquidity);
+
+    loan.lender = pool.key();
+    loan.pool_funded = true;
+    loan.status = LoanStatus::Approved;
+
+    emit!(LoanApproved{
+        loan_id,
+        borrower: loan.borrower,
+        lender: loan.lender,
+        pool: loan.pool,
+        amount: 0, // Amount hidden for privacy
+        approved_by: ctx.accounts.risk_manager.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit!(LoanPoolFunded{
+        loan_id,
+        pool: loan.pool,
+        approved_by: ctx.accounts.risk_manager.key(),
+        max_principal,
+        timestamp: clock.unix_timestamp
+    });
+
+    msg!(" Loan #{} approved, funded pro-rata by all pool lenders", loan_id);
+    msg!("Next step: Borrower calls disburse_loan to lock actual amount");
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use lending_pool::states::LenderAccount;
+
+use crate::{constants::ACCOUNT_VERSION, errors::LoanManagerError, events::{LenderOperatorRevoked, LenderOperatorSet}, state::{LenderOperator, Loan}};
+
+#[derive(Accounts)]
+pub struct SetLenderOperator<'info>{
+    /// Lender (cold key), only they can delegate
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        constraint = lender_account.lender == lender.key() @ LoanManagerError::UnauthorizedLender
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    // one operator per lender account, revoke first to replace it
+    #[account(
+        init,
+        payer = lender,
+        space = LenderOperator::LEN,
+        seeds = [LenderOperator::SEED_PREFIX, lender_account.key().as_ref()],
+        bump
+    )]
+    pub lender_operator : Account<'info,LenderOperator>,
+
+    pub system_program : Program<'info,System>
+}
+
+#[derive(Accounts)]
+pub struct RevokeLenderOperator<'info>{
+    /// Lender (only they can revoke), receives the rent back
+    #[account(mut)]
+    pub lender : Signer<'info>,
+
+    #[account(
+        mut,
+        close = lender,
+        seeds = [LenderOperator::SEED_PREFIX, lender_operator.lender_account.as_ref()],
+        bump = lender_operator.bump,
+        constraint = lender_operator.lender == lender.key() @ LoanManagerError::UnauthorizedLender
+    )]
+    pub lender_operator : Account<'info,LenderOperator>,
+}
+
+pub fn set_handler(
+    ctx:Context<SetLenderOperator>,
+    operator:Pubkey,
+    max_principal_per_loan:u64,
+    max_total_exposure:u64,
+    min_credit_score:u16,
+    expires_at:i64,
+)->Result<()>{
+    let clock = Clock::get()?;
+
+    require!(
+        operator != Pubkey::default()
+            && max_principal_per_loan > 0
+            && max_principal_per_loan <= max_total_exposure
+            && (expires_at == 0 || expires_at > clock.unix_timestamp),
+        LoanManagerError::InvalidOperatorLimits
+    );
+
+    let delegation = &mut ctx.accounts.lender_operator;
+
+    delegation.lender = ctx.accounts.lender.key();
+    delegation.lender_account = ctx.accounts.lender_account.key();
+    delegation.operator = operator;
+    delegation.max_principal_per_loan = max_principal_per_loan;
+    delegation.max_total_exposure = max_total_exposure;
+    delegation.approved_principal = 0;
+    delegation.min_credit_score = min_credit_score;
+    delegation.expires_at = expires_at;
+    delegation.created_at = clock.unix_timestamp;
+    delegation.version = ACCOUNT_VERSION;
+    delegation.bump = ctx.bumps.lender_operator;
+
+    emit!(LenderOperatorSet{
+        lender: delegation.lender,
+        lender_account: delegation.lender_account,
+        operator,
+        max_principal_per_loan,
+        max_total_exposure,
+        min_credit_score,
+        expires_at,
+        timestamp: clock.unix_timestamp
+    });
+
+    msg!(" Lender operator set: {}", operator);
+    msg!("  Max principal per loan: {}", max_principal_per_loan);
+    msg!("  Max total exposure: {}", max_total_exposure);
+    msg!("  Min credit score: {}", min_credit_score);
+    msg!("  Expires at: {}", expires_at);
+    Ok(())
+}
+
+pub fn revoke_handler(ctx:Context<RevokeLenderOperator>)->Result<()>{
+    let clock = Clock::get()?;
+    let delegation = &ctx.accounts.lender_operator;
+
+    emit!(LenderOperatorRevoked{
+        lender: delegation.lender,
+        lender_account: delegation.lender_account,
+        operator: delegation.operator,
+        approved_principal: delegation.approved_principal,
+        timestamp: clock.unix_timestamp
+    });
+
+    msg!(" Lender operator revoked: {}", delegation.operator);
+    Ok(())
+}
+
+/// Give up to `amount` of the exposure an operator approval charged for `loan` back to the
+/// delegation. A revoked delegation is gone, and one set again after the revoke lives at the
+/// same address but never carried this loan, in both cases the part is only cleared from the loan
+pub fn release_operator_exposure(loan:&mut Loan,lender_operator:Option<&UncheckedAccount>,amount:u64)->Result<()>{
+    let amount = amount.min(loan.operator_exposure);
+
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let account = lender_operator.ok_or(error!(LoanManagerError::LenderOperatorRequired))?;
+
+    require_keys_eq!(account.key(), loan.approving_operator, LoanManagerError::UnauthorizedOperator);
+
+    if account.owner == &crate::ID && !account.data_is_empty() {
+        let mut delegation = LenderOperator::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+
+        if delegation.created_at == loan.operator_created_at {
+            delegation.approved_principal = delegation.approved_principal.saturating_sub(amount);
+
+            delegation.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        }
+    }
+
+    loan.operator_exposure -= amount;
+    Ok(())
+}
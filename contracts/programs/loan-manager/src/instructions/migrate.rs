@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use lending_pool::migration::{migrate_account, Migrate};
+
+use crate::{constants::ACCOUNT_VERSION, errors::LoanManagerError, events::AccountMigrated, state::{Loan, LoanCounter, LoanStatus, PrivateAmount}};
+
+// permissionless: the new layout is derived from the account's own data only
+#[derive(Accounts)]
+pub struct MigrateAccount<'info>{
+    // tops up the rent for the larger layout
+    #[account(mut)]
+    pub payer : Signer<'info>,
+
+    /// CHECK: baseline layouts do not deserialize as the current one, discriminator and size are checked in migrate
+    #[account(
+        mut,
+        owner = crate::ID @ LoanManagerError::InvalidAccountLayout
+    )]
+    pub account : UncheckedAccount<'info>,
+
+    pub system_program : Program<'info,System>,
+}
+
+/// Loan as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct LoanV0{
+    pub loan_id : u64,
+
+    pub borrower : Pubkey,
+
+    pub lender : Pubkey,
+
+    pub pool : Pubkey,
+
+    pub principal_commitment: PrivateAmount,
+    pub collateral_commitment: PrivateAmount,
+    pub repaid_commitment: PrivateAmount,
+
+    pub principal_range: (u64, u64),
+
+    pub collateral_mint : Pubkey,
+
+    pub interest_rate : u16,
+
+    pub duration : i64,
+
+    pub status : LoanStatus,
+
+    pub start_time : i64,
+
+    pub end_time : i64,
+
+    pub merkel_root : [u8;32],
+
+    pub used_nullifier : Vec<[u8;32]>,
+
+    pub amount_hash: [u8; 32],
+    pub collateral_hash: [u8; 32],
+
+    pub health_factor : u16,
+
+    pub last_health_check : i64,
+
+    pub collateral_value_usd : u64,
+
+    pub warning_sent : bool,
+
+    pub credit_score : u16,
+
+    pub required_collateral_ratio : u16,
+
+    pub total_repaid : u64,
+    pub bump : u8
+}
+
+impl Migrate for Loan {
+    type V0 = LoanV0;
+    const V0_SPACE: usize = 816;
+    const SPACE: usize = Loan::LEN;
+
+    // the baseline never stored a loan's principal, it priced repayments on the middle of
+    // principal_range. What of that estimate is not repaid yet becomes the outstanding debt,
+    // without a borrow index snapshot it is owed as is
+    fn from_v0(old: LoanV0, _now: i64) -> Result<Self> {
+        let outstanding = match old.status {
+            LoanStatus::Active | LoanStatus::PartiallyRepaid => {
+                let estimated_principal = ((old.principal_range.0 as u128 + old.principal_range.1 as u128) / 2) as u64;
+                estimated_principal.saturating_sub(old.total_repaid)
+            }
+            _ => 0,
+        };
+
+        Ok(Loan{
+            version: ACCOUNT_VERSION,
+            loan_id: old.loan_id,
+            borrower: old.borrower,
+            lender: old.lender,
+            pool: old.pool,
+            principal_commitment: old.principal_commitment,
+            collateral_commitment: old.collateral_commitment,
+            repaid_commitment: old.repaid_commitment,
+            principal_range: old.principal_range,
+            collateral_mint: old.collateral_mint,
+            interest_rate: old.interest_rate,
+            duration: old.duration,
+            status: old.status,
+            start_time: old.start_time,
+            end_time: old.end_time,
+            merkel_root: old.merkel_root,
+            used_nullifier: old.used_nullifier,
+            amount_hash: old.amount_hash,
+            collateral_hash: old.collateral_hash,
+            health_factor: old.health_factor,
+            last_health_check: old.last_health_check,
+            collateral_value_usd: old.collateral_value_usd,
+            warning_sent: old.warning_sent,
+            credit_score: old.credit_score,
+            required_collateral_ratio: old.required_collateral_ratio,
+            total_repaid: old.total_repaid,
+            origination_borrow_index: 0,
+            borrow_index_snapshot: 0,
+            debt_snapshot: outstanding,
+            principal_outstanding: outstanding,
+            pool_funded: false,
+            approving_operator: Pubkey::default(),
+            operator_created_at: 0,
+            operator_exposure: 0,
+            reserved_principal: 0,
+            bump: old.bump,
+        })
+    }
+}
+
+/// LoanCounter as the baseline program wrote it
+#[derive(AnchorDeserialize)]
+pub struct LoanCounterV0{
+    pub next_loan_id : u64,
+
+    pub total_loans : u64,
+
+    pub active_loans : u64,
+
+    pub bump : u8
+}
+
+impl Migrate for LoanCounter {
+    type V0 = LoanCounterV0;
+    const V0_SPACE: usize = 33;
+    const SPACE: usize = LoanCounter::LEN;
+
+    fn from_v0(old: LoanCounterV0, _now: i64) -> Result<Self> {
+        Ok(LoanCounter{
+            version: ACCOUNT_VERSION,
+            next_loan_id: old.next_loan_id,
+            total_loans: old.total_loans,
+            active_loans: old.active_loans,
+            bump: old.bump,
+        })
+    }
+}
+
+/// Upgrade a baseline account of type T to the current layout in place
+pub fn migrate<T: Migrate>(ctx:Context<MigrateAccount>)->Result<()>{
+    let clock = Clock::get()?;
+    let account = ctx.accounts.account.to_account_info();
+
+    migrate_account::<T>(
+        &ctx.accounts.payer.to_account_info(),
+        &account,
+        &ctx.accounts.system_program.to_account_info(),
+        clock.unix_timestamp,
+        LoanManagerError::InvalidAccountLayout.into(),
+        LoanManagerError::AccountAlreadyMigrated.into(),
+    )?;
+
+    emit!(AccountMigrated{
+        account: account.key(),
+        from_version: 0,
+        to_version: ACCOUNT_VERSION,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(" Account migrated: v0 -> v{}", ACCOUNT_VERSION);
+    Ok(())
+}
@@ -5,6 +5,11 @@ pub mod disburse_loan;
 pub mod repay_loan;
 pub mod add_collateral;
 pub mod update_health;
+pub mod write_off_loan;
+pub mod lender_operator;
+pub mod offer_book;
+pub mod fund_loan_from_pool;
+pub mod migrate;
 
 // Re-export
 pub use request_loan::*;
@@ -12,4 +17,9 @@ pub use approve_loan::*;
 pub use disburse_loan::*;
 pub use repay_loan::*;
 pub use add_collateral::*;
-pub use update_health::*;
\ No newline at end of file
+pub use update_health::*;
+pub use write_off_loan::*;
+pub use lender_operator::*;
+pub use offer_book::*;
+pub use fund_loan_from_pool::*;
+pub use migrate::*;
\ No newline at end of file
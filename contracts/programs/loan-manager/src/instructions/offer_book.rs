@@ -0,0 +1,319 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use lending_pool::{constants::LOAN_AUTHORITY_SEED, cpi::accounts::ReserveLenderFunds, program::LendingPool as LendingPoolProgram, states::{LenderAccount, LendingPool}};
+
+use crate::{constants::{ACCOUNT_VERSION, MAX_LOAN_DURATION, MAX_OFFERS_PER_LENDER, MIN_LOAN_DURATION}, errors::LoanManagerError, events::{LoanApproved, LoanMatched, OfferCancelled, OfferPosted}, state::{Loan, LoanOffer, LoanStatus, OfferBook}};
+
+#[derive(Accounts)]
+pub struct InitializeOfferBook<'info>{
+    #[account(mut)]
+    pub payer : Signer<'info>,
+
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = OfferBook::LEN,
+        seeds = [OfferBook::SEED_PREFIX, pool.key().as_ref()],
+        bump
+    )]
+    pub offer_book : Account<'info,OfferBook>,
+
+    pub system_program : Program<'info,System>
+}
+
+#[derive(Accounts)]
+pub struct PostOffer<'info>{
+    pub lender : Signer<'info>,
+
+    pub pool : Account<'info,LendingPool>,
+
+    // the offer's total is reserved on it in lending-pool until matched loans draw on it
+    #[account(
+        mut,
+        constraint = lender_account.lender == lender.key() @ LoanManagerError::UnauthorizedLender,
+        constraint = lender_account.pool == pool.key() @ LoanManagerError::UnauthorizedLender
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    #[account(
+        constraint = lender_share_account.mint == pool.share_mint @ LoanManagerError::InvalidTokenMint,
+        constraint = lender_share_account.owner == lender.key() @ LoanManagerError::UnauthorizedLender
+    )]
+    pub lender_share_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [OfferBook::SEED_PREFIX, pool.key().as_ref()],
+        bump = offer_book.bump
+    )]
+    pub offer_book : Account<'info,OfferBook>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority : UncheckedAccount<'info>,
+
+    pub lending_pool_program : Program<'info,LendingPoolProgram>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOffer<'info>{
+    pub lender : Signer<'info>,
+
+    #[account(
+        constraint = pool.key() == offer_book.pool
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    // gets back what is left of the offer's reservation
+    #[account(
+        mut,
+        constraint = lender_account.lender == lender.key() @ LoanManagerError::UnauthorizedLender,
+        constraint = lender_account.pool == pool.key() @ LoanManagerError::UnauthorizedLender
+    )]
+    pub lender_account : Account<'info,LenderAccount>,
+
+    #[account(
+        mut,
+        seeds = [OfferBook::SEED_PREFIX, offer_book.pool.as_ref()],
+        bump = offer_book.bump
+    )]
+    pub offer_book : Account<'info,OfferBook>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority : UncheckedAccount<'info>,
+
+    pub lending_pool_program : Program<'info,LendingPoolProgram>,
+}
+
+/// Permissionless crank, approves a pending loan against the best offer in its pool's book
+#[derive(Accounts)]
+#[instruction(loan_id:u64)]
+pub struct MatchLoan<'info>{
+    #[account(
+        mut,
+        seeds = [Loan::SEED_PREFIX, loan_id.to_le_bytes().as_ref()],
+        bump = loan.bump,
+        constraint = loan.status == LoanStatus::Pending @ LoanManagerError::InvalidLoanStatus,
+    )]
+    pub loan : Account<'info,Loan>,
+
+    #[account(
+        constraint = pool.key() == loan.pool @ LoanManagerError::InvalidLoanStatus
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        mut,
+        seeds = [OfferBook::SEED_PREFIX, pool.key().as_ref()],
+        bump = offer_book.bump
+    )]
+    pub offer_book : Account<'info,OfferBook>,
+}
+
+pub fn initialize_handler(ctx:Context<InitializeOfferBook>)->Result<()>{
+    let offer_book = &mut ctx.accounts.offer_book;
+
+    offer_book.pool = ctx.accounts.pool.key();
+    offer_book.next_offer_id = 0;
+    offer_book.offers = vec![];
+    offer_book.version = ACCOUNT_VERSION;
+    offer_book.bump = ctx.bumps.offer_book;
+
+    msg!(" Offer book initialized for pool {}", offer_book.pool);
+    Ok(())
+}
+
+pub fn post_handler(
+    ctx:Context<PostOffer>,
+    max_amount:u64,
+    total_amount:u64,
+    min_credit_score:u16,
+    max_duration_days:u16,
+    min_rate_bps:u16,
+)->Result<()>{
+    let clock = Clock::get()?;
+    let offer_book = &mut ctx.accounts.offer_book;
+    let lender_account = &ctx.accounts.lender_account;
+
+    let max_duration = (max_duration_days as i64) * 24 * 60 * 60;
+
+    require!(
+        max_amount > 0
+            && total_amount >= max_amount
+            && (MIN_LOAN_DURATION..=MAX_LOAN_DURATION).contains(&max_duration),
+        LoanManagerError::InvalidOfferTerms
+    );
+
+    require!(!offer_book.is_full(),LoanManagerError::OfferBookFull);
+
+    require!(offer_book.offers_of(&ctx.accounts.lender.key()) < MAX_OFFERS_PER_LENDER,LoanManagerError::TooManyOffers);
+
+    // lending-pool locks the whole budget, failing if the lender's shares don't cover it
+    let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+    let cpi_accounts = ReserveLenderFunds{
+        loan_authority: ctx.accounts.loan_authority.to_account_info(),
+        pool: ctx.accounts.pool.to_account_info(),
+        lender_account: lender_account.to_account_info(),
+        lender_share_account: Some(ctx.accounts.lender_share_account.to_account_info()),
+    };
+    lending_pool::cpi::reserve_lender_funds(
+        CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds),
+        total_amount,
+    )?;
+
+    let offer_id = offer_book.next_offer_id;
+
+    offer_book.next_offer_id = offer_book.next_offer_id
+        .checked_add(1)
+        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+
+    offer_book.offers.push(LoanOffer{
+        offer_id,
+        lender: ctx.accounts.lender.key(),
+        lender_account: lender_account.key(),
+        max_amount,
+        remaining_amount: total_amount,
+        min_credit_score,
+        max_duration,
+        min_rate_bps,
+        created_at: clock.unix_timestamp,
+    });
+
+    emit!(OfferPosted{
+        pool: offer_book.pool,
+        offer_id,
+        lender: ctx.accounts.lender.key(),
+        max_amount,
+        total_amount,
+        min_credit_score,
+        max_duration,
+        min_rate_bps,
+        timestamp: clock.unix_timestamp
+    });
+
+    msg!(" Offer #{} posted", offer_id);
+    msg!("  Max amount per loan: {}", max_amount);
+    msg!("  Total amount: {}", total_amount);
+    msg!("  Min credit score: {}", min_credit_score);
+    msg!("  Min rate: {} bps", min_rate_bps);
+    Ok(())
+}
+
+pub fn cancel_handler(ctx:Context<CancelOffer>,offer_id:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let offer_book = &mut ctx.accounts.offer_book;
+    let lender = ctx.accounts.lender.key();
+
+    let index = offer_book
+        .offers
+        .iter()
+        .position(|o| o.offer_id == offer_id && o.lender == lender)
+        .ok_or(error!(LoanManagerError::OfferNotFound))?;
+
+    let offer = offer_book.offers.remove(index);
+
+    require!(offer.lender_account == ctx.accounts.lender_account.key(),LoanManagerError::OfferLenderMismatch);
+
+    // loans already matched keep their part until disbursement
+    if offer.remaining_amount > 0 {
+        let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+        let cpi_accounts = ReserveLenderFunds{
+            loan_authority: ctx.accounts.loan_authority.to_account_info(),
+            pool: ctx.accounts.pool.to_account_info(),
+            lender_account: ctx.accounts.lender_account.to_account_info(),
+            lender_share_account: None,
+        };
+        lending_pool::cpi::release_lender_funds(
+            CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds),
+            offer.remaining_amount,
+        )?;
+    }
+
+    emit!(OfferCancelled{
+        pool: offer_book.pool,
+        offer_id,
+        lender,
+        remaining_amount: offer.remaining_amount,
+        timestamp: clock.unix_timestamp
+    });
+
+    msg!(" Offer #{} cancelled", offer_id);
+    Ok(())
+}
+
+pub fn match_handler(ctx:Context<MatchLoan>,loan_id:u64)->Result<()>{
+    let clock = Clock::get()?;
+    let loan = &mut ctx.accounts.loan;
+    let pool = &ctx.accounts.pool;
+    let offer_book = &mut ctx.accounts.offer_book;
+
+    msg!(" Matching loan #{}", loan_id);
+
+    // matching originates a loan, it stops with loan requests or disbursements
+    require!(
+        !pool.pause_flags.loan_requests && !pool.pause_flags.disbursements,
+        LoanManagerError::PoolOperationPaused
+    );
+
+    let index = offer_book.best_offer(loan).ok_or(error!(LoanManagerError::NoMatchingOffer))?;
+
+    let max_principal = loan.principal_range.1;
+
+    require!(pool.available_liquidity >= max_principal,LoanManagerError::InsufficientPoolLiquidity);
+
+    let offer = &mut offer_book.offers[index];
+
+    offer.remaining_amount = offer.remaining_amount
+        .checked_sub(max_principal)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
+
+    let offer_id = offer.offer_id;
+    let offer_remaining_amount = offer.remaining_amount;
+
+    // the lender's funds stay reserved in lending-pool, now for this loan until it is disbursed
+    loan.lender = offer.lender;
+    loan.reserved_principal = max_principal;
+    loan.status = LoanStatus::Approved;
+
+    // exhausted offers leave the book
+    if offer_remaining_amount == 0 {
+        offer_book.offers.remove(index);
+    }
+
+    emit!(LoanApproved{
+        loan_id,
+        borrower: loan.borrower,
+        lender: loan.lender,
+        pool: loan.pool,
+        amount: 0, // Amount hidden for privacy
+        approved_by: loan.lender,
+        timestamp: clock.unix_timestamp,
+    });
+
+    emit!(LoanMatched{
+        loan_id,
+        pool: loan.pool,
+        offer_id,
+        lender: loan.lender,
+        interest_rate: loan.interest_rate,
+        offer_remaining_amount,
+        timestamp: clock.unix_timestamp
+    });
+
+    msg!(" Loan #{} matched with offer #{}", loan_id, offer_id);
+    msg!("  Lender: {}", loan.lender);
+    msg!("  Rate: {} bps", loan.interest_rate);
+    msg!("Next step: Borrower calls disburse_loan to lock actual amount");
+    Ok(())
+}
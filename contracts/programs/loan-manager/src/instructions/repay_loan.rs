@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use lending_pool::{constants::LOAN_AUTHORITY_SEED, cpi::accounts::{AccrueInterest, RecordLoanRepayment}, program::LendingPool as LendingPoolProgram, states::{InterestRateModel, LenderAccount, LendingPool}};
 
 use crate::{
     errors::LoanManagerError, 
     events::{LoanFullyRepaid, PartialRepayment}, 
-    instructions::{LenderAccount, LendingPool}, 
+    instructions::release_operator_exposure,
     state::{Loan, LoanStatus, PrivateAmount}, 
     utils::{calculate_required_collateral, verify_nullifier_unused, verify_privacy_commitment}
 };
@@ -36,14 +37,20 @@ pub struct RepayLoan<'info> {
         constraint = pool.key() == loan.pool,
     )]
     pub pool: Account<'info, LendingPool>,
+
+    /// Pool's rate model, interest accrues at the current borrow rate
+    #[account(
+        constraint = interest_rate_model.pool == pool.key() @ LoanManagerError::InvalidInterestRateModel,
+    )]
+    pub interest_rate_model: Account<'info, InterestRateModel>,
     
-    /// Lender's account
+    /// Lender's account, not needed for pool-funded loans
     #[account(
         mut,
         constraint = lender_account.lender == loan.lender,
         constraint = lender_account.pool == pool.key(),
     )]
-    pub lender_account: Account<'info, LenderAccount>,
+    pub lender_account: Option<Account<'info, LenderAccount>>,
     
     /// Borrower's token account (source for repayment)
     #[account(
@@ -51,7 +58,7 @@ pub struct RepayLoan<'info> {
         constraint = borrower_token_account.owner == borrower.key(),
         constraint = borrower_token_account.mint == pool.token_mint,
     )]
-    pub borrower_token_account: Account<'info, TokenAccount>,
+    pub borrower_token_account: InterfaceAccount<'info, TokenAccount>,
     
     /// Borrower's collateral account (destination if fully repaid)
     #[account(
@@ -59,38 +66,92 @@ pub struct RepayLoan<'info> {
         constraint = borrower_collateral_account.owner == borrower.key(),
         constraint = borrower_collateral_account.mint == loan.collateral_mint,
     )]
-    pub borrower_collateral_account: Account<'info, TokenAccount>,
+    pub borrower_collateral_account: InterfaceAccount<'info, TokenAccount>,
     
     /// Pool token account (destination for repayment)
     #[account(
         mut,
         constraint = pool_token_account.key() == pool.pool_token_account,
     )]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
     
     /// Collateral vault (source if fully repaid)
     #[account(
         mut,
         constraint = collateral_vault.mint == loan.collateral_mint,
     )]
-    pub collateral_vault: Account<'info, TokenAccount>,
+    pub collateral_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LoanManagerError::InvalidTokenMint,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        constraint = collateral_mint.key() == loan.collateral_mint @ LoanManagerError::InvalidTokenMint,
+    )]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Collateral may live under a different token program than the pool token
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority: UncheckedAccount<'info>,
+
+    pub lending_pool_program: Program<'info, LendingPoolProgram>,
+
+    /// CHECK: pool's withdrawal queue, when passed lending-pool fills queued withdrawals from
+    /// the repayment as far as the destination token accounts of the queued requests (remaining
+    /// accounts) allow
+    #[account(mut)]
+    pub withdrawal_queue: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: pool's share mint, checked by lending-pool
+    #[account(mut)]
+    pub share_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: withdrawal queue's share escrow, checked by lending-pool
+    #[account(mut)]
+    pub escrow_share_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the loan's approving LenderOperator, required while the loan holds operator
+    /// exposure, checked against loan.approving_operator
+    #[account(mut)]
+    pub lender_operator: Option<UncheckedAccount<'info>>,
 }
 
-pub fn handler(
-    ctx: Context<RepayLoan>,
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RepayLoan<'info>>,
     loan_id: u64,
     repayment_commitment: [u8; 32],
     repayment_nullifier: [u8; 32],
     actual_repayment_amount: u64,
     repayment_secret: [u8; 32],
 ) -> Result<()> {
-    let loan = &mut ctx.accounts.loan;
-    let pool = &mut ctx.accounts.pool;
-    let lender_account = &mut ctx.accounts.lender_account;
     let clock = Clock::get()?;
 
+    // Accrue pool interest in lending-pool first, the debt below is priced at the new index
+    let cpi_accounts = AccrueInterest {
+        pool: ctx.accounts.pool.to_account_info(),
+        interest_rate_model: ctx.accounts.interest_rate_model.to_account_info(),
+    };
+    lending_pool::cpi::accrue_interest(CpiContext::new(
+        ctx.accounts.lending_pool_program.to_account_info(),
+        cpi_accounts,
+    ))?;
+
+    ctx.accounts.pool.reload()?;
+
+    let loan = &mut ctx.accounts.loan;
+    let pool = &ctx.accounts.pool;
+    let lender_account = &ctx.accounts.lender_account;
+
     msg!("💳 Processing loan repayment for loan #{}...", loan_id);
 
     // ✅ STEP 1: Validate loan status
@@ -99,6 +160,18 @@ pub fn handler(
         LoanManagerError::InvalidLoanStatus
     );
 
+    require!(!pool.pause_flags.repayments, LoanManagerError::PoolOperationPaused);
+
+    require!(pool.flash_loan_amount == 0, LoanManagerError::FlashLoanActive);
+
+    // share prices overstate pool value until the pool's bad debt is absorbed
+    require!(pool.bad_debt == 0, LoanManagerError::BadDebtPending);
+
+    require!(
+        loan.pool_funded || lender_account.is_some(),
+        LoanManagerError::LenderAccountRequired
+    );
+
     // ✅ STEP 2: Validate borrower
     require!(
         loan.borrower == ctx.accounts.borrower.key(),
@@ -122,56 +195,51 @@ pub fn handler(
     verify_nullifier_unused(&repayment_nullifier, &loan.used_nullifier)?;
     msg!("✅ Nullifier verified (no double-spend)");
 
-    // ✅ STEP 5: Calculate loan amounts
-    let estimated_principal = (loan.principal_range.0 + loan.principal_range.1) / 2;
-
-    let time_elapsed = clock
-        .unix_timestamp
-        .checked_sub(loan.start_time)
-        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
-
-    let seconds_per_year = 365 * 24 * 60 * 60;
-
-    // ✅ FIX: Calculate interest correctly
-    let interest_accrued = (estimated_principal as u128)
-        .checked_mul(loan.interest_rate as u128)
-        .and_then(|v| v.checked_mul(time_elapsed as u128))
-        .and_then(|v| v.checked_div(seconds_per_year as u128))
-        .and_then(|v| v.checked_div(10000))
-        .and_then(|v| u64::try_from(v).ok())
+    // ✅ STEP 5: Compute exact debt from the borrow index
+    let total_owed = loan
+        .current_debt(pool.borrow_index)
         .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
 
-    let total_owed = estimated_principal
-        .checked_add(interest_accrued)
-        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+    let interest_accrued = total_owed.saturating_sub(loan.principal_outstanding);
 
     msg!("📊 Loan calculation:");
-    msg!("  Principal: ~{}", estimated_principal);
+    msg!("  Principal outstanding: {}", loan.principal_outstanding);
+    msg!("  Interest rate: {} bps", loan.interest_rate);
     msg!("  Interest accrued: {}", interest_accrued);
     msg!("  Total owed: {}", total_owed);
     msg!("  Repayment amount: {}", actual_repayment_amount);
 
-    // ✅ STEP 6: Validate repayment amount
-    require!(
-        actual_repayment_amount <= total_owed,
-        LoanManagerError::RepaymentExceedsBalance
-    );
-    msg!("✅ Repayment amount validated");
-
-    // ✅ STEP 7: Transfer repayment from borrower to pool
+    // ✅ STEP 6: Transfer repayment from borrower to pool
     msg!("💸 Transferring repayment...");
 
-    let cpi_accounts = Transfer {
+    let pool_balance_before = ctx.accounts.pool_token_account.amount;
+
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.borrower_token_account.to_account_info(),
+        mint: ctx.accounts.token_mint.to_account_info(),
         to: ctx.accounts.pool_token_account.to_account_info(),
         authority: ctx.accounts.borrower.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-    token::transfer(cpi_ctx, actual_repayment_amount)?;
+    token_interface::transfer_checked(cpi_ctx, actual_repayment_amount, ctx.accounts.token_mint.decimals)?;
+
+    // Only what reaches the pool pays down debt, transfer fees are on the borrower
+    ctx.accounts.pool_token_account.reload()?;
+
+    let repayment_received = ctx.accounts.pool_token_account.amount
+        .checked_sub(pool_balance_before)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
 
-    msg!("✅ Repayment transferred: {} tokens", actual_repayment_amount);
+    msg!("✅ Repayment transferred: {} tokens received", repayment_received);
+
+    // ✅ STEP 7: Validate repayment amount
+    require!(
+        repayment_received <= total_owed,
+        LoanManagerError::RepaymentExceedsBalance
+    );
+    msg!("✅ Repayment amount validated");
 
     // ✅ STEP 8: Mark nullifier as used
     loan.used_nullifier.push(repayment_nullifier);
@@ -180,35 +248,60 @@ pub fn handler(
     loan.repaid_commitment = PrivateAmount::new(repayment_commitment, repayment_nullifier);
 
     // ✅ STEP 10: Calculate interest and principal portions
-    let interest_portion = actual_repayment_amount.min(interest_accrued);
-    let principal_portion = actual_repayment_amount.saturating_sub(interest_portion);
+    let interest_portion = repayment_received.min(interest_accrued);
+    let principal_portion = repayment_received.saturating_sub(interest_portion);
 
     msg!("📊 Repayment breakdown:");
     msg!("  Interest portion: {}", interest_portion);
     msg!("  Principal portion: {}", principal_portion);
 
-    // ✅ STEP 11: Update pool reserves (happens for both full and partial)
-    pool.available_liquidity = pool
-        .available_liquidity
-        .checked_add(principal_portion)
-        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-    pool.cumalative_interest = pool
-        .cumalative_interest
-        .checked_add(interest_portion)
-        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+    let remaining_balance = total_owed.saturating_sub(repayment_received);
+
+    // ✅ STEP 11: Book the repayment in lending-pool (pool liquidity, lender account, reserves)
+    let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+    let cpi_accounts = RecordLoanRepayment {
+        loan_authority: ctx.accounts.loan_authority.to_account_info(),
+        pool: ctx.accounts.pool.to_account_info(),
+        lender_account: lender_account.as_ref().map(|a| a.to_account_info()),
+        withdrawal_queue: ctx.accounts.withdrawal_queue.as_ref().map(|a| a.to_account_info()),
+        share_mint: ctx.accounts.share_mint.as_ref().map(|a| a.to_account_info()),
+        escrow_share_account: ctx.accounts.escrow_share_account.as_ref().map(|a| a.to_account_info()),
+        token_mint: Some(ctx.accounts.token_mint.to_account_info()),
+        pool_token_account: Some(ctx.accounts.pool_token_account.to_account_info()),
+        token_program: Some(ctx.accounts.token_program.to_account_info()),
+    };
+    lending_pool::cpi::record_loan_repayment(
+        CpiContext::new_with_signer(
+            ctx.accounts.lending_pool_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        loan_id,
+        principal_portion,
+        interest_portion,
+        remaining_balance == 0,
+    )?;
 
     msg!("✅ Pool updated");
-    msg!("  New available liquidity: {}", pool.available_liquidity);
-    msg!("  Total interest earned: {}", pool.cumalative_interest);
 
-    // ✅ STEP 12: Calculate remaining balance
-    let remaining_balance = total_owed.saturating_sub(actual_repayment_amount);
+    // ✅ STEP 12: Re-snapshot the remaining debt at the current index
+    loan.debt_snapshot = remaining_balance;
+    loan.borrow_index_snapshot = pool.borrow_index;
+    loan.principal_outstanding = loan
+        .principal_outstanding
+        .checked_sub(principal_portion)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
+
+    // repaid principal no longer counts against the approving operator's exposure
+    let released_exposure = if remaining_balance == 0 { loan.operator_exposure } else { principal_portion };
+    release_operator_exposure(loan, ctx.accounts.lender_operator.as_ref(), released_exposure)?;
 
     msg!("📊 Remaining balance: {}", remaining_balance);
 
     // ✅ STEP 13: Check if fully repaid or partial
-    if remaining_balance == 0 || actual_repayment_amount >= total_owed {
+    if remaining_balance == 0 {
         // ═══════════════════════════════════════════
         // FULLY REPAID PATH
         // ═══════════════════════════════════════════
@@ -229,43 +322,19 @@ pub fn handler(
         let seeds = &[Loan::SEED_PREFIX, loan_id_bytes.as_ref(), &[loan.bump]];
         let signer_seeds = &[&seeds[..]];
 
-        let cpi_accounts = Transfer {
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.collateral_vault.to_account_info(),
+            mint: ctx.accounts.collateral_mint.to_account_info(),
             to: ctx.accounts.borrower_collateral_account.to_account_info(),
             authority: loan.to_account_info(),
         };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_program = ctx.accounts.collateral_token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        token::transfer(cpi_ctx, collateral_to_release)?;
+        token_interface::transfer_checked(cpi_ctx, collateral_to_release, ctx.accounts.collateral_mint.decimals)?;
 
         msg!("✅ Collateral released: {} tokens", collateral_to_release);
 
-        // Update lender account
-        lender_account.lent_amount = lender_account
-            .lent_amount
-            .checked_sub(estimated_principal)
-            .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
-
-        lender_account.available_amount = lender_account
-            .available_amount
-            .checked_add(estimated_principal)
-            .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-        lender_account.interest_earned = lender_account
-            .interest_earned
-            .checked_add(interest_portion)
-            .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-        msg!("✅ Lender account updated");
-
-        // Update pool stats
-        pool.active_loan_count = pool.active_loan_count.saturating_sub(1);
-        pool.total_borrowed = pool
-            .total_borrowed
-            .checked_sub(estimated_principal)
-            .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
-
         // Emit full repayment event
         emit!(LoanFullyRepaid {
             loan_id: loan.loan_id,
@@ -291,40 +360,11 @@ pub fn handler(
         // ✅ Track total repaid amount
         loan.total_repaid = loan
             .total_repaid
-            .checked_add(actual_repayment_amount)
+            .checked_add(repayment_received)
             .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
 
         msg!("Total repaid so far: {}", loan.total_repaid);
 
-        // ✅ Update lender account (partial repayment)
-        lender_account.lent_amount = lender_account
-            .lent_amount
-            .checked_sub(principal_portion)
-            .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
-
-        lender_account.available_amount = lender_account
-            .available_amount
-            .checked_add(principal_portion)
-            .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-        lender_account.interest_earned = lender_account
-            .interest_earned
-            .checked_add(interest_portion)
-            .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
-
-        msg!("✅ Lender account updated");
-        msg!("  Available: +{}", principal_portion);
-        msg!("  Lent: -{}", principal_portion);
-        msg!("  Interest earned: +{}", interest_portion);
-
-        // Update pool stats (partial)
-        pool.total_borrowed = pool
-            .total_borrowed
-            .checked_sub(principal_portion)
-            .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
-
-        msg!("✅ Pool total borrowed updated: -{}", principal_portion);
-
         // Calculate repayment percentage
         let repayment_percentage = ((loan.total_repaid as u128)
             .checked_mul(100)
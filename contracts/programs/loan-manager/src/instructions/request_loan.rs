@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
+use lending_pool::states::{Attestation, InterestRateModel, LendingPool, MarketStatus, PoolRegistry};
 
-use crate::{constants::{DEFAULT_INTEREST_RATE, MAX_LOAN_AMOUNT, MAX_LOAN_DURATION, MIN_LOAN_AMOUNT, MIN_LOAN_DURATION}, errors::LoanManagerError, events::Loanrequested, state::{Loan, LoanCounter, NoirCreditProof, PrivateAmount}, utils::{calculate_required_collateral, extract_score_tire_from_commitment, verify_noir_credit_proof, verify_nullifier_unused}};
+use crate::{constants::{ACCOUNT_VERSION, LENDING_POOL_PROGRAM_ID, MAX_LOAN_AMOUNT, MAX_LOAN_DURATION, MIN_LOAN_AMOUNT, MIN_LOAN_DURATION}, errors::LoanManagerError, events::Loanrequested, state::{Loan, LoanCounter, NoirCreditProof, PrivateAmount}, utils::{calculate_required_collateral, extract_score_tire_from_commitment, verify_noir_credit_proof, verify_nullifier_unused}};
 
 
 #[derive(Accounts)]
@@ -10,8 +11,21 @@ pub struct RequestLoan<'info>{
     #[account(mut)]
     pub borrower: Signer<'info>,
 
-    /// Check : Pool  
-    pub pool : UncheckedAccount<'info>,
+    pub pool : Account<'info,LendingPool>,
+
+    /// lending-pool's registry, only listed pools can originate loans
+    #[account(
+        seeds = [PoolRegistry::SEED_PREFIX],
+        bump = pool_registry.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+    )]
+    pub pool_registry : Account<'info,PoolRegistry>,
+
+    /// Pool's rate model, prices the loan from current utilization
+    #[account(
+        constraint = interest_rate_model.pool == pool.key() @ LoanManagerError::InvalidInterestRateModel
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
 
     /// Check : Collateral Mint 
     pub collateral_mint : UncheckedAccount<'info>,
@@ -37,8 +51,18 @@ pub struct RequestLoan<'info>{
     )]
     pub loan : Account<'info,Loan>,
 
-
-   
+    /// Borrower's attestation from the pool's issuer, only needed when the pool is gated
+    #[account(
+        seeds = [
+            Attestation::SEED_PREFIX,
+            pool.access_policy.issuer.as_ref(),
+            borrower.key().as_ref(),
+            pool.access_policy.borrower_credential.to_le_bytes().as_ref(),
+        ],
+        bump = attestation.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+    )]
+    pub attestation : Option<Account<'info,Attestation>>,
 
     pub system_program : Program<'info,System>
 }
@@ -65,12 +89,42 @@ pub fn handler(
     msg!("Principal: HIDDEN (commitment: {:?}...)", &principal_commitment[..4]);
     msg!("Collateral: HIDDEN (commitment: {:?}...)", &collateral_commitment[..4]);
 
+    require!(!ctx.accounts.pool.pause_flags.loan_requests,LoanManagerError::PoolOperationPaused);
+
+    if ctx.accounts.pool.access_policy.requires_borrower_attestation() {
+        let attestation = ctx.accounts.attestation.as_ref().ok_or(error!(LoanManagerError::AttestationRequired))?;
+
+        require!(attestation.is_valid(clock.unix_timestamp),LoanManagerError::AttestationInvalid);
+    }
+
     require!(principal_range.0 >= MIN_LOAN_AMOUNT,LoanManagerError::LoanAmountTooLow);
 
-    require!(principal_range.1 > MAX_LOAN_AMOUNT,LoanManagerError::LoanAmountTooHigh);
+    require!(principal_range.1 <= MAX_LOAN_AMOUNT,LoanManagerError::LoanAmountTooHigh);
+
+    // Pool must be a listed market, its risk params tighten the global limits
+    let market = ctx
+        .accounts
+        .pool_registry
+        .find(&ctx.accounts.pool.key())
+        .filter(|m| m.status == MarketStatus::Listed)
+        .ok_or(error!(LoanManagerError::PoolNotListed))?;
+
+    require!(principal_range.1 <= market.max_loan_amount,LoanManagerError::LoanAmountTooHigh);
+
+    require!(credit_threshould >= market.min_credit_score,LoanManagerError::CreditScoreTooLow);
 
     let duration_seconds = (duration_days as i64) *24*60*60;
 
+    // Reject new loans once the pool hits its configured max utilization
+    let utilization = ctx.accounts.pool.calculate_utilization();
+
+    require!(utilization < ctx.accounts.interest_rate_model.max_utilization,LoanManagerError::UtilizationTooHigh);
+
+    let interest_rate = ctx.accounts.interest_rate_model.borrow_rate(utilization);
+
+    msg!("Pool utilization: {}%", utilization);
+    msg!("Quoted interest rate: {} bps", interest_rate);
+
     require!(duration_seconds>=MIN_LOAN_DURATION,LoanManagerError::DurationTooShort);
 
     require!(duration_seconds<= MAX_LOAN_DURATION,LoanManagerError::DurationTooLong);
@@ -112,6 +166,8 @@ pub fn handler(
     
     let loan_id = loan_counter.next_loan_id;
 
+    loan_counter.version = ACCOUNT_VERSION;
+
     loan_counter.next_loan_id = loan_counter.next_loan_id.checked_add(1)
                     .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
 
@@ -140,18 +196,28 @@ pub fn handler(
 
     loan.collateral_mint = ctx.accounts.collateral_mint.key();
 
-    loan.interest_rate = DEFAULT_INTEREST_RATE;
+    loan.interest_rate = interest_rate;
     loan.duration = duration_seconds;
     loan.status = crate::state::LoanStatus::Pending;
     loan.start_time = 0;
     loan.end_time = 0;
     loan.merkel_root = [0u8;32];
     loan.used_nullifier = vec![];
+    loan.origination_borrow_index = 0;
+    loan.borrow_index_snapshot = 0;
+    loan.debt_snapshot = 0;
+    loan.principal_outstanding = 0;
+    loan.pool_funded = false;
+    loan.approving_operator = Pubkey::default();
+    loan.operator_created_at = 0;
+    loan.operator_exposure = 0;
+    loan.reserved_principal = 0;
 
     loan.health_factor = 100;
     loan.last_health_check = clock.unix_timestamp;
     loan.credit_score = credit_threshould;
     loan.required_collateral_ratio = required_collateral_ratio;
+    loan.version = ACCOUNT_VERSION;
     loan.bump = ctx.bumps.loan;
 
 
@@ -7,6 +7,7 @@ use crate::state::*;
 use crate::errors::*;
 use crate::events::*;
 use crate::constants::*;
+use lending_pool::{constants::LOAN_AUTHORITY_SEED, cpi::accounts::RecordLoanOutcome, program::LendingPool as LendingPoolProgram, states::{LendingPool, ProtocolRoles, Role}};
 
 pub fn handler(
     ctx: Context<UpdateHealthFactor>,
@@ -49,7 +50,26 @@ pub fn handler(
         msg!("   Threshold: {}.{}x", HEALTH_FACTOR_LIQUIDATION / 100, HEALTH_FACTOR_LIQUIDATION % 100);
         
         // In production: Trigger liquidation process
-        loan.status = LoanStatus::Defaulted;
+        // While liquidations are paused the loan stays open, it is defaulted on the next update after unpause
+        if ctx.accounts.pool.pause_flags.liquidations {
+            msg!("   Liquidations paused on pool, loan not defaulted");
+            loan.warning_sent = true;
+        } else {
+            // lending-pool freezes junior exits until the loss is written off
+            let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+            let cpi_accounts = RecordLoanOutcome{
+                loan_authority: ctx.accounts.loan_authority.to_account_info(),
+                pool: ctx.accounts.pool.to_account_info(),
+                lender_account: None,
+            };
+            lending_pool::cpi::record_loan_default(
+                CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds),
+                loan_id,
+            )?;
+
+            loan.status = LoanStatus::Defaulted;
+        }
         
     } else if new_health_factor <= HEALTH_FACTOR_CRITICAL {
         // DANGER: At critical level
@@ -112,9 +132,17 @@ pub fn handler(
     new_health_factor: u16,
 )]
 pub struct UpdateHealthFactor<'info> {
-    /// Health monitor service (authorized to update health)
-    /// In production: This would be a specific authorized pubkey
+    /// Health monitor service, must hold the HealthMonitor role
     pub monitor: Signer<'info>,
+
+    /// Protocol roles, owned by lending-pool
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+        constraint = roles.has_role(Role::HealthMonitor, &monitor.key()) @ LoanManagerError::MissingRole,
+    )]
+    pub roles: Account<'info, ProtocolRoles>,
     
     /// Loan account
     #[account(
@@ -123,4 +151,20 @@ pub struct UpdateHealthFactor<'info> {
         bump = loan.bump,
     )]
     pub loan: Account<'info, Loan>,
+
+    /// Loan's pool, defaulting is skipped while its liquidations are paused
+    #[account(
+        mut,
+        constraint = pool.key() == loan.pool,
+    )]
+    pub pool: Account<'info, LendingPool>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority: UncheckedAccount<'info>,
+
+    pub lending_pool_program: Program<'info, LendingPoolProgram>,
 }
\ No newline at end of file
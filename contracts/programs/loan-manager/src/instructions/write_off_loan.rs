@@ -0,0 +1,397 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use lending_pool::{constants::LOAN_AUTHORITY_SEED, cpi::accounts::{AccrueInterest, RecordLoanOutcome, RecordLoanRepayment}, program::LendingPool as LendingPoolProgram, states::{InterestRateModel, LenderAccount, LendingPool, ProtocolRoles, Role}};
+
+use crate::{constants::LENDING_POOL_PROGRAM_ID, errors::LoanManagerError, events::{CollateralRecovered, LoanWrittenOff}, instructions::release_operator_exposure, state::{Loan, LoanStatus}};
+
+#[derive(Accounts)]
+#[instruction(loan_id: u64)]
+pub struct WriteOffLoan<'info>{
+    /// Risk manager, realizing a loss is a risk decision
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LoanManagerError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        seeds = [Loan::SEED_PREFIX, loan_id.to_le_bytes().as_ref()],
+        bump = loan.bump,
+        constraint = (loan.status == LoanStatus::Defaulted || loan.status == LoanStatus::Liquidated) @ LoanManagerError::InvalidLoanStatus,
+    )]
+    pub loan : Account<'info,Loan>,
+
+    #[account(
+        mut,
+        constraint = pool.key() == loan.pool
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        constraint = interest_rate_model.pool == pool.key() @ LoanManagerError::InvalidInterestRateModel
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // not needed for pool-funded loans
+    #[account(
+        mut,
+        constraint = lender_account.lender == loan.lender,
+        constraint = lender_account.pool == pool.key()
+    )]
+    pub lender_account : Option<Account<'info,LenderAccount>>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority : UncheckedAccount<'info>,
+
+    pub lending_pool_program : Program<'info,LendingPoolProgram>,
+
+    /// CHECK: the loan's approving LenderOperator, required while the loan holds operator
+    /// exposure, checked against loan.approving_operator
+    #[account(mut)]
+    pub lender_operator : Option<UncheckedAccount<'info>>,
+}
+
+pub fn handler(ctx:Context<WriteOffLoan>,loan_id:u64)->Result<()>{
+    let clock = Clock::get()?;
+
+    msg!(" Writing off loan #{}", loan_id);
+
+    require!(ctx.accounts.loan.debt_snapshot > 0,LoanManagerError::LoanAlreadyWrittenOff);
+
+    require!(
+        ctx.accounts.loan.pool_funded || ctx.accounts.lender_account.is_some(),
+        LoanManagerError::LenderAccountRequired
+    );
+
+    // interest up to now is part of the loss, lending-pool accrues it first
+    let cpi_accounts = AccrueInterest{
+        pool: ctx.accounts.pool.to_account_info(),
+        interest_rate_model: ctx.accounts.interest_rate_model.to_account_info(),
+    };
+    lending_pool::cpi::accrue_interest(CpiContext::new(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts))?;
+
+    ctx.accounts.pool.reload()?;
+
+    let loan = &ctx.accounts.loan;
+    let pool = &ctx.accounts.pool;
+
+    // recover_collateral has already paid down what the collateral brought back
+    let loss = loan
+        .current_debt(pool.borrow_index)
+        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+
+    // lending-pool books the loss as bad debt, absorb_bad_debt covers it from the
+    // insurance fund first, then socializes the rest, junior tranche first
+    let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+    let cpi_accounts = RecordLoanOutcome{
+        loan_authority: ctx.accounts.loan_authority.to_account_info(),
+        pool: pool.to_account_info(),
+        lender_account: ctx.accounts.lender_account.as_ref().map(|a| a.to_account_info()),
+    };
+    lending_pool::cpi::record_loan_write_off(
+        CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds),
+        loan_id,
+        loan.principal_outstanding,
+        loss,
+    )?;
+
+    ctx.accounts.pool.reload()?;
+
+    let pending_bad_debt = ctx.accounts.pool.bad_debt;
+    let borrow_index = ctx.accounts.pool.borrow_index;
+    let pool_key = ctx.accounts.pool.key();
+    let loan = &mut ctx.accounts.loan;
+
+    loan.debt_snapshot = 0;
+    loan.principal_outstanding = 0;
+    loan.borrow_index_snapshot = borrow_index;
+
+    // nothing of the loan is outstanding anymore
+    let released_exposure = loan.operator_exposure;
+    release_operator_exposure(loan, ctx.accounts.lender_operator.as_ref(), released_exposure)?;
+
+    emit!(LoanWrittenOff{
+        loan_id,
+        pool: pool_key,
+        lender: loan.lender,
+        loss,
+        pending_bad_debt,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Loss realized: {} tokens", loss);
+    msg!("  Pending bad debt: {}", pending_bad_debt);
+    msg!("Next step: absorb_bad_debt in lending-pool covers it from insurance, then depositors");
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(loan_id: u64)]
+pub struct RecoverCollateral<'info>{
+    /// Risk manager, sells the seized collateral and pays the proceeds in
+    pub risk_manager : Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolRoles::SEED_PREFIX],
+        bump = roles.bump,
+        seeds::program = LENDING_POOL_PROGRAM_ID,
+        constraint = roles.has_role(Role::RiskManager, &risk_manager.key()) @ LoanManagerError::MissingRole,
+    )]
+    pub roles : Account<'info,ProtocolRoles>,
+
+    #[account(
+        mut,
+        seeds = [Loan::SEED_PREFIX, loan_id.to_le_bytes().as_ref()],
+        bump = loan.bump,
+        constraint = (loan.status == LoanStatus::Defaulted || loan.status == LoanStatus::Liquidated) @ LoanManagerError::InvalidLoanStatus,
+    )]
+    pub loan : Account<'info,Loan>,
+
+    #[account(
+        mut,
+        constraint = pool.key() == loan.pool
+    )]
+    pub pool : Account<'info,LendingPool>,
+
+    #[account(
+        constraint = interest_rate_model.pool == pool.key() @ LoanManagerError::InvalidInterestRateModel
+    )]
+    pub interest_rate_model : Account<'info,InterestRateModel>,
+
+    // not needed for pool-funded loans
+    #[account(
+        mut,
+        constraint = lender_account.lender == loan.lender,
+        constraint = lender_account.pool == pool.key()
+    )]
+    pub lender_account : Option<Account<'info,LenderAccount>>,
+
+    #[account(
+        constraint = token_mint.key() == pool.token_mint @ LoanManagerError::InvalidTokenMint
+    )]
+    pub token_mint : InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.key() == pool.pool_token_account
+    )]
+    pub pool_token_account : InterfaceAccount<'info, TokenAccount>,
+
+    /// Proceeds of the collateral sale
+    #[account(
+        mut,
+        constraint = recovery_token_account.owner == risk_manager.key(),
+        constraint = recovery_token_account.mint == pool.token_mint
+    )]
+    pub recovery_token_account : InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = collateral_vault.owner == loan.key(),
+        constraint = collateral_vault.mint == loan.collateral_mint,
+    )]
+    pub collateral_vault : InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = collateral_mint.key() == loan.collateral_mint @ LoanManagerError::InvalidTokenMint
+    )]
+    pub collateral_mint : InterfaceAccount<'info, Mint>,
+
+    /// Receives the seized collateral
+    #[account(
+        mut,
+        constraint = recovery_collateral_account.owner == risk_manager.key(),
+        constraint = recovery_collateral_account.mint == loan.collateral_mint
+    )]
+    pub recovery_collateral_account : InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program : Interface<'info, TokenInterface>,
+
+    // collateral may live under a different token program than the pool token
+    pub collateral_token_program : Interface<'info, TokenInterface>,
+
+    /// CHECK: PDA signing for this program in lending-pool
+    #[account(
+        seeds = [LOAN_AUTHORITY_SEED],
+        bump,
+    )]
+    pub loan_authority : UncheckedAccount<'info>,
+
+    pub lending_pool_program : Program<'info,LendingPoolProgram>,
+
+    /// CHECK: pool's withdrawal queue, lending-pool requires it while withdrawals are queued
+    /// and fills them from the recovery. Destination token accounts of the queued requests
+    /// go in the remaining accounts
+    #[account(mut)]
+    pub withdrawal_queue : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: pool's share mint, checked by lending-pool
+    #[account(mut)]
+    pub share_mint : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: withdrawal queue's share escrow, checked by lending-pool
+    #[account(mut)]
+    pub escrow_share_account : Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the loan's approving LenderOperator, required while the loan holds operator
+    /// exposure, checked against loan.approving_operator
+    #[account(mut)]
+    pub lender_operator : Option<UncheckedAccount<'info>>,
+}
+
+pub fn recover_handler<'info>(ctx:Context<'_, '_, 'info, 'info, RecoverCollateral<'info>>,loan_id:u64,recovered_amount:u64)->Result<()>{
+    let clock = Clock::get()?;
+
+    msg!(" Recovering collateral of loan #{}", loan_id);
+
+    require!(ctx.accounts.loan.debt_snapshot > 0,LoanManagerError::LoanAlreadyWrittenOff);
+
+    require!(
+        ctx.accounts.loan.pool_funded || ctx.accounts.lender_account.is_some(),
+        LoanManagerError::LenderAccountRequired
+    );
+
+    // the recovery is priced against the debt as of now
+    let cpi_accounts = AccrueInterest{
+        pool: ctx.accounts.pool.to_account_info(),
+        interest_rate_model: ctx.accounts.interest_rate_model.to_account_info(),
+    };
+    lending_pool::cpi::accrue_interest(CpiContext::new(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts))?;
+
+    ctx.accounts.pool.reload()?;
+
+    let debt = ctx.accounts.loan
+        .current_debt(ctx.accounts.pool.borrow_index)
+        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+
+    require!(recovered_amount > 0 && recovered_amount <= debt,LoanManagerError::InvalidRecoveryAmount);
+
+    // sale proceeds into the pool, only what arrives pays down the debt
+    let pool_balance_before = ctx.accounts.pool_token_account.amount;
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.recovery_token_account.to_account_info(),
+        mint: ctx.accounts.token_mint.to_account_info(),
+        to: ctx.accounts.pool_token_account.to_account_info(),
+        authority: ctx.accounts.risk_manager.to_account_info(),
+    };
+    token_interface::transfer_checked(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+        recovered_amount,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    ctx.accounts.pool_token_account.reload()?;
+
+    let recovered = ctx.accounts.pool_token_account.amount
+        .checked_sub(pool_balance_before)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
+
+    // the whole collateral goes to the risk manager, a second recovery finds the vault empty
+    let collateral_seized = ctx.accounts.collateral_vault.amount;
+
+    if collateral_seized > 0 {
+        let loan_id_bytes = ctx.accounts.loan.loan_id.to_le_bytes();
+        let seeds = &[Loan::SEED_PREFIX, loan_id_bytes.as_ref(), &[ctx.accounts.loan.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.collateral_vault.to_account_info(),
+            mint: ctx.accounts.collateral_mint.to_account_info(),
+            to: ctx.accounts.recovery_collateral_account.to_account_info(),
+            authority: ctx.accounts.loan.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(ctx.accounts.collateral_token_program.to_account_info(), cpi_accounts, signer_seeds),
+            collateral_seized,
+            ctx.accounts.collateral_mint.decimals,
+        )?;
+    }
+
+    // paid like a repayment, interest first
+    let interest_accrued = debt.saturating_sub(ctx.accounts.loan.principal_outstanding);
+    let interest_portion = recovered.min(interest_accrued);
+    let principal_portion = recovered - interest_portion;
+    let remaining_debt = debt - recovered;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[LOAN_AUTHORITY_SEED, &[ctx.bumps.loan_authority]]];
+
+    let cpi_accounts = RecordLoanRepayment {
+        loan_authority: ctx.accounts.loan_authority.to_account_info(),
+        pool: ctx.accounts.pool.to_account_info(),
+        lender_account: ctx.accounts.lender_account.as_ref().map(|a| a.to_account_info()),
+        withdrawal_queue: ctx.accounts.withdrawal_queue.as_ref().map(|a| a.to_account_info()),
+        share_mint: ctx.accounts.share_mint.as_ref().map(|a| a.to_account_info()),
+        escrow_share_account: ctx.accounts.escrow_share_account.as_ref().map(|a| a.to_account_info()),
+        token_mint: Some(ctx.accounts.token_mint.to_account_info()),
+        pool_token_account: Some(ctx.accounts.pool_token_account.to_account_info()),
+        token_program: Some(ctx.accounts.token_program.to_account_info()),
+    };
+    lending_pool::cpi::record_loan_repayment(
+        CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        loan_id,
+        principal_portion,
+        interest_portion,
+        false,
+    )?;
+
+    // fully recovered, nothing is left to write off and the default is settled
+    if remaining_debt == 0 {
+        let cpi_accounts = RecordLoanOutcome{
+            loan_authority: ctx.accounts.loan_authority.to_account_info(),
+            pool: ctx.accounts.pool.to_account_info(),
+            lender_account: ctx.accounts.lender_account.as_ref().map(|a| a.to_account_info()),
+        };
+        lending_pool::cpi::record_loan_write_off(
+            CpiContext::new_with_signer(ctx.accounts.lending_pool_program.to_account_info(), cpi_accounts, signer_seeds),
+            loan_id,
+            0,
+            0,
+        )?;
+    }
+
+    ctx.accounts.pool.reload()?;
+
+    let borrow_index = ctx.accounts.pool.borrow_index;
+    let pool_key = ctx.accounts.pool.key();
+    let loan = &mut ctx.accounts.loan;
+
+    loan.debt_snapshot = remaining_debt;
+    loan.borrow_index_snapshot = borrow_index;
+    loan.principal_outstanding = loan
+        .principal_outstanding
+        .checked_sub(principal_portion)
+        .ok_or(error!(LoanManagerError::ArithmeticUnderflow))?;
+    loan.total_repaid = loan
+        .total_repaid
+        .checked_add(recovered)
+        .ok_or(error!(LoanManagerError::ArithmeticOverflow))?;
+
+    let released_exposure = if remaining_debt == 0 { loan.operator_exposure } else { principal_portion };
+    release_operator_exposure(loan, ctx.accounts.lender_operator.as_ref(), released_exposure)?;
+
+    emit!(CollateralRecovered{
+        loan_id,
+        pool: pool_key,
+        recovered,
+        collateral_seized,
+        remaining_debt,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Recovered: {} tokens", recovered);
+    msg!("Collateral seized: {}", collateral_seized);
+    msg!("  Remaining debt: {}", remaining_debt);
+    Ok(())
+}
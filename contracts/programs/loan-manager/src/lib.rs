@@ -58,6 +58,7 @@ pub mod loan_manager {
     }
 
     /// Approve a loan and match with lender
+    /// Signed by the lender or by its operator within the delegation limits
     /// CRITICAL: Handles Edge Case #1 (Race Condition - Atomic Liquidity Lock)
     pub fn approve_loan(
         ctx: Context<ApproveLoan>,
@@ -91,8 +92,8 @@ pub mod loan_manager {
     /// Repay loan (full or partial)
     /// CRITICAL: Handles Edge Case #2 (Partial Repayment Tracking)
     /// PRIVACY: Uses repayment commitments
-    pub fn repay_loan(
-        ctx: Context<RepayLoan>,
+    pub fn repay_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RepayLoan<'info>>,
         loan_id: u64,
         // Privacy commitment for repayment
         repayment_commitment: [u8; 32],
@@ -135,7 +136,7 @@ pub mod loan_manager {
     }
 
     /// Update loan health factor
-    /// Called by health monitoring service
+    /// Called by health monitoring service (HealthMonitor role)
     pub fn update_health_factor(
         ctx: Context<UpdateHealthFactor>,
         loan_id: u64,
@@ -149,4 +150,117 @@ pub mod loan_manager {
             health_factor,
         )
     }
-}
\ No newline at end of file
+
+    /// Seize a defaulted or liquidated loan's collateral and pay what it sold for to the pool (RiskManager role)
+    /// Recoveries pay interest first, then principal, write_off_loan only loses what is left
+    pub fn recover_collateral<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecoverCollateral<'info>>,
+        loan_id: u64,
+        recovered_amount: u64,
+    ) -> Result<()> {
+        instructions::write_off_loan::recover_handler(ctx, loan_id, recovered_amount)
+    }
+
+    /// Realize a defaulted or liquidated loan's remaining debt as pool bad debt (RiskManager role)
+    /// Run recover_collateral first, lending-pool's absorb_bad_debt covers the loss from insurance, then socializes the rest
+    pub fn write_off_loan(
+        ctx: Context<WriteOffLoan>,
+        loan_id: u64,
+    ) -> Result<()> {
+        instructions::write_off_loan::handler(ctx, loan_id)
+    }
+
+    /// Delegate loan approvals to an operator (hot) key with limits
+    /// Signed by the lender, one operator per lender account
+    pub fn set_lender_operator(
+        ctx: Context<SetLenderOperator>,
+        operator: Pubkey,
+        max_principal_per_loan: u64,
+        max_total_exposure: u64,
+        min_credit_score: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::lender_operator::set_handler(
+            ctx,
+            operator,
+            max_principal_per_loan,
+            max_total_exposure,
+            min_credit_score,
+            expires_at,
+        )
+    }
+
+    /// Revoke the lender's operator, closing the delegation
+    pub fn revoke_lender_operator(
+        ctx: Context<RevokeLenderOperator>,
+    ) -> Result<()> {
+        instructions::lender_operator::revoke_handler(ctx)
+    }
+
+    /// Create a pool's offer book (permissionless, payer funds the rent)
+    pub fn initialize_offer_book(
+        ctx: Context<InitializeOfferBook>,
+    ) -> Result<()> {
+        instructions::offer_book::initialize_handler(ctx)
+    }
+
+    /// Post a standing lending offer to the pool's offer book
+    /// Its total amount is reserved from the lender's lendable funds until matched loans are disbursed
+    pub fn post_offer(
+        ctx: Context<PostOffer>,
+        max_amount: u64,
+        total_amount: u64,
+        min_credit_score: u16,
+        max_duration_days: u16,
+        min_rate_bps: u16,
+    ) -> Result<()> {
+        instructions::offer_book::post_handler(
+            ctx,
+            max_amount,
+            total_amount,
+            min_credit_score,
+            max_duration_days,
+            min_rate_bps,
+        )
+    }
+
+    /// Cancel one of the lender's standing offers, releasing what is left of its reservation
+    pub fn cancel_offer(
+        ctx: Context<CancelOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        instructions::offer_book::cancel_handler(ctx, offer_id)
+    }
+
+    /// Match a pending loan with the best eligible offer and approve it
+    /// Permissionless crank: lowest minimum rate wins, oldest offer on ties
+    pub fn match_loan(
+        ctx: Context<MatchLoan>,
+        loan_id: u64,
+    ) -> Result<()> {
+        instructions::offer_book::match_handler(ctx, loan_id)
+    }
+
+    /// Approve a pending loan funded by the pool as a whole (RiskManager role)
+    /// Interest and defaults are shared pro-rata by all of the pool's lenders
+    pub fn fund_loan_from_pool(
+        ctx: Context<FundLoanFromPool>,
+        loan_id: u64,
+    ) -> Result<()> {
+        instructions::fund_loan_from_pool::fund_from_pool_handler(ctx, loan_id)
+    }
+
+    /// Upgrade a Loan account to the current layout version (permissionless, payer funds the rent)
+    pub fn migrate_loan(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<Loan>(ctx)
+    }
+
+    /// Upgrade a LoanCounter account to the current layout version (permissionless, payer funds the rent)
+    pub fn migrate_loan_counter(
+        ctx: Context<MigrateAccount>,
+    ) -> Result<()> {
+        instructions::migrate::migrate::<LoanCounter>(ctx)
+    }
+}
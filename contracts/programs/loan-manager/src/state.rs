@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::MAX_LOAN_OFFERS;
+
 /// Frontend encrypts with Arcium SDK, we store the ciphertext
 // #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
 // pub struct  EncryptedAmount{
@@ -49,6 +51,9 @@ impl PrivateAmount {
 #[account]
 
 pub struct Loan {
+    // layout version
+    pub version : u8,
+
     pub loan_id : u64,
 
     pub borrower : Pubkey,
@@ -98,6 +103,23 @@ pub struct Loan {
     pub required_collateral_ratio : u16,
 
     pub total_repaid : u64,
+    // pool borrow index when the loan was disbursed
+    pub origination_borrow_index : u128,
+    // debt is debt_snapshot grown by borrow_index / borrow_index_snapshot
+    pub borrow_index_snapshot : u128,
+    pub debt_snapshot : u64,
+    // principal not yet repaid, separates interest from principal in repayments
+    pub principal_outstanding : u64,
+    // funded by the pool as a whole (loan.lender = pool), exposure is shared by all lenders
+    pub pool_funded : bool,
+    // LenderOperator that approved the loan, default = the lender approved it directly
+    pub approving_operator : Pubkey,
+    // created_at of that delegation, a delegation re-created at the same address does not carry this loan's exposure
+    pub operator_created_at : i64,
+    // principal still counted in that operator's approved_principal, released as principal is repaid or written off
+    pub operator_exposure : u64,
+    // lender's offer reservation moved onto the loan by match_loan, released into the loan at disbursement
+    pub reserved_principal : u64,
     pub bump : u8
 
 
@@ -124,6 +146,7 @@ pub enum LoanStatus {
 
 impl Loan {
     pub const LEN: usize = 8 + // discriminator
+    1 + // version
     8 + // loan_id
     32 + // borrower
     32 + // lender
@@ -148,8 +171,29 @@ impl Loan {
     2 + // credit_score
     2 + // required_collateral_ratio
     8+
+    16 + // origination_borrow_index
+    16 + // borrow_index_snapshot
+    8 + // debt_snapshot
+    8 + // principal_outstanding
+    1 + // pool_funded
+    32 + // approving_operator
+    8 + // operator_created_at
+    8 + // operator_exposure
+    8 + // reserved_principal
     1; // bump
 
+    /// Exact outstanding debt at `borrow_index` (rounded up, in favour of the pool)
+    pub fn current_debt(&self, borrow_index: u128) -> Option<u64> {
+        if self.borrow_index_snapshot == 0 {
+            return Some(self.debt_snapshot);
+        }
+        (self.debt_snapshot as u128)
+            .checked_mul(borrow_index)?
+            .checked_add(self.borrow_index_snapshot - 1)?
+            .checked_div(self.borrow_index_snapshot)
+            .and_then(|v| u64::try_from(v).ok())
+    }
+
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -170,6 +214,9 @@ pub struct  NoirCreditProof{
 #[account]
 
 pub struct LoanCounter{
+    // layout version
+    pub version : u8,
+
     pub next_loan_id : u64,
 
     pub total_loans : u64,
@@ -181,6 +228,7 @@ pub struct LoanCounter{
 
 impl LoanCounter{
     pub const  LEN : usize = 8+ //discriminator
+        1+ // version
         8+
         8+
         8+
@@ -194,3 +242,141 @@ impl Loan {
 impl LoanCounter{
     pub const  SEED_PREFIX : &'static [u8] = b"loan_counter";
 }
+
+/// Hot key allowed to approve loans for a lender account within limits, revoked by closing it
+#[account]
+
+pub struct LenderOperator{
+    // layout version
+    pub version : u8,
+
+    pub lender : Pubkey,
+
+    pub lender_account : Pubkey,
+
+    pub operator : Pubkey,
+    // largest principal (upper end of the range) the operator can approve per loan
+    pub max_principal_per_loan : u64,
+    // cap on approved_principal
+    pub max_total_exposure : u64,
+    // principal of loans approved by this operator that is not yet repaid or written off
+    pub approved_principal : u64,
+    // lowest borrower credit score the operator can approve
+    pub min_credit_score : u16,
+    // 0 = never expires
+    pub expires_at : i64,
+
+    pub created_at : i64,
+
+    pub bump : u8
+}
+
+impl LenderOperator {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // lender
+    32 + // lender_account
+    32 + // operator
+    8 + // max_principal_per_loan
+    8 + // max_total_exposure
+    8 + // approved_principal
+    2 + // min_credit_score
+    8 + // expires_at
+    8 + // created_at
+    1; // bump
+
+    pub const SEED_PREFIX : &'static [u8] = b"lender_operator";
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// Lender's standing offer, matched automatically against pending loans
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LoanOffer{
+    pub offer_id : u64,
+
+    pub lender : Pubkey,
+
+    pub lender_account : Pubkey,
+    // largest principal (upper end of the range) per loan
+    pub max_amount : u64,
+    // budget left across all matched loans
+    pub remaining_amount : u64,
+
+    pub min_credit_score : u16,
+    // in seconds, like Loan.duration
+    pub max_duration : i64,
+    // loans quoted below this rate are not matched
+    pub min_rate_bps : u16,
+
+    pub created_at : i64,
+}
+
+impl LoanOffer {
+    pub const LEN: usize = 8 + // offer_id
+    32 + // lender
+    32 + // lender_account
+    8 + // max_amount
+    8 + // remaining_amount
+    2 + // min_credit_score
+    8 + // max_duration
+    2 + // min_rate_bps
+    8; // created_at
+
+    pub fn accepts(&self, loan: &Loan) -> bool {
+        let principal = loan.principal_range.1;
+        principal <= self.max_amount
+            && principal <= self.remaining_amount
+            && loan.credit_score >= self.min_credit_score
+            && loan.duration <= self.max_duration
+            && loan.interest_rate >= self.min_rate_bps
+    }
+}
+
+/// Standing lender offers for one pool
+#[account]
+
+pub struct OfferBook{
+    // layout version
+    pub version : u8,
+
+    pub pool : Pubkey,
+
+    pub next_offer_id : u64,
+
+    pub offers : Vec<LoanOffer>,
+
+    pub bump : u8
+}
+
+impl OfferBook {
+    pub const LEN: usize = 8 + // discriminator
+    1 + // version
+    32 + // pool
+    8 + // next_offer_id
+    (4 + LoanOffer::LEN * MAX_LOAN_OFFERS) + // offers
+    1; // bump
+
+    pub const SEED_PREFIX : &'static [u8] = b"offer_book";
+
+    pub fn is_full(&self) -> bool {
+        self.offers.len() >= MAX_LOAN_OFFERS
+    }
+
+    /// Standing offers `lender` holds in this book
+    pub fn offers_of(&self, lender: &Pubkey) -> usize {
+        self.offers.iter().filter(|o| o.lender == *lender).count()
+    }
+
+    /// Index of the best offer for `loan`: lowest minimum rate, oldest first on ties
+    pub fn best_offer(&self, loan: &Loan) -> Option<usize> {
+        self.offers
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.accepts(loan))
+            .min_by_key(|(_, o)| (o.min_rate_bps, o.offer_id))
+            .map(|(i, _)| i)
+    }
+}
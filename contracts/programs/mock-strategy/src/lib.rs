@@ -0,0 +1,260 @@
+// programs/mock-strategy/src/lib.rs
+// Test-only strategy implementing lending-pool's yield strategy interface
+// (deposit, withdraw, report_balance). It holds a depositor's funds in one token
+// account, anything sent to that account on top is the strategy's "yield". The depositor
+// is the pool's YieldStrategy PDA, it signs withdrawals, the vault pays under the state PDA.
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("HGh3X4qYu5a9ywqimDgShTDgAqjL5xXpHXHiFMxpghSC");
+
+#[program]
+pub mod mock_strategy {
+    use super::*;
+
+    /// Create the strategy state and token account for a depositor (the pool's YieldStrategy PDA)
+    pub fn initialize(
+        ctx: Context<InitializeStrategy>,
+        depositor: Pubkey,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.strategy_state;
+
+        state.depositor = depositor;
+        state.token_mint = ctx.accounts.token_mint.key();
+        state.vault = ctx.accounts.strategy_token_account.key();
+        state.total_deposited = 0;
+        state.total_withdrawn = 0;
+        state.bump = ctx.bumps.strategy_state;
+        Ok(())
+    }
+
+    /// The depositor already moved `amount` into the vault, only recorded here
+    pub fn deposit(
+        ctx: Context<StrategyDeposit>,
+        amount: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.strategy_state;
+        state.total_deposited = state.total_deposited.checked_add(amount).ok_or(MockStrategyError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw(
+        ctx: Context<StrategyWithdraw>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(ctx.accounts.strategy_token_account.amount>=amount,MockStrategyError::InsufficientBalance);
+
+        let depositor = ctx.accounts.depositor.key();
+        let seeds = &[
+            StrategyState::SEED_PREFIX,
+            depositor.as_ref(),
+            &[ctx.accounts.strategy_state.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_account = TransferChecked{
+            from : ctx.accounts.strategy_token_account.to_account_info(),
+            mint : ctx.accounts.token_mint.to_account_info(),
+            to : ctx.accounts.depositor_token_account.to_account_info(),
+            authority : ctx.accounts.strategy_state.to_account_info(),
+        };
+
+        token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), amount, ctx.accounts.token_mint.decimals)?;
+
+        let state = &mut ctx.accounts.strategy_state;
+        state.total_withdrawn = state.total_withdrawn.checked_add(amount).ok_or(MockStrategyError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Everything in the strategy token account belongs to the depositor
+    pub fn report_balance(
+        ctx: Context<ReportBalance>,
+    ) -> Result<u64> {
+        Ok(ctx.accounts.strategy_token_account.amount)
+    }
+
+    /// Test hook, moves `amount` out of the strategy so it reports less than was deposited
+    pub fn simulate_loss(
+        ctx: Context<SimulateLoss>,
+        amount: u64,
+    ) -> Result<()> {
+        let depositor = ctx.accounts.depositor.key();
+        let seeds = &[
+            StrategyState::SEED_PREFIX,
+            depositor.as_ref(),
+            &[ctx.accounts.strategy_state.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_account = TransferChecked{
+            from : ctx.accounts.strategy_token_account.to_account_info(),
+            mint : ctx.accounts.token_mint.to_account_info(),
+            to : ctx.accounts.recipient_token_account.to_account_info(),
+            authority : ctx.accounts.strategy_state.to_account_info(),
+        };
+
+        token_interface::transfer_checked(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_account, signer_seeds), amount, ctx.accounts.token_mint.decimals)
+    }
+}
+
+#[account]
+pub struct StrategyState{
+    pub depositor : Pubkey,
+    pub token_mint : Pubkey,
+    pub vault : Pubkey,
+    pub total_deposited : u64,
+    pub total_withdrawn : u64,
+    pub bump : u8
+}
+
+impl StrategyState {
+    pub const LEN: usize = 8 + // discriminator
+    32 + // depositor
+    32 + // token_mint
+    32 + // vault
+    8 + // total_deposited
+    8 + // total_withdrawn
+    1; // bump
+
+    pub const SEED_PREFIX: &'static [u8] = b"strategy";
+    pub const VAULT_SEED: &'static [u8] = b"strategy_vault";
+}
+
+#[derive(Accounts)]
+#[instruction(depositor: Pubkey)]
+pub struct InitializeStrategy<'info>{
+    #[account(mut)]
+    pub payer : Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = StrategyState::LEN,
+        seeds = [StrategyState::SEED_PREFIX, depositor.as_ref()],
+        bump
+    )]
+    pub strategy_state : Account<'info,StrategyState>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [StrategyState::VAULT_SEED, strategy_state.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = strategy_state,
+        token::token_program = token_program,
+    )]
+    pub strategy_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    pub token_program : Interface<'info,TokenInterface>,
+
+    pub system_program : Program<'info,System>,
+}
+
+// account order is fixed by the strategy interface
+#[derive(Accounts)]
+pub struct StrategyDeposit<'info>{
+    /// CHECK: only used to find the depositor's strategy state
+    pub depositor : UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [StrategyState::SEED_PREFIX, depositor.key().as_ref()],
+        bump = strategy_state.bump,
+    )]
+    pub strategy_state : Account<'info,StrategyState>,
+
+    #[account(
+        mut,
+        address = strategy_state.vault @ MockStrategyError::InvalidTokenAccount
+    )]
+    pub strategy_token_account : InterfaceAccount<'info,TokenAccount>,
+}
+
+// account order is fixed by the strategy interface
+#[derive(Accounts)]
+pub struct StrategyWithdraw<'info>{
+    pub depositor : Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [StrategyState::SEED_PREFIX, depositor.key().as_ref()],
+        bump = strategy_state.bump,
+    )]
+    pub strategy_state : Account<'info,StrategyState>,
+
+    #[account(
+        mut,
+        address = strategy_state.vault @ MockStrategyError::InvalidTokenAccount
+    )]
+    pub strategy_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.mint == strategy_state.token_mint @ MockStrategyError::InvalidTokenAccount
+    )]
+    pub depositor_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(address = strategy_state.token_mint @ MockStrategyError::InvalidTokenAccount)]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    pub token_program : Interface<'info,TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ReportBalance<'info>{
+    /// CHECK: only used to find the depositor's strategy state
+    pub depositor : UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [StrategyState::SEED_PREFIX, depositor.key().as_ref()],
+        bump = strategy_state.bump,
+    )]
+    pub strategy_state : Account<'info,StrategyState>,
+
+    #[account(address = strategy_state.vault @ MockStrategyError::InvalidTokenAccount)]
+    pub strategy_token_account : InterfaceAccount<'info,TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SimulateLoss<'info>{
+    /// CHECK: only used to find the depositor's strategy state
+    pub depositor : UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [StrategyState::SEED_PREFIX, depositor.key().as_ref()],
+        bump = strategy_state.bump,
+    )]
+    pub strategy_state : Account<'info,StrategyState>,
+
+    #[account(
+        mut,
+        address = strategy_state.vault @ MockStrategyError::InvalidTokenAccount
+    )]
+    pub strategy_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == strategy_state.token_mint @ MockStrategyError::InvalidTokenAccount
+    )]
+    pub recipient_token_account : InterfaceAccount<'info,TokenAccount>,
+
+    #[account(address = strategy_state.token_mint @ MockStrategyError::InvalidTokenAccount)]
+    pub token_mint : InterfaceAccount<'info,Mint>,
+
+    pub token_program : Interface<'info,TokenInterface>,
+}
+
+#[error_code]
+pub enum MockStrategyError {
+    #[msg("Token account does not belong to this strategy")]
+    InvalidTokenAccount,
+
+    #[msg("Strategy holds less than requested")]
+    InsufficientBalance,
+
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
@@ -0,0 +1,145 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+import { LendingPool } from "../target/types/lending_pool";
+import { MockStrategy } from "../target/types/mock_strategy";
+import { createPool, deposit, expectError, fundedKeypair, payer, TestPool, tokenAccount } from "./helpers";
+
+const UNIT = 1_000_000;
+const SHARE_PRICE_PRECISION = 1_000_000_000;
+
+// Lenders claim or compound the interest in their shares, only interest paid into the
+// pool counts. Interest is paid here by harvesting yield minted into mock-strategy
+describe("claiming interest", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingPool = anchor.workspace.lendingPool as Program<LendingPool>;
+  const mockStrategy = anchor.workspace.mockStrategy as Program<MockStrategy>;
+
+  let p: TestPool;
+  let lender: Keypair;
+  let lenderAccount: PublicKey;
+  let lenderTokenAccount: PublicKey;
+  let seniorShareAccount: PublicKey;
+  let juniorShareAccount: PublicKey;
+  let yieldStrategy: PublicKey;
+  let strategyState: PublicKey;
+  let strategyTokenAccount: PublicKey;
+
+  const balance = async (account: PublicKey) => Number((await getAccount(provider.connection, account)).amount);
+
+  before(async () => {
+    p = await createPool(lendingPool, provider, "claim");
+    lender = await fundedKeypair(provider);
+
+    seniorShareAccount = await deposit(lendingPool, provider, p, lender, 1_000 * UNIT, "senior");
+    juniorShareAccount = await deposit(lendingPool, provider, p, lender, 300 * UNIT, "junior");
+    lenderTokenAccount = await tokenAccount(provider, p.tokenMint, lender.publicKey);
+
+    [lenderAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lender_account"), p.pool.toBuffer(), lender.publicKey.toBuffer()],
+      lendingPool.programId
+    );
+    [yieldStrategy] = PublicKey.findProgramAddressSync([Buffer.from("yield_strategy"), p.pool.toBuffer()], lendingPool.programId);
+    [strategyState] = PublicKey.findProgramAddressSync([Buffer.from("strategy"), yieldStrategy.toBuffer()], mockStrategy.programId);
+    [strategyTokenAccount] = PublicKey.findProgramAddressSync([Buffer.from("strategy_vault"), strategyState.toBuffer()], mockStrategy.programId);
+
+    await mockStrategy.methods
+      .initialize(yieldStrategy)
+      .accountsPartial({ payer: provider.wallet.publicKey, strategyState, strategyTokenAccount, tokenMint: p.tokenMint, tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
+
+    await lendingPool.methods
+      .initializeYieldStrategy(5000)
+      .accountsPartial({ authority: provider.wallet.publicKey, pool: p.pool, strategyProgram: mockStrategy.programId, strategyState, strategyTokenAccount })
+      .rpc();
+  });
+
+  const claim = (amount: number, tranche: "senior" | "junior") =>
+    lendingPool.methods
+      .claimInterest(new BN(amount), { [tranche]: {} } as never)
+      .accountsPartial({
+        lender: lender.publicKey,
+        pool: p.pool,
+        tokenMint: p.tokenMint,
+        lenderTokenAccount,
+        poolTokenAccount: p.poolTokenAccount,
+        shareMint: tranche === "senior" ? p.shareMint : p.juniorShareMint,
+        lenderShareAccount: tranche === "senior" ? seniorShareAccount : juniorShareAccount,
+        yieldStrategy: null,
+        strategyProgram: null,
+        strategyState: null,
+        strategyTokenAccount: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lender])
+      .rpc();
+
+  it("has nothing to claim before interest is paid", async () => {
+    const account = await lendingPool.account.lenderAccount.fetch(lenderAccount);
+    expect(account.seniorRateCheckpoint.toNumber()).to.equal(SHARE_PRICE_PRECISION);
+    expect(account.juniorRateCheckpoint.toNumber()).to.equal(SHARE_PRICE_PRECISION);
+
+    await expectError(claim(0, "senior"), "ZeroClaimAmount");
+    await expectError(claim(1 * UNIT, "senior"), "InsufficientClaimableInterest");
+  });
+
+  it("claims interest paid into the pool", async () => {
+    await mintTo(provider.connection, payer(provider), p.tokenMint, strategyTokenAccount, payer(provider), 130 * UNIT);
+
+    await lendingPool.methods
+      .harvestStrategy()
+      .accountsPartial({
+        pool: p.pool,
+        yieldStrategy,
+        strategyProgram: mockStrategy.programId,
+        strategyState,
+        strategyTokenAccount,
+        tokenMint: p.tokenMint,
+        poolTokenAccount: p.poolTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const sharesBefore = await balance(seniorShareAccount);
+
+    await claim(10 * UNIT, "senior");
+
+    expect(await balance(lenderTokenAccount)).to.equal(10 * UNIT);
+    expect(await balance(seniorShareAccount)).to.be.lessThan(sharesBefore);
+
+    // the lender's principal is not interest
+    await expectError(claim(130 * UNIT, "senior"), "InsufficientClaimableInterest");
+  });
+
+  it("compounds junior interest into senior shares", async () => {
+    const juniorBefore = await balance(juniorShareAccount);
+    const seniorBefore = await balance(seniorShareAccount);
+    const poolBefore = await lendingPool.account.lendingPool.fetch(p.pool);
+
+    await lendingPool.methods
+      .compoundInterest(new BN(5 * UNIT), { senior: {} } as never)
+      .accountsPartial({
+        lender: lender.publicKey,
+        pool: p.pool,
+        sourceShareMint: p.juniorShareMint,
+        sourceShareAccount: juniorShareAccount,
+        shareMint: p.shareMint,
+        lenderShareAccount: seniorShareAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lender])
+      .rpc();
+
+    expect(await balance(juniorShareAccount)).to.be.lessThan(juniorBefore);
+    expect(await balance(seniorShareAccount)).to.be.greaterThan(seniorBefore);
+
+    // no tokens move, only the tranche split
+    const pool = await lendingPool.account.lendingPool.fetch(p.pool);
+    expect(pool.totalDeposits.toString()).to.equal(poolBefore.totalDeposits.toString());
+    expect(pool.juniorDeposits.toNumber()).to.equal(poolBefore.juniorDeposits.toNumber() - 5 * UNIT);
+  });
+});
@@ -0,0 +1,122 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+import { LendingPool } from "../target/types/lending_pool";
+import { createPool, deposit, expectError, fundedKeypair, TestPool, tokenAccount } from "./helpers";
+
+const UNIT = 1_000_000;
+
+// exit_pool redeems both tranches before closing the lender account, neither
+// it nor close_lender_account lets a lender leave with a queued request
+describe("exiting a pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingPool = anchor.workspace.lendingPool as Program<LendingPool>;
+
+  let p: TestPool;
+  let lender: Keypair;
+  let seniorShareAccount: PublicKey;
+  let juniorShareAccount: PublicKey;
+  let lenderTokenAccount: PublicKey;
+  let withdrawalQueue: PublicKey;
+  let lenderAccount: PublicKey;
+
+  const balance = async (account: PublicKey) => Number((await getAccount(provider.connection, account)).amount);
+
+  before(async () => {
+    p = await createPool(lendingPool, provider, "exit");
+    lender = await fundedKeypair(provider);
+
+    seniorShareAccount = await deposit(lendingPool, provider, p, lender, 500 * UNIT, "senior");
+    juniorShareAccount = await deposit(lendingPool, provider, p, lender, 300 * UNIT, "junior");
+    lenderTokenAccount = await tokenAccount(provider, p.tokenMint, lender.publicKey);
+
+    [withdrawalQueue] = PublicKey.findProgramAddressSync([Buffer.from("withdrawal_queue"), p.pool.toBuffer()], lendingPool.programId);
+    [lenderAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lender_account"), p.pool.toBuffer(), lender.publicKey.toBuffer()],
+      lendingPool.programId
+    );
+  });
+
+  const close = () =>
+    lendingPool.methods
+      .closeLenderAccount()
+      .accountsPartial({ lender: lender.publicKey, pool: p.pool, withdrawalQueue })
+      .signers([lender])
+      .rpc();
+
+  const exit = () =>
+    lendingPool.methods
+      .exitPool()
+      .accountsPartial({
+        lender: lender.publicKey,
+        pool: p.pool,
+        tokenMint: p.tokenMint,
+        lenderTokenAccount,
+        poolTokenAccount: p.poolTokenAccount,
+        seniorShareMint: p.shareMint,
+        seniorShareAccount,
+        juniorShareMint: p.juniorShareMint,
+        juniorShareAccount,
+        withdrawalQueue,
+        yieldStrategy: null,
+        strategyProgram: null,
+        strategyState: null,
+        strategyTokenAccount: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lender])
+      .rpc();
+
+  it("keeps the account of a lender with a queued withdrawal", async () => {
+    await lendingPool.methods
+      .requestWithdrawal(new BN(100 * UNIT))
+      .accountsPartial({
+        lender: lender.publicKey,
+        pool: p.pool,
+        shareMint: p.shareMint,
+        lenderShareAccount: seniorShareAccount,
+        lenderTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lender])
+      .rpc();
+
+    await expectError(exit(), "WithdrawalRequestPending");
+    await expectError(close(), "WithdrawalRequestPending");
+
+    await lendingPool.methods
+      .processWithdrawalQueue(1)
+      .accountsPartial({
+        pool: p.pool,
+        shareMint: p.shareMint,
+        tokenMint: p.tokenMint,
+        poolTokenAccount: p.poolTokenAccount,
+        yieldStrategy: null,
+        strategyProgram: null,
+        strategyState: null,
+        strategyTokenAccount: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([{ pubkey: lenderTokenAccount, isWritable: true, isSigner: false }])
+      .rpc();
+
+    expect(await balance(lenderTokenAccount)).to.equal(100 * UNIT);
+  });
+
+  it("redeems both tranches and closes the account", async () => {
+    await exit();
+
+    expect(await balance(seniorShareAccount)).to.equal(0);
+    expect(await balance(juniorShareAccount)).to.equal(0);
+    expect(await balance(lenderTokenAccount)).to.equal(800 * UNIT);
+    expect(await provider.connection.getAccountInfo(lenderAccount)).to.be.null;
+
+    const pool = await lendingPool.account.lendingPool.fetch(p.pool);
+    expect(pool.totalDeposits.toNumber()).to.equal(0);
+    expect(pool.juniorDeposits.toNumber()).to.equal(0);
+  });
+});
@@ -0,0 +1,115 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+import { LendingPool } from "../target/types/lending_pool";
+
+// Helpers shared by the test files, the protocol roles account is a singleton so
+// whichever file runs first creates it with the provider wallet as admin
+
+export const rolesPda = (lendingPool: Program<LendingPool>) =>
+  PublicKey.findProgramAddressSync([Buffer.from("protocol_roles")], lendingPool.programId)[0];
+
+export const ensureRoles = async (lendingPool: Program<LendingPool>, provider: anchor.AnchorProvider) => {
+  const roles = rolesPda(lendingPool);
+  if (!(await provider.connection.getAccountInfo(roles))) {
+    await lendingPool.methods.initializeRoles().accounts({ admin: provider.wallet.publicKey }).rpc();
+  }
+  return roles;
+};
+
+export const payer = (provider: anchor.AnchorProvider) => (provider.wallet as anchor.Wallet).payer;
+
+export const grantFreshRole = async (lendingPool: Program<LendingPool>, provider: anchor.AnchorProvider, role: object) => {
+  const member = Keypair.generate();
+  await lendingPool.methods.grantRole(role as never, member.publicKey).accounts({ admin: provider.wallet.publicKey }).rpc();
+  return member;
+};
+
+export const fundedKeypair = async (provider: anchor.AnchorProvider) => {
+  const keypair = Keypair.generate();
+  const sig = await provider.connection.requestAirdrop(keypair.publicKey, 10 * LAMPORTS_PER_SOL);
+  await provider.connection.confirmTransaction(sig, "confirmed");
+  return keypair;
+};
+
+// token account of `owner` holding `amount` freshly minted tokens, the provider wallet is every test mint's authority
+export const tokenAccount = async (provider: anchor.AnchorProvider, mint: PublicKey, owner: PublicKey, amount = 0) => {
+  const account = await getOrCreateAssociatedTokenAccount(provider.connection, payer(provider), mint, owner, true);
+  if (amount > 0) {
+    await mintTo(provider.connection, payer(provider), mint, account.address, payer(provider), amount);
+  }
+  return account.address;
+};
+
+export type TestPool = {
+  tokenMint: PublicKey;
+  pool: PublicKey;
+  poolTokenAccount: PublicKey;
+  shareMint: PublicKey;
+  juniorShareMint: PublicKey;
+  interestRateModel: PublicKey;
+};
+
+// every pool gets its own 6 decimals mint, so test files never share a pool
+export const createPool = async (lendingPool: Program<LendingPool>, provider: anchor.AnchorProvider, name: string): Promise<TestPool> => {
+  const tokenMint = await createMint(provider.connection, payer(provider), provider.wallet.publicKey, null, 6);
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, lendingPool.programId)[0];
+  const pool = pda(Buffer.from("lending_pool"), tokenMint.toBuffer());
+  const poolTokenAccount = await tokenAccount(provider, tokenMint, pool);
+
+  await lendingPool.methods
+    .initializePool(name)
+    .accountsPartial({ authority: provider.wallet.publicKey, pool, tokenMint, poolTokenAccount, tokenProgram: TOKEN_PROGRAM_ID })
+    .rpc();
+
+  return {
+    tokenMint,
+    pool,
+    poolTokenAccount,
+    shareMint: pda(Buffer.from("share_mint"), pool.toBuffer()),
+    juniorShareMint: pda(Buffer.from("junior_share_mint"), pool.toBuffer()),
+    interestRateModel: pda(Buffer.from("interest_rate_model"), pool.toBuffer()),
+  };
+};
+
+// mints `amount` to `lender` and deposits it, returns the lender's share account of the tranche
+export const deposit = async (
+  lendingPool: Program<LendingPool>,
+  provider: anchor.AnchorProvider,
+  p: TestPool,
+  lender: Keypair,
+  amount: number,
+  tranche: "senior" | "junior"
+) => {
+  const shareMint = tranche === "senior" ? p.shareMint : p.juniorShareMint;
+  const lenderShareAccount = await tokenAccount(provider, shareMint, lender.publicKey);
+
+  await lendingPool.methods
+    .deposit(new BN(amount), { [tranche]: {} } as never)
+    .accountsPartial({
+      lender: lender.publicKey,
+      pool: p.pool,
+      tokenMint: p.tokenMint,
+      lenderTokenAccount: await tokenAccount(provider, p.tokenMint, lender.publicKey, amount),
+      shareMint,
+      lenderShareAccount,
+      poolTokenAccount: p.poolTokenAccount,
+      attestation: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([lender])
+    .rpc();
+
+  return lenderShareAccount;
+};
+
+export const expectError = async (call: Promise<unknown>, code: string) => {
+  try {
+    await call;
+    expect.fail(`expected ${code}`);
+  } catch (err) {
+    expect(err.error?.errorCode?.code, String(err)).to.equal(code);
+  }
+};
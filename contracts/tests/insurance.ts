@@ -0,0 +1,256 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { createMint, getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { createHash } from "crypto";
+import { expect } from "chai";
+import { LendingPool } from "../target/types/lending_pool";
+import { LoanManager } from "../target/types/loan_manager";
+import { createPool, deposit, ensureRoles, expectError, fundedKeypair, grantFreshRole, payer, TestPool, tokenAccount } from "./helpers";
+
+const UNIT = 1_000_000;
+const STAKE_ACCUMULATOR_PRECISION = new BN(10).pow(new BN(18));
+
+// loan-manager's LoanCounter is created by the first request_loan, so this file
+// runs the only loan of the test validator: loan #0, funded by the pool, kept open
+// while liquidations are paused, then defaulted and written off with less
+// insurance than the loss. The uncovered principal is charged to lender stakes
+describe("bad debt waterfall", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingPool = anchor.workspace.lendingPool as Program<LendingPool>;
+  const loanManager = anchor.workspace.loanManager as Program<LoanManager>;
+
+  const loanId = new BN(0);
+  const loan = PublicKey.findProgramAddressSync([Buffer.from("loan"), loanId.toArrayLike(Buffer, "le", 8)], loanManager.programId)[0];
+
+  const principal = 1_000 * UNIT;
+  const insured = 400 * UNIT;
+  const collateral = 600_000 * UNIT;
+  const collateralSecret = Keypair.generate().publicKey.toBuffer();
+
+  let roles: PublicKey;
+  let riskManager: Keypair;
+  let monitor: Keypair;
+  let pauser: Keypair;
+  let p: TestPool;
+  let insuranceFund: PublicKey;
+  let insuranceVault: PublicKey;
+
+  before(async () => {
+    roles = await ensureRoles(lendingPool, provider);
+    riskManager = await grantFreshRole(lendingPool, provider, { riskManager: {} });
+    monitor = await grantFreshRole(lendingPool, provider, { healthMonitor: {} });
+    pauser = await grantFreshRole(lendingPool, provider, { pauser: {} });
+
+    p = await createPool(lendingPool, provider, "waterfall");
+
+    const poolRegistry = PublicKey.findProgramAddressSync([Buffer.from("pool_registry")], lendingPool.programId)[0];
+    if (!(await provider.connection.getAccountInfo(poolRegistry))) {
+      await lendingPool.methods.initializeRegistry().accounts({ authority: provider.wallet.publicKey }).rpc();
+    }
+    await lendingPool.methods
+      .listMarket(new BN(2_000_000 * UNIT), 0)
+      .accountsPartial({ riskManager: riskManager.publicKey, roles, pool: p.pool, tokenMint: p.tokenMint })
+      .signers([riskManager])
+      .rpc();
+
+    // pool-funded loans need the largest principal of the request in unreserved liquidity
+    await deposit(lendingPool, provider, p, await fundedKeypair(provider), 10_000 * UNIT, "senior");
+    await deposit(lendingPool, provider, p, await fundedKeypair(provider), 100_000 * UNIT, "junior");
+
+    [insuranceFund] = PublicKey.findProgramAddressSync([Buffer.from("insurance_fund"), p.pool.toBuffer()], lendingPool.programId);
+    [insuranceVault] = PublicKey.findProgramAddressSync([Buffer.from("insurance_vault"), p.pool.toBuffer()], lendingPool.programId);
+
+    await lendingPool.methods
+      .initializeInsuranceFund()
+      .accountsPartial({ authority: provider.wallet.publicKey, pool: p.pool, tokenMint: p.tokenMint, tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
+    await lendingPool.methods
+      .contributeInsurance(new BN(insured))
+      .accountsPartial({
+        contributor: provider.wallet.publicKey,
+        pool: p.pool,
+        tokenMint: p.tokenMint,
+        contributorTokenAccount: await tokenAccount(provider, p.tokenMint, provider.wallet.publicKey, insured),
+        insuranceVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // request, fund from the pool, disburse
+    const collateralMint = await createMint(provider.connection, payer(provider), provider.wallet.publicKey, null, 6);
+    const amountLe = new BN(collateral).toArrayLike(Buffer, "le", 8);
+    const collateralCommitment = createHash("sha256").update(collateralSecret).update(amountLe).digest();
+
+    const scoreCommitment = Buffer.alloc(32, 1);
+    scoreCommitment.writeUInt16LE(900, 0);
+    const publicInputs = Buffer.alloc(2);
+    publicInputs.writeUInt16LE(700, 0);
+
+    await loanManager.methods
+      .requestLoan(
+        [...Keypair.generate().publicKey.toBytes()],
+        [...Keypair.generate().publicKey.toBytes()],
+        [new BN(100 * UNIT), new BN(2_000 * UNIT)],
+        [...collateralCommitment],
+        [...collateralSecret],
+        [new BN(collateral), new BN(collateral)],
+        30,
+        {
+          proof: Buffer.alloc(64, 1),
+          publicInputs,
+          scoreCommitment: [...scoreCommitment],
+          timestamp: new BN(Math.floor(Date.now() / 1000) - 60),
+        },
+        700
+      )
+      .accountsPartial({
+        borrower: provider.wallet.publicKey,
+        pool: p.pool,
+        interestRateModel: p.interestRateModel,
+        collateralMint,
+        loan,
+        attestation: null,
+      })
+      .rpc();
+
+    await loanManager.methods
+      .fundLoanFromPool(loanId)
+      .accountsPartial({ riskManager: riskManager.publicKey, roles, loan, pool: p.pool })
+      .signers([riskManager])
+      .rpc();
+
+    await loanManager.methods
+      .disburseLoan(loanId, new BN(principal), new BN(collateral), [...Buffer.alloc(32)], [...collateralSecret])
+      .accountsPartial({
+        borrower: provider.wallet.publicKey,
+        loan,
+        pool: p.pool,
+        interestRateModel: p.interestRateModel,
+        lenderAccount: null,
+        lenderShareAccount: null,
+        borrowerCollateralAccount: await tokenAccount(provider, collateralMint, provider.wallet.publicKey, collateral),
+        collateralVault: await tokenAccount(provider, collateralMint, loan),
+        borrowerTokenAccount: await tokenAccount(provider, p.tokenMint, provider.wallet.publicKey),
+        poolTokenAccount: p.poolTokenAccount,
+        tokenMint: p.tokenMint,
+        collateralMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        yieldStrategy: null,
+        strategyProgram: null,
+        strategyState: null,
+        strategyTokenAccount: null,
+        collateralTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  });
+
+  // collateral values differ so the two updates are not the same transaction
+  const updateHealth = (collateralValue: number) =>
+    loanManager.methods
+      .updateHealthFactor(loanId, new BN(collateralValue), 90)
+      .accountsPartial({ monitor: monitor.publicKey, roles, loan, pool: p.pool })
+      .signers([monitor])
+      .rpc();
+
+  it("accepts a request below the global loan cap", async () => {
+    const account = await loanManager.account.loan.fetch(loan);
+
+    expect(account.loanId.toNumber()).to.equal(0);
+    expect(account.borrower.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+    expect(account.status).to.deep.equal({ active: {} });
+  });
+
+  it("does not default a loan while liquidations are paused", async () => {
+    const flags = {
+      deposits: false,
+      withdrawals: false,
+      loanRequests: false,
+      disbursements: false,
+      repayments: false,
+      liquidations: true,
+      flashLoans: false,
+    };
+
+    await lendingPool.methods.pausePool(flags).accountsPartial({ pauser: pauser.publicKey, roles, pool: p.pool }).signers([pauser]).rpc();
+    await updateHealth(1);
+
+    let account = await loanManager.account.loan.fetch(loan);
+    expect(account.status).to.deep.equal({ active: {} });
+    expect(account.warningSent).to.equal(true);
+    expect((await lendingPool.account.lendingPool.fetch(p.pool)).pendingDefaults).to.equal(0);
+
+    await lendingPool.methods.unpausePool(flags).accountsPartial({ pauser: pauser.publicKey, roles, pool: p.pool }).signers([pauser]).rpc();
+    await updateHealth(0);
+
+    account = await loanManager.account.loan.fetch(loan);
+    expect(account.status).to.deep.equal({ defaulted: {} });
+  });
+
+  it("writes the whole debt off", async () => {
+    await loanManager.methods
+      .writeOffLoan(loanId)
+      .accountsPartial({
+        riskManager: riskManager.publicKey,
+        roles,
+        loan,
+        pool: p.pool,
+        interestRateModel: p.interestRateModel,
+        lenderAccount: null,
+        lenderOperator: null,
+      })
+      .signers([riskManager])
+      .rpc();
+
+    const pool = await lendingPool.account.lendingPool.fetch(p.pool);
+    expect(pool.badDebt.toNumber()).to.be.greaterThan(0);
+    expect(pool.badDebtStakeLoss.toNumber()).to.equal(principal);
+    expect(pool.poolFundedPrincipal.toNumber()).to.equal(0);
+  });
+
+  const absorb = (vault: PublicKey | null) =>
+    lendingPool.methods
+      .absorbBadDebt()
+      .accountsPartial({
+        pool: p.pool,
+        interestRateModel: p.interestRateModel,
+        insuranceFund,
+        insuranceVault: vault,
+        tokenMint: p.tokenMint,
+        poolTokenAccount: p.poolTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+  it("cannot skip the pool's insurance fund", async () => {
+    await expectError(absorb(null), "InsuranceVaultRequired");
+    await expectError(absorb(await tokenAccount(provider, p.tokenMint, provider.wallet.publicKey)), "ConstraintSeeds");
+  });
+
+  it("covers from insurance first, then socializes the rest junior first", async () => {
+    const before = await lendingPool.account.lendingPool.fetch(p.pool);
+    const loss = before.badDebt.toNumber();
+    expect(loss).to.be.greaterThan(insured);
+
+    await absorb(insuranceVault);
+
+    const after = await lendingPool.account.lendingPool.fetch(p.pool);
+    const fund = await lendingPool.account.insuranceFund.fetch(insuranceFund);
+    const shortfall = loss - insured;
+
+    expect(after.badDebt.toNumber()).to.equal(0);
+    expect(fund.totalCovered.toNumber()).to.equal(insured);
+    expect(Number((await getAccount(provider.connection, insuranceVault)).amount)).to.equal(0);
+    expect(after.availableLiquidity.toNumber()).to.equal(before.availableLiquidity.toNumber() + insured);
+    expect(after.juniorDeposits.toNumber()).to.equal(before.juniorDeposits.toNumber() - shortfall);
+    expect(after.totalDeposits.toNumber()).to.equal(before.totalDeposits.toNumber() - shortfall);
+
+    // the principal's uncovered part is spread over both depositors' stakes
+    const stakeLoss = new BN(principal).mul(new BN(shortfall)).div(new BN(loss));
+    expect(after.totalLenderStake.toNumber()).to.equal(110_000 * UNIT);
+    expect(after.lossPerStake.toString()).to.equal(stakeLoss.mul(STAKE_ACCUMULATOR_PRECISION).div(after.totalLenderStake).toString());
+    expect(after.badDebtStakeLoss.toNumber()).to.equal(0);
+  });
+});
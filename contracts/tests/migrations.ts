@@ -0,0 +1,163 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import { LendingPool } from "../target/types/lending_pool";
+import { LoanManager } from "../target/types/loan_manager";
+import { CollateralVault } from "../target/types/collateral_vault";
+import { CreditScorer } from "../target/types/credit_scorer";
+
+// Accounts in tests/fixtures were written with the baseline layouts, from before
+// the version byte (version 0), they are loaded by the validator (see Anchor.toml)
+const LENDING_POOL_V0 = new PublicKey("EPqbe741s3uCBmVaUMMPqagyTBRJq69XJ4UqYxfETBK2");
+const LOAN_V0 = new PublicKey("WvzrjxuwCPEBVcsTGDuDg1RECLbj6WeogYjrZFff8DE");
+const COLLATERAL_LOCK_V0 = new PublicKey("7eXCvjsD6XYLfshxTxZphEy1AuKamnGZjTezFrHS9jrN");
+const CREDIT_SCORE_V0 = new PublicKey("8Y6f7zngBrvQV72C2wBLoDjtmSjdNMXuDCeBb6t3ACH9");
+
+const AUTHORITY = new PublicKey("Af2Y56WUFQuTTTYHMCjMozYsDxvTvSM6YQnyv8E6EK3v");
+const TOKEN_MINT = new PublicKey("FCoJCr7KE8ghAiDNSMYcuotaznyYpYi9DEGb9yXCXw6G");
+const BORROWER = new PublicKey("CXUmGwgtVPWb5soPiaE5VJYWKz8MeJ9WHL6iAbmKPhvP");
+const LENDER = new PublicKey("AaKp4239hqK45EwYm4gUVXevFzE5JC7iKNdxGhZWvHvG");
+const COLLATERAL_MINT = new PublicKey("5YnjgsAzJMQQGcKhxV98UM6XmhkGWAk2uRUohtU1xDHs");
+
+const ACCOUNT_VERSION = 1;
+
+describe("account migrations", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingPool = anchor.workspace.lendingPool as Program<LendingPool>;
+  const loanManager = anchor.workspace.loanManager as Program<LoanManager>;
+  const collateralVault = anchor.workspace.collateralVault as Program<CollateralVault>;
+  const creditScorer = anchor.workspace.creditScorer as Program<CreditScorer>;
+
+  const expectAlreadyMigrated = async (call: Promise<string>) => {
+    try {
+      await call;
+      expect.fail("second migration should fail");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("AccountAlreadyMigrated");
+    }
+  };
+
+  it("migrates a v0 lending pool", async () => {
+    const before = await provider.connection.getAccountInfo(LENDING_POOL_V0);
+
+    await lendingPool.methods
+      .migrateLendingPool()
+      .accounts({ payer: provider.wallet.publicKey, account: LENDING_POOL_V0 })
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(LENDING_POOL_V0);
+    expect(before.data.length).to.equal(208);
+    expect(after.data.length).to.be.greaterThan(before.data.length);
+
+    const pool = await lendingPool.account.lendingPool.fetch(LENDING_POOL_V0);
+    expect(pool.version).to.equal(ACCOUNT_VERSION);
+    expect(pool.authority.toBase58()).to.equal(AUTHORITY.toBase58());
+    expect(pool.tokenMint.toBase58()).to.equal(TOKEN_MINT.toBase58());
+    expect(pool.poolName).to.equal("Legacy USDC Pool");
+    expect(pool.totalDeposits.toString()).to.equal("5000000000");
+    expect(pool.availableLiquidity.toString()).to.equal("3500000000");
+    expect(pool.totalBorrowed.toString()).to.equal("1500000000");
+    expect(pool.cumulativeInterest.toString()).to.equal("120000000");
+    // baseline deposits were not tokenized, new fields start as a new pool's would
+    expect(pool.totalShares.toNumber()).to.equal(0);
+    expect(pool.shareMint.toBase58()).to.equal(PublicKey.default.toBase58());
+    expect(pool.borrowIndex.toString()).to.equal("1000000000000000000");
+    expect(pool.config.minDepositAmount.toString()).to.equal("100000000");
+    expect(pool.flashLoanFeeBps).to.equal(9);
+    // the baseline pause flag paused everything
+    expect(pool.pauseFlags.deposits).to.equal(true);
+    expect(pool.pauseFlags.repayments).to.equal(true);
+    expect(pool.activeLoansCount).to.equal(3);
+    expect(pool.totalLoansCount).to.equal(11);
+    expect(pool.createdAt.toString()).to.equal("1690000000");
+    expect(pool.bump).to.equal(254);
+
+    await expectAlreadyMigrated(
+      lendingPool.methods
+        .migrateLendingPool()
+        .accounts({ payer: provider.wallet.publicKey, account: LENDING_POOL_V0 })
+        .rpc()
+    );
+  });
+
+  it("migrates a v0 loan", async () => {
+    await loanManager.methods
+      .migrateLoan()
+      .accounts({ payer: provider.wallet.publicKey, account: LOAN_V0 })
+      .rpc();
+
+    const loan = await loanManager.account.loan.fetch(LOAN_V0);
+    expect(loan.version).to.equal(ACCOUNT_VERSION);
+    expect(loan.loanId.toString()).to.equal("7");
+    expect(loan.borrower.toBase58()).to.equal(BORROWER.toBase58());
+    expect(loan.lender.toBase58()).to.equal(LENDER.toBase58());
+    expect(loan.collateralMint.toBase58()).to.equal(COLLATERAL_MINT.toBase58());
+    expect(loan.status).to.deep.equal({ active: {} });
+    expect(loan.interestRate).to.equal(800);
+    expect(loan.usedNullifier.length).to.equal(2);
+    expect(loan.creditScore).to.equal(750);
+    expect(loan.totalRepaid.toString()).to.equal("100000000");
+    // middle of principal_range less what was repaid, owed without an index snapshot
+    expect(loan.debtSnapshot.toString()).to.equal("1400000000");
+    expect(loan.principalOutstanding.toString()).to.equal("1400000000");
+    expect(loan.borrowIndexSnapshot.toString()).to.equal("0");
+    expect(loan.poolFunded).to.equal(false);
+    expect(loan.bump).to.equal(253);
+
+    await expectAlreadyMigrated(
+      loanManager.methods
+        .migrateLoan()
+        .accounts({ payer: provider.wallet.publicKey, account: LOAN_V0 })
+        .rpc()
+    );
+  });
+
+  it("migrates a v0 collateral lock", async () => {
+    await collateralVault.methods
+      .migrateCollateralLock()
+      .accounts({ payer: provider.wallet.publicKey, account: COLLATERAL_LOCK_V0 })
+      .rpc();
+
+    const lock = await collateralVault.account.collateralLock.fetch(COLLATERAL_LOCK_V0);
+    expect(lock.version).to.equal(ACCOUNT_VERSION);
+    expect(lock.loanId.toString()).to.equal("7");
+    expect(lock.borrower.toBase58()).to.equal(BORROWER.toBase58());
+    expect(lock.amount.toString()).to.equal("2000000000");
+    expect(lock.status).to.deep.equal({ locked: {} });
+    expect(lock.initialHealthFactor).to.equal(150);
+    expect(lock.currentHealthFactor).to.equal(140);
+    expect(lock.bump).to.equal(252);
+  });
+
+  it("migrates a v0 credit score", async () => {
+    await creditScorer.methods
+      .migrateCreditScore()
+      .accounts({ payer: provider.wallet.publicKey, account: CREDIT_SCORE_V0 })
+      .rpc();
+
+    const score = await creditScorer.account.creditScore.fetch(CREDIT_SCORE_V0);
+    expect(score.version).to.equal(ACCOUNT_VERSION);
+    expect(score.wallet.toBase58()).to.equal(BORROWER.toBase58());
+    expect(score.score).to.equal(750);
+    expect(score.status).to.deep.equal({ completed: {} });
+    expect(Array.from(score.scoreData)).to.deep.equal([1, 2, 3]);
+    expect(score.expiresAt.toString()).to.equal("1700043300");
+    expect(score.usageCount).to.equal(2);
+    expect(score.bump).to.equal(251);
+  });
+
+  it("rejects an account of another type", async () => {
+    try {
+      await lendingPool.methods
+        .migrateLenderAccount()
+        .accounts({ payer: provider.wallet.publicKey, account: LENDING_POOL_V0 })
+        .rpc();
+      expect.fail("a lending pool is not a lender account");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidAccountLayout");
+    }
+  });
+});
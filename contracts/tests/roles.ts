@@ -0,0 +1,109 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { LendingPool } from "../target/types/lending_pool";
+import { LoanManager } from "../target/types/loan_manager";
+import { CollateralVault } from "../target/types/collateral_vault";
+import { ensureRoles, expectError } from "./helpers";
+
+// loan-manager and collateral-vault read lending-pool's ProtocolRoles account, a
+// holder of the role gets past the roles check and fails on the (missing) loan or lock
+describe("protocol roles in other programs", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingPool = anchor.workspace.lendingPool as Program<LendingPool>;
+  const loanManager = anchor.workspace.loanManager as Program<LoanManager>;
+  const collateralVault = anchor.workspace.collateralVault as Program<CollateralVault>;
+
+  const monitor = Keypair.generate();
+  const keeper = Keypair.generate();
+  const riskManager = Keypair.generate();
+  const missingLoanId = new BN(9_999_999);
+
+  let roles: anchor.web3.PublicKey;
+
+  before(async () => {
+    roles = await ensureRoles(lendingPool, provider);
+  });
+
+  const updateHealth = (signer: Keypair) =>
+    loanManager.methods
+      .updateHealthFactor(missingLoanId, new BN(1_000), 90)
+      .accountsPartial({ monitor: signer.publicKey, roles, pool: Keypair.generate().publicKey })
+      .signers([signer])
+      .rpc();
+
+  const writeOff = (signer: Keypair) =>
+    loanManager.methods
+      .writeOffLoan(missingLoanId)
+      .accountsPartial({
+        riskManager: signer.publicKey,
+        roles,
+        pool: Keypair.generate().publicKey,
+        interestRateModel: Keypair.generate().publicKey,
+        lenderAccount: null,
+      })
+      .signers([signer])
+      .rpc();
+
+  const vaultAccounts = {
+    collateralLock: Keypair.generate().publicKey,
+    vault: Keypair.generate().publicKey,
+    collateralMint: Keypair.generate().publicKey,
+    tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+  };
+
+  const release = (signer: Keypair) =>
+    collateralVault.methods
+      .releaseCollateral(missingLoanId)
+      .accountsPartial({
+        ...vaultAccounts,
+        roles,
+        monitor: signer.publicKey,
+        borrowerCollateralAccount: Keypair.generate().publicKey,
+        vaultTokenAccount: Keypair.generate().publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+  const liquidate = (signer: Keypair) =>
+    collateralVault.methods
+      .liquidateCollateral(missingLoanId)
+      .accountsPartial({
+        ...vaultAccounts,
+        roles,
+        keeper: signer.publicKey,
+        lenderCollateralAccount: Keypair.generate().publicKey,
+        vaultTokenAccont: Keypair.generate().publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+  it("rejects signers without the role", async () => {
+    await expectError(updateHealth(monitor), "MissingRole");
+    await expectError(writeOff(riskManager), "MissingRole");
+    await expectError(release(monitor), "MissingRole");
+    await expectError(liquidate(keeper), "MissingRole");
+  });
+
+  it("accepts role holders", async () => {
+    await lendingPool.methods
+      .grantRole({ healthMonitor: {} }, monitor.publicKey)
+      .accounts({ admin: provider.wallet.publicKey })
+      .rpc();
+    await lendingPool.methods
+      .grantRole({ liquidatorKeeper: {} }, keeper.publicKey)
+      .accounts({ admin: provider.wallet.publicKey })
+      .rpc();
+    await lendingPool.methods
+      .grantRole({ riskManager: {} }, riskManager.publicKey)
+      .accounts({ admin: provider.wallet.publicKey })
+      .rpc();
+
+    await expectError(updateHealth(monitor), "AccountNotInitialized");
+    await expectError(writeOff(riskManager), "AccountNotInitialized");
+    await expectError(release(monitor), "AccountNotInitialized");
+    await expectError(liquidate(keeper), "AccountNotInitialized");
+  });
+});
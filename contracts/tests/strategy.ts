@@ -0,0 +1,162 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+import { LendingPool } from "../target/types/lending_pool";
+import { MockStrategy } from "../target/types/mock_strategy";
+import { createPool, deposit, expectError, fundedKeypair, TestPool, tokenAccount } from "./helpers";
+
+const UNIT = 1_000_000;
+
+// Pool funds deployed in mock-strategy come back when a payout finds the pool
+// token account short, and a strategy loss is booked as bad debt and absorbed
+describe("yield strategy", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingPool = anchor.workspace.lendingPool as Program<LendingPool>;
+  const mockStrategy = anchor.workspace.mockStrategy as Program<MockStrategy>;
+
+  let p: TestPool;
+  let senior: Keypair;
+  let junior: Keypair;
+  let yieldStrategy: PublicKey;
+  let strategyState: PublicKey;
+  let strategyTokenAccount: PublicKey;
+
+  const balance = async (account: PublicKey) => Number((await getAccount(provider.connection, account)).amount);
+  const deployed = async () => (await lendingPool.account.yieldStrategy.fetch(yieldStrategy)).deployed.toNumber();
+
+  const strategyAccounts = () => ({
+    strategyProgram: mockStrategy.programId,
+    strategyState,
+    strategyTokenAccount,
+  });
+
+  before(async () => {
+    p = await createPool(lendingPool, provider, "strategy");
+    senior = await fundedKeypair(provider);
+    junior = await fundedKeypair(provider);
+
+    await deposit(lendingPool, provider, p, senior, 1_000 * UNIT, "senior");
+    await deposit(lendingPool, provider, p, junior, 300 * UNIT, "junior");
+
+    [yieldStrategy] = PublicKey.findProgramAddressSync([Buffer.from("yield_strategy"), p.pool.toBuffer()], lendingPool.programId);
+    // the strategy knows the pool by its YieldStrategy PDA, the pool PDA never signs into it
+    [strategyState] = PublicKey.findProgramAddressSync([Buffer.from("strategy"), yieldStrategy.toBuffer()], mockStrategy.programId);
+    [strategyTokenAccount] = PublicKey.findProgramAddressSync([Buffer.from("strategy_vault"), strategyState.toBuffer()], mockStrategy.programId);
+
+    await mockStrategy.methods
+      .initialize(yieldStrategy)
+      .accountsPartial({ payer: provider.wallet.publicKey, strategyState, strategyTokenAccount, tokenMint: p.tokenMint, tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
+
+    await lendingPool.methods
+      .initializeYieldStrategy(5000)
+      .accountsPartial({ authority: provider.wallet.publicKey, pool: p.pool, ...strategyAccounts() })
+      .rpc();
+
+    await lendingPool.methods
+      .allocateToStrategy(new BN(600 * UNIT))
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        pool: p.pool,
+        ...strategyAccounts(),
+        tokenMint: p.tokenMint,
+        poolTokenAccount: p.poolTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  });
+
+  const exit = async (lender: Keypair) => {
+    const lenderTokenAccount = await tokenAccount(provider, p.tokenMint, lender.publicKey);
+
+    await lendingPool.methods
+      .exitPool()
+      .accountsPartial({
+        lender: lender.publicKey,
+        pool: p.pool,
+        tokenMint: p.tokenMint,
+        lenderTokenAccount,
+        poolTokenAccount: p.poolTokenAccount,
+        seniorShareMint: p.shareMint,
+        seniorShareAccount: await tokenAccount(provider, p.shareMint, lender.publicKey),
+        juniorShareMint: p.juniorShareMint,
+        juniorShareAccount: await tokenAccount(provider, p.juniorShareMint, lender.publicKey),
+        yieldStrategy,
+        ...strategyAccounts(),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lender])
+      .rpc();
+
+    return balance(lenderTokenAccount);
+  };
+
+  const realizeLoss = () =>
+    lendingPool.methods
+      .realizeStrategyLoss()
+      .accountsPartial({
+        authority: provider.wallet.publicKey,
+        pool: p.pool,
+        ...strategyAccounts(),
+        tokenMint: p.tokenMint,
+        poolTokenAccount: p.poolTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+  it("recalls deployed funds to pay an exit", async () => {
+    expect(await balance(p.poolTokenAccount)).to.equal(700 * UNIT);
+
+    expect(await exit(senior)).to.equal(1_000 * UNIT);
+
+    expect(await deployed()).to.equal(300 * UNIT);
+    expect(await balance(p.poolTokenAccount)).to.equal(0);
+  });
+
+  it("books a strategy loss as bad debt, junior absorbs it", async () => {
+    await expectError(realizeLoss(), "NoStrategyLoss");
+
+    await mockStrategy.methods
+      .simulateLoss(new BN(100 * UNIT))
+      .accountsPartial({
+        depositor: yieldStrategy,
+        strategyTokenAccount,
+        recipientTokenAccount: await tokenAccount(provider, p.tokenMint, provider.wallet.publicKey),
+        tokenMint: p.tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await realizeLoss();
+
+    let pool = await lendingPool.account.lendingPool.fetch(p.pool);
+    expect(pool.badDebt.toNumber()).to.equal(100 * UNIT);
+    expect(pool.availableLiquidity.toNumber()).to.equal(200 * UNIT);
+    expect(await deployed()).to.equal(200 * UNIT);
+
+    // no insurance fund, the whole loss is socialized
+    await lendingPool.methods
+      .absorbBadDebt()
+      .accountsPartial({
+        pool: p.pool,
+        interestRateModel: p.interestRateModel,
+        insuranceVault: null,
+        tokenMint: p.tokenMint,
+        poolTokenAccount: p.poolTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    pool = await lendingPool.account.lendingPool.fetch(p.pool);
+    expect(pool.badDebt.toNumber()).to.equal(0);
+    expect(pool.juniorDeposits.toNumber()).to.equal(200 * UNIT);
+
+    // what is left in the strategy still pays the junior lender out
+    expect(await exit(junior)).to.equal(200 * UNIT);
+    expect(await deployed()).to.equal(0);
+  });
+});